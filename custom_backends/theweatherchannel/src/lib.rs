@@ -1,40 +1,100 @@
+mod parse;
+
 use std::collections::HashMap;
 
 use scraper::Html;
 
-use weather_plugin::{export_plugin, networking};
+use weather_plugin::{export_plugin, get_conditions_sentence, get_forecast_sentence, networking};
 use weather_plugin::{WeatherCondition, WeatherData, WeatherForecast, WindData};
 use weather_plugin::custom_backend::PluginRegistrar;
 use weather_plugin::custom_backend::WeatherForecastPlugin;
-use weather_plugin::location;
 use weather_plugin::location::Coordinates;
 use weather_plugin::now;
 use weather_plugin::settings::Settings;
+use weather_plugin::weather_file::WeatherFile;
+
+use parse::{Current, Hour};
+
+const HOUR: i128 = 3_600_000;
+
+fn get_conditions(phrase: &str, precipitation_chance: u8, below_freezing: bool, weather_codes: &HashMap<String, Vec<String>>) -> weather_plugin::Result<(Vec<WeatherCondition>, String)> {
+    let id = parse::get_likely_condition_id(phrase, precipitation_chance, below_freezing);
+    let conditions = vec![WeatherCondition::new(id, weather_codes)?];
+    let sentence = get_conditions_sentence(conditions.clone());
+    Ok((conditions, sentence))
+}
 
-fn get_the_weather_channel_current(weather_soup: Html, forecast_soup: Html, air_quality_soup: Html) -> WeatherData {
-    WeatherData {
+/// Dewpoint from the Magnus formula, weather.com only shows it for the current conditions
+fn get_dewpoint(temperature: f32, humidity: u8, metric: bool) -> f32 {
+    let celsius = if metric { temperature } else { (temperature - 32.0) * 5.0 / 9.0 };
+    let gamma = (f32::from(humidity.max(1)) / 100.0).ln() + 17.62 * celsius / (243.12 + celsius);
+    let dewpoint = 243.12 * gamma / (17.62 - gamma);
+    if metric { dewpoint } else { dewpoint * 9.0 / 5.0 + 32.0 }
+}
+
+fn below_freezing(temperature: f32, metric: bool) -> bool {
+    temperature <= if metric { 0.0 } else { 32.0 }
+}
+
+/// The today page has no chance of precipitation, the first hour of the hour by hour page is the current one
+fn get_current(current: &Current, next_hour: Option<&Hour>, aqi: u8, metric: bool, weather_codes: &HashMap<String, Vec<String>>) -> weather_plugin::Result<WeatherData> {
+    let chance = next_hour.map_or(0, |hour| hour.precipitation_chance);
+    let (conditions, condition_sentence) = get_conditions(&current.phrase, chance, below_freezing(current.temperature, metric), weather_codes)?;
+    Ok(WeatherData {
         time: now() as i128,
-        temperature: 0.0,
-        min_temp: 0.0,
-        max_temp: 0.0,
+        temperature: current.temperature,
+        min_temp: current.low.unwrap_or(current.temperature).min(current.temperature),
+        max_temp: current.high.unwrap_or(current.temperature).max(current.temperature),
         wind: WindData {
-            speed: 0.0,
-            heading: 0,
+            speed: current.wind.speed,
+            heading: current.wind.heading,
         },
         raw_data: String::new(),
-        dewpoint: 0.0,
-        feels_like: 0.0,
+        dewpoint: current.dewpoint,
+        feels_like: current.feels_like,
+        aqi,
+        cloud_cover: next_hour.map_or(0, |hour| hour.cloud_cover),
+        precipitation: 0.0,
+        conditions,
+        condition_sentence,
+    })
+}
+
+fn get_hour(hour: &Hour, time: i128, metric: bool, weather_codes: &HashMap<String, Vec<String>>) -> weather_plugin::Result<WeatherData> {
+    let (conditions, condition_sentence) = get_conditions(&hour.phrase, hour.precipitation_chance, below_freezing(hour.temperature, metric), weather_codes)?;
+    Ok(WeatherData {
+        time,
+        temperature: hour.temperature,
+        min_temp: hour.temperature,
+        max_temp: hour.temperature,
+        wind: WindData {
+            speed: hour.wind.speed,
+            heading: hour.wind.heading,
+        },
+        raw_data: String::new(),
+        dewpoint: get_dewpoint(hour.temperature, hour.humidity, metric),
+        feels_like: hour.feels_like,
         aqi: 0,
-        cloud_cover: 0,
-        conditions: vec![],
-        condition_sentence: "WIP".to_string(),
+        cloud_cover: hour.cloud_cover,
+        precipitation: 0.0,
+        conditions,
+        condition_sentence,
+    })
+}
+
+fn get_page(page: &str, coordinates: [&str; 2], cookies: &HashMap<String, String>) -> weather_plugin::Result<Html> {
+    let response = networking::get_url(format!("https://weather.com/weather/{page}/l/{},{}", coordinates[0], coordinates[1]),
+                                       None, None, Some(cookies.clone()))?;
+    if response.status != 200 {
+        return Err(format!("weather.com returned status {} for the {page} page", response.status))?;
     }
+    Ok(Html::parse_document(&response.text))
 }
 
 fn get_the_weather_channel_forecast(coordinates: [&str; 2], settings: Settings) -> weather_plugin::Result<WeatherForecast> {
     let region_country = weather_plugin::location::reverse_geocode(Coordinates {
-        latitude: coordinates[0].parse().unwrap(),
-        longitude: coordinates[1].parse().unwrap()
+        latitude: coordinates[0].parse().map_err(|_| "Invalid latitude")?,
+        longitude: coordinates[1].parse().map_err(|_| "Invalid longitude")?,
     })?;
     let mut cookies = HashMap::new();
     if !settings.metric_default {
@@ -42,32 +102,39 @@ fn get_the_weather_channel_forecast(coordinates: [&str; 2], settings: Settings)
     } else {
         cookies.insert("unitOfMeasurement".to_string(), "m".to_string());
     }
-    let r1 = networking::get_url(format!("https://weather.com/weather/today/l/{},{}", &coordinates[0], &coordinates[1]),
-                                 None, None, Some(cookies.clone()))?;
-    let r2 = networking::get_url(format!("https://weather.com/weather/hourbyhour/l/{},{}", &coordinates[0], &coordinates[1]),
-                                 None, None, Some(cookies.clone()))?;
-    let r3 = networking::get_url(format!("https://weather.com/weather/air-quality/l/{},{}", &coordinates[0], &coordinates[1]) + &coordinates[0] + "," + &coordinates[1],
-                                 None, None, Some(cookies.clone()))?;
-    let weather_soup = Html::parse_document(&r1.text);
-    let forecast_soup = Html::parse_document(&r2.text);
-    let air_quality_soup = Html::parse_document(&r3.text);
-    let current = get_the_weather_channel_current(weather_soup, forecast_soup, air_quality_soup);
-    let forecast = vec![current.clone()];
-    let region = &region_country.clone()[0];
-    let country = &region_country.clone()[1];
+    let current = parse::parse_current(&get_page("today", coordinates, &cookies)?)?;
+    let hours = parse::parse_hourly(&get_page("hourbyhour", coordinates, &cookies)?)?;
+    // Air quality is optional, not every location has it
+    let aqi = get_page("air-quality", coordinates, &cookies)
+        .ok()
+        .and_then(|page| parse::parse_air_quality(&page))
+        .map_or(0, |aqi| aqi.min(u16::from(u8::MAX)) as u8);
+    let weather_file = WeatherFile::weather_codes()?;
+    let weather_codes: HashMap<String, Vec<String>> = bincode::deserialize(&weather_file.data)?;
+    let current = get_current(&current, hours.first(), aqi, settings.metric_default, &weather_codes)?;
+    // The hour by hour page starts with the current hour
+    let start = current.time / HOUR * HOUR;
+    let mut forecast = vec![current.clone()];
+    for (i, hour) in hours.iter().enumerate().skip(1) {
+        forecast.push(get_hour(hour, start + i as i128 * HOUR, settings.metric_default, &weather_codes)?);
+    }
+    let forecast_sentence = get_forecast_sentence(&forecast);
     Ok(WeatherForecast {
-        region: region.to_string(),
-        country: country.to_string(),
+        region: region_country[0].to_string(),
+        country: region_country[1].to_string(),
         forecast,
         current_weather: current,
-        forecast_sentence: "WIP".to_string(),
+        forecast_sentence,
+        daily: Vec::new(),
+        alerts: Vec::new(),
         raw_data: None,
+        utc_offset: None,
     })
 }
 
 export_plugin!(register);
 
-extern "C" fn register(registrar: &mut dyn PluginRegistrar) {
+fn register(registrar: &mut dyn PluginRegistrar) {
     registrar.register_function("theweatherchannel", Box::new(TheWeatherChannel));
 }
 
@@ -0,0 +1,365 @@
+//! Extracts weather data from the pages of weather.com.
+//! Values are in the units selected by the `unitOfMeasurement` cookie.
+
+use scraper::{ElementRef, Html, Selector};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wind {
+    pub speed: f64,
+    pub heading: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Current {
+    pub temperature: f32,
+    /// Missing in the evening, when the day is over
+    pub high: Option<f32>,
+    pub low: Option<f32>,
+    pub feels_like: f32,
+    pub dewpoint: f32,
+    pub wind: Wind,
+    pub phrase: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hour {
+    pub temperature: f32,
+    pub feels_like: f32,
+    pub humidity: u8,
+    pub cloud_cover: u8,
+    pub precipitation_chance: u8,
+    pub wind: Wind,
+    pub phrase: String,
+}
+
+const COMPASS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+fn selector(selector: &str) -> Selector {
+    Selector::parse(selector).expect("invalid selector")
+}
+
+/// Text nodes are separated by spaces, icons contain titles like "Wind Direction"
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+fn select_text(element: ElementRef, query: &str) -> Option<String> {
+    element.select(&selector(query)).next().map(text)
+}
+
+/// Parses the first number in the text, "--" is used by weather.com for missing values
+fn parse_number(text: &str) -> Option<f32> {
+    let start = text.find(|c: char| c.is_ascii_digit() || c == '-')?;
+    let number: String = text[start..]
+        .chars()
+        .enumerate()
+        .take_while(|(i, c)| c.is_ascii_digit() || *c == '.' || (*i == 0 && *c == '-'))
+        .map(|(_, c)| c)
+        .collect();
+    number.parse().ok()
+}
+
+fn parse_percentage(text: &str) -> Option<u8> {
+    parse_number(text).map(|n| n.clamp(0.0, 100.0) as u8)
+}
+
+fn require<T>(value: Option<T>, name: &str) -> weather_plugin::Result<T> {
+    Ok(value.ok_or(format!("Failed to find the {name} on weather.com"))?)
+}
+
+/// Parses text like "WNW 12 mph", "Calm" has no speed or heading
+pub fn parse_wind(text: &str) -> Wind {
+    let heading = text
+        .split_whitespace()
+        .find_map(|word| COMPASS.iter().position(|point| *point == word))
+        .map_or(0, |i| (i as f32 * 22.5).round() as u16);
+    Wind {
+        speed: parse_number(text).map_or(0.0, f64::from),
+        heading,
+    }
+}
+
+/// Value of an entry in the details list of the today page, by its label
+fn get_detail(today: &Html, label: &str) -> Option<String> {
+    let label_selector = selector("[data-testid=WeatherDetailsLabel]");
+    today
+        .select(&selector("[data-testid=WeatherDetailsListItem]"))
+        .find(|item| {
+            item.select(&label_selector)
+                .next()
+                .map_or(false, |l| text(l).eq_ignore_ascii_case(label))
+        })
+        .and_then(|item| select_text(item, "[data-testid=wxData]"))
+}
+
+/// Current conditions from the today page
+pub fn parse_current(today: &Html) -> weather_plugin::Result<Current> {
+    let container = require(
+        today
+            .select(&selector("[data-testid=CurrentConditionsContainer]"))
+            .next(),
+        "current conditions",
+    )?;
+    let temperature = require(
+        select_text(container, "[data-testid=TemperatureValue]").and_then(|t| parse_number(&t)),
+        "temperature",
+    )?;
+    let phrase = select_text(container, "[data-testid=wxPhrase]").unwrap_or_default();
+    let (high, low) = get_detail(today, "High / Low").map_or((None, None), |t| {
+        let mut parts = t.splitn(2, '/');
+        (
+            parts.next().and_then(parse_number),
+            parts.next().and_then(parse_number),
+        )
+    });
+    let feels_like = select_text(
+        today.root_element(),
+        "[data-testid=TodaysDetailsModule] [data-testid=TemperatureValue]",
+    )
+    .and_then(|t| parse_number(&t))
+    .unwrap_or(temperature);
+    let dewpoint = get_detail(today, "Dew Point")
+        .and_then(|t| parse_number(&t))
+        .unwrap_or(temperature);
+    let wind = get_detail(today, "Wind").map_or(
+        Wind {
+            speed: 0.0,
+            heading: 0,
+        },
+        |t| parse_wind(&t),
+    );
+    Ok(Current {
+        temperature,
+        high,
+        low,
+        feels_like,
+        dewpoint,
+        wind,
+        phrase,
+    })
+}
+
+fn parse_hour(hour: ElementRef) -> weather_plugin::Result<Hour> {
+    let temperature = require(
+        select_text(
+            hour,
+            "[data-testid=detailsTemperature] [data-testid=TemperatureValue]",
+        )
+        .and_then(|t| parse_number(&t)),
+        "hourly temperature",
+    )?;
+    let section = |name: &str, value: &str| {
+        select_text(hour, &format!("[data-testid={name}] [data-testid={value}]"))
+    };
+    Ok(Hour {
+        temperature,
+        feels_like: section("FeelsLikeSection", "TemperatureValue")
+            .and_then(|t| parse_number(&t))
+            .unwrap_or(temperature),
+        humidity: section("HumiditySection", "PercentageValue")
+            .and_then(|t| parse_percentage(&t))
+            .unwrap_or(0),
+        cloud_cover: section("CloudCoverSection", "PercentageValue")
+            .and_then(|t| parse_percentage(&t))
+            .unwrap_or(0),
+        precipitation_chance: section("Precip", "PercentageValue")
+            .and_then(|t| parse_percentage(&t))
+            .unwrap_or(0),
+        wind: parse_wind(&select_text(hour, "[data-testid=Wind]").unwrap_or_default()),
+        phrase: select_text(hour, "[data-testid=DetailsSummary] svg title").unwrap_or_default(),
+    })
+}
+
+/// Hours from the hour by hour page, starting with the current hour
+pub fn parse_hourly(hourly: &Html) -> weather_plugin::Result<Vec<Hour>> {
+    hourly
+        .select(&selector("details[data-testid=ExpandedDetailsCard]"))
+        .map(parse_hour)
+        .collect()
+}
+
+/// US AQI from the air quality page
+pub fn parse_air_quality(air_quality: &Html) -> Option<u16> {
+    air_quality
+        .select(&selector("text[data-testid=DonutChartValue]"))
+        .next()
+        .and_then(|value| parse_number(&text(value)))
+        .map(|aqi| aqi.max(0.0) as u16)
+}
+
+/// Maps a weather.com condition phrase to the closest OpenWeatherMap condition id
+pub fn get_condition_id(phrase: &str) -> u16 {
+    let phrase = phrase.to_lowercase();
+    let has = |word: &str| phrase.contains(word);
+    if has("thunder") || has("t-storm") {
+        211
+    } else if has("tornado") {
+        781
+    } else if has("freezing rain") || has("freezing drizzle") {
+        511
+    } else if has("wintry mix") || (has("snow") && has("rain")) {
+        616
+    } else if has("sleet") {
+        611
+    } else if has("heavy snow") || has("blizzard") {
+        602
+    } else if has("light snow") || has("flurries") {
+        600
+    } else if has("snow") {
+        601
+    } else if has("drizzle") {
+        300
+    } else if has("heavy rain") {
+        502
+    } else if has("shower") {
+        521
+    } else if has("light rain") {
+        500
+    } else if has("rain") {
+        501
+    } else if has("fog") {
+        741
+    } else if has("haze") {
+        721
+    } else if has("smoke") {
+        711
+    } else if has("dust") || has("sand") {
+        761
+    } else if has("mostly cloudy") {
+        803
+    } else if has("partly cloudy") || has("partly sunny") {
+        802
+    } else if has("mostly sunny") || has("mostly clear") || has("fair") {
+        801
+    } else if has("cloudy") || has("overcast") {
+        804
+    } else {
+        800
+    }
+}
+
+/// Like `get_condition_id`, but a likely chance of precipitation turns a dry phrase
+/// (e.g. "Cloudy" at 60%) into light rain or snow, so the forecast sentence mentions it
+pub fn get_likely_condition_id(
+    phrase: &str,
+    precipitation_chance: u8,
+    below_freezing: bool,
+) -> u16 {
+    let id = get_condition_id(phrase);
+    if precipitation_chance < 50 || matches!(id / 100, 2 | 3 | 5 | 6) {
+        id
+    } else if below_freezing {
+        600
+    } else {
+        500
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Html {
+        let text = match name {
+            "today" => include_str!("../fixtures/today.html"),
+            "hourbyhour" => include_str!("../fixtures/hourbyhour.html"),
+            "air_quality" => include_str!("../fixtures/air_quality.html"),
+            _ => unreachable!(),
+        };
+        Html::parse_document(text)
+    }
+
+    #[test]
+    fn current() {
+        let current = parse_current(&fixture("today")).unwrap();
+        assert_eq!(
+            current,
+            Current {
+                temperature: 61.0,
+                high: Some(66.0),
+                low: Some(53.0),
+                feels_like: 59.0,
+                dewpoint: 53.0,
+                wind: Wind {
+                    speed: 12.0,
+                    heading: 293,
+                },
+                phrase: "Partly Cloudy".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn hourly() {
+        let hours = parse_hourly(&fixture("hourbyhour")).unwrap();
+        assert_eq!(hours.len(), 3);
+        assert_eq!(
+            hours[0],
+            Hour {
+                temperature: 62.0,
+                feels_like: 61.0,
+                humidity: 72,
+                cloud_cover: 41,
+                precipitation_chance: 7,
+                wind: Wind {
+                    speed: 13.0,
+                    heading: 270,
+                },
+                phrase: "Partly Cloudy".to_string(),
+            }
+        );
+        assert_eq!(hours[1].wind.heading, 248);
+        assert_eq!(hours[2].phrase, "Light Rain");
+        assert_eq!(
+            hours[2].wind,
+            Wind {
+                speed: 0.0,
+                heading: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn air_quality() {
+        assert_eq!(parse_air_quality(&fixture("air_quality")), Some(42));
+        assert_eq!(parse_air_quality(&fixture("today")), None);
+    }
+
+    #[test]
+    fn missing_values() {
+        assert_eq!(parse_number("--"), None);
+        assert_eq!(parse_number("-3°"), Some(-3.0));
+        assert!(parse_current(&fixture("air_quality")).is_err());
+    }
+
+    #[test]
+    fn condition_ids() {
+        assert_eq!(get_condition_id("Partly Cloudy"), 802);
+        assert_eq!(get_condition_id("Light Rain"), 500);
+        assert_eq!(get_condition_id("Scattered Thunderstorms"), 211);
+        assert_eq!(get_condition_id("Rain/Snow Showers"), 616);
+        assert_eq!(get_condition_id("Sunny"), 800);
+    }
+
+    #[test]
+    fn likely_condition_ids() {
+        let hours = parse_hourly(&fixture("hourbyhour")).unwrap();
+        let ids: Vec<u16> = hours
+            .iter()
+            .map(|hour| get_likely_condition_id(&hour.phrase, hour.precipitation_chance, false))
+            .collect();
+        assert_eq!(ids, [802, 803, 500]);
+        assert_eq!(get_likely_condition_id("Cloudy", 60, false), 500);
+        assert_eq!(get_likely_condition_id("Cloudy", 60, true), 600);
+        assert_eq!(get_likely_condition_id("Snow Showers", 60, false), 601);
+        assert_eq!(get_likely_condition_id("Thunderstorms", 10, false), 211);
+    }
+}
@@ -2,101 +2,13 @@ use std::collections::HashMap;
 
 use regex::Regex;
 
-use cli::layout::layout_serde::{ItemSerde, LayoutDefaultsSerde, LayoutSerde};
+use cli::layout::layout_serde::{ItemSerde, LayoutDefaultsSerde, LayoutSerde, RowSerde};
+use cli::layout::source::parse_shorthand as string_to_item;
 
 fn strip(line: &str) -> &str { // TODO: Fix function (its actually good for now, but it can be better)
     return line.trim_end();
 }
 
-pub fn string_to_item(s: &str) -> ItemSerde {
-    let mut new_s: String = s.to_string();
-    if !new_s.is_empty() {
-        let mut color = None;
-        if new_s.chars().next().expect("0th char expected") == '$' {
-            new_s = new_s[1..].to_string();
-            let mut tmp_color = String::default();
-            while new_s.chars().next().expect("ending $ not found") != '$' {
-                tmp_color += &new_s.chars().next().unwrap().to_string();
-                new_s = new_s[1..].to_string()
-            }
-            new_s = new_s[1..].to_string();
-            color = Some(tmp_color);
-        }
-        if new_s.chars().next().expect("Oth char expected") == '@' {
-            new_s = new_s[1..].to_string();
-            let splt: Vec<&str> = new_s.split('|').collect();
-            let mut metric: Option<String> = None;
-            let mut imperial: Option<String> = None;
-            if splt.len() == 2 {
-                metric = Some(splt[1].to_string());
-                imperial = Some(splt[1].to_string());
-            } else if splt.len() == 3 {
-                imperial = Some(splt[1].to_string());
-                metric = Some(splt[2].to_string());
-            }
-            return ItemSerde {
-                item_type: "variable".to_string(),
-                color,
-                bg_color: None,
-                metric,
-                imperial,
-                unit_color: None,
-                value: splt[0].to_string(),
-                args: None,
-                kwargs: None,
-                scale: None,
-            };
-        } else if new_s.chars().next().expect("Oth char expected") == '#' {
-            new_s = new_s[1..].to_string();
-            let mut split: Vec<&str> = new_s.split('|').collect();
-            let value = split[0];
-            split.remove(0);
-            let mut args: Vec<ItemSerde> = Vec::new();
-            let mut kwargs: HashMap<String, ItemSerde> = HashMap::new();
-            for item in split {
-                if item.contains('=') {
-                    let temp_item = item.to_string();
-                    let kwarg: Vec<&str> = temp_item.split('=').collect();
-                    kwargs.insert(
-                        kwarg[0].to_string(),
-                        string_to_item(kwarg[1]),
-                    );
-                } else {
-                    args.push(string_to_item(item));
-                }
-            }
-            let item: ItemSerde = ItemSerde {
-                item_type: "function".to_string(),
-                color,
-                bg_color: None,
-                metric: None,
-                imperial: None,
-                unit_color: None,
-                value: value.to_string(),
-                args: Some(args),
-                kwargs: Some(kwargs),
-                scale: None,
-            };
-            return item;
-        } else if new_s.chars().next().expect("Oth char expected") == '\\' {
-            new_s = new_s[1..].to_string();
-        }
-    }
-    ItemSerde {
-        item_type: "text".to_string(),
-        color: None,
-        bg_color: None,
-        metric: None,
-        imperial: None,
-        unit_color: None,
-        value: new_s,
-        args: None,
-        kwargs: None,
-        scale: None,
-    }
-}
-
-
 fn string_to_row(s: String) -> Vec<ItemSerde> {
     let mut item_list = Vec::new();
     let mut previous_char = '\0';
@@ -119,7 +31,7 @@ fn string_to_row(s: String) -> Vec<ItemSerde> {
 
 pub fn compile_layout(s: String) -> weather_error::Result<LayoutSerde> {
     let lines: Vec<&str> = s.split("\n").collect();
-    let mut rows: Vec<Vec<ItemSerde>> = Vec::new();
+    let mut rows: Vec<RowSerde> = Vec::new();
     let mut is_layout = false;
     let mut variables: HashMap<&str, String> = HashMap::new();
     for line in lines {
@@ -128,7 +40,7 @@ pub fn compile_layout(s: String) -> weather_error::Result<LayoutSerde> {
             is_layout = true;
         }
         else if is_layout {
-            rows.push(string_to_row(stripped_line.to_string()));
+            rows.push(RowSerde::Items(string_to_row(stripped_line.to_string())));
         }
         else {
             let variable = Regex::new(r#"\w*=\w*"#).unwrap();
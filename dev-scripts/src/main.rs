@@ -6,7 +6,7 @@ use std::io::Write;
 use clap::{Args, Parser, Subcommand};
 
 use crate::layout::compile_layout;
-use crate::update_hash::update_hash;
+use crate::update_hash::{update_hash, update_layout_hashes};
 
 mod update_docs;
 mod update_hash;
@@ -67,6 +67,11 @@ fn build_docs() -> weather_error::Result<()> {
     fs::copy("./docs_templates/weather_ascii_images.res", "./docs/weather_ascii_images.res")?;
     fs::copy("./docs_templates/weather_codes.res", "./docs/weather_codes.res")?;
     fs::copy("./docs_templates/default_layout.res", "./docs/default_layout.res")?;
+    fs::create_dir_all("./docs/layouts")?;
+    for file in fs::read_dir("./docs_templates/layouts")? {
+        let file = file?;
+        fs::copy(file.path(), working_dir.join("docs").join("layouts").join(file.file_name()))?;
+    }
     println!("Done!");
     Ok(())
 }
@@ -110,6 +115,7 @@ fn index_hashes() -> weather_error::Result<()> {
     update_hash("./docs_templates/weather", "weather-exe-hash-unix")?;
     update_hash("./docs_templates/weatherd.exe", "weatherd-exe-hash-windows")?;
     update_hash("./docs_templates/weatherd", "weatherd-exe-hash-unix")?;
+    update_layout_hashes()?;
     Ok(())
 }
 
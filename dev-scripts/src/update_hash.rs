@@ -12,4 +12,17 @@ pub fn update_hash(file: &str, key: &str) -> weather_error::Result<()> {
     let data = serde_json::to_string_pretty(&index_json)?;
     fs::OpenOptions::new().write(true).truncate(true).open("./docs_templates/index.json")?.write_all(data.as_bytes())?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Updates the hashes of the layouts in the layout gallery index
+pub fn update_layout_hashes() -> weather_error::Result<()> {
+    let index_path = "./docs_templates/layouts/index.json";
+    let mut index: Value = serde_json::from_slice(&fs::read(index_path)?)?;
+    for layout in index["layouts"].as_array_mut().ok_or("layouts/index.json has no layouts")? {
+        let file = layout["file"].as_str().ok_or("A layout in layouts/index.json has no file")?;
+        let file_hash = local::hash_file(&format!("./docs_templates/layouts/{file}"))?;
+        layout["hash"] = Value::String(file_hash);
+    }
+    fs::write(index_path, serde_json::to_string_pretty(&index)?)?;
+    Ok(())
+}
@@ -0,0 +1,372 @@
+use crate::WeatherData;
+
+const HOUR: f64 = 3_600_000.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Precipitation {
+    None,
+    Rain,
+    Snow,
+}
+
+impl Precipitation {
+    fn noun(self) -> &'static str {
+        match self {
+            Self::None => "be clear",
+            Self::Rain => "rain",
+            Self::Snow => "snow",
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            Self::None => "being clear",
+            Self::Rain => "raining",
+            Self::Snow => "snowing",
+        }
+    }
+}
+
+fn has_condition(data: &WeatherData, predicate: impl Fn(u16) -> bool) -> bool {
+    data.conditions
+        .iter()
+        .any(|condition| predicate(condition.condition_id))
+}
+
+fn get_precipitation(data: &WeatherData) -> Precipitation {
+    if has_condition(data, |id| id / 100 == 6) {
+        Precipitation::Snow
+    } else if has_condition(data, |id| matches!(id / 100, 2 | 3 | 5)) {
+        Precipitation::Rain
+    } else {
+        Precipitation::None
+    }
+}
+
+/// Hours since the first period for each entry.
+/// Backends that don't provide increasing timestamps are assumed to be hourly.
+fn get_offsets(forecast: &[WeatherData]) -> Vec<f64> {
+    let increasing = forecast.len() > 1 && forecast.windows(2).all(|w| w[1].time > w[0].time);
+    if increasing {
+        let start = forecast[0].time;
+        forecast
+            .iter()
+            .map(|data| ((data.time - start) as f64 / HOUR).max(0.0))
+            .collect()
+    } else {
+        (0..forecast.len()).map(|i| i as f64).collect()
+    }
+}
+
+/// The offset at which the period at `index` ends (or `index` starts)
+fn end_offset(offsets: &[f64], index: usize) -> f64 {
+    if index < offsets.len() {
+        return offsets[index];
+    }
+    let len = offsets.len();
+    let step = if len > 1 {
+        offsets[len - 1] - offsets[len - 2]
+    } else {
+        1.0
+    };
+    offsets[len - 1] + step
+}
+
+fn format_hours(hours: f64) -> String {
+    let rounded = hours.round() as u64;
+    if rounded == 0 {
+        "less than an hour".to_string()
+    } else if rounded == 1 {
+        "1 hour".to_string()
+    } else if rounded < 48 {
+        format!("{rounded} hours")
+    } else {
+        format!("{} days", (hours / 24.0).round() as u64)
+    }
+}
+
+fn describe_precipitation(forecast: &[WeatherData], offsets: &[f64]) -> String {
+    let kinds: Vec<Precipitation> = forecast.iter().map(get_precipitation).collect();
+    let horizon = end_offset(offsets, kinds.len());
+    let first = kinds[0];
+    if first != Precipitation::None {
+        let end = kinds
+            .iter()
+            .position(|&k| k != first)
+            .unwrap_or(kinds.len());
+        if end == kinds.len() {
+            return format!(
+                "It will continue {} for at least the next {}.",
+                first.verb(),
+                format_hours(horizon)
+            );
+        }
+        return format!(
+            "It will continue {} for {}.",
+            first.verb(),
+            format_hours(end_offset(offsets, end))
+        );
+    }
+    match kinds.iter().position(|&k| k != Precipitation::None) {
+        None => format!(
+            "Conditions are predicted to be clear for the next {}.",
+            format_hours(horizon)
+        ),
+        Some(start) => {
+            let kind = kinds[start];
+            let end = kinds[start..]
+                .iter()
+                .position(|&k| k != kind)
+                .map_or(kinds.len(), |p| p + start);
+            format!(
+                "It will {} in {} for {}.",
+                kind.noun(),
+                format_hours(offsets[start]),
+                format_hours(end_offset(offsets, end) - offsets[start])
+            )
+        }
+    }
+}
+
+/// Indexes of the periods within the next day
+fn next_day(offsets: &[f64]) -> usize {
+    offsets.iter().take_while(|&&o| o <= 24.0).count()
+}
+
+fn describe_temperature(forecast: &[WeatherData], offsets: &[f64]) -> Option<String> {
+    let window = &forecast[..next_day(offsets)];
+    let current = window[0].temperature;
+    let (max_index, max) = window
+        .iter()
+        .map(|d| d.temperature)
+        .enumerate()
+        .reduce(|best, x| if x.1 > best.1 { x } else { best })?;
+    let (min_index, min) = window
+        .iter()
+        .map(|d| d.temperature)
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    let rise = max - current;
+    let drop = current - min;
+    if rise >= drop && rise >= 5.0 {
+        Some(format!(
+            "Temperatures will rise to {max:.0}° in {}.",
+            format_hours(offsets[max_index])
+        ))
+    } else if drop >= 5.0 {
+        Some(format!(
+            "Temperatures will drop to {min:.0}° in {}.",
+            format_hours(offsets[min_index])
+        ))
+    } else {
+        Some(format!(
+            "Temperatures will stay around {current:.0}° for the next {}.",
+            format_hours(end_offset(offsets, window.len()))
+        ))
+    }
+}
+
+fn describe_wind(forecast: &[WeatherData], offsets: &[f64]) -> Option<String> {
+    let window = &forecast[..next_day(offsets)];
+    let current = window[0].wind.speed;
+    let (max_index, max) = window
+        .iter()
+        .map(|d| d.wind.speed)
+        .enumerate()
+        .reduce(|best, x| if x.1 > best.1 { x } else { best })?;
+    if max >= current * 1.5 && max - current >= 5.0 {
+        return Some(format!(
+            "Winds will pick up to {max:.0} in {}.",
+            format_hours(offsets[max_index])
+        ));
+    }
+    let calm = window.iter().position(|d| d.wind.speed <= current / 2.0);
+    match calm {
+        Some(index) if current >= 10.0 => Some(format!(
+            "Winds will die down in {}.",
+            format_hours(offsets[index])
+        )),
+        _ => None,
+    }
+}
+
+/// A notable condition, described differently when it's already happening
+struct Event {
+    current: &'static str,
+    future: &'static str,
+    matches: fn(u16) -> bool,
+}
+
+const EVENTS: [Event; 4] = [
+    Event {
+        current: "A tornado has been reported.",
+        future: "A tornado is possible in",
+        matches: |id| id == 781,
+    },
+    Event {
+        current: "Squalls are occurring.",
+        future: "Squalls are expected in",
+        matches: |id| id == 771,
+    },
+    Event {
+        current: "Thunderstorms are occurring.",
+        future: "Thunderstorms are expected in",
+        matches: |id| id / 100 == 2,
+    },
+    Event {
+        current: "It is foggy.",
+        future: "Fog is expected in",
+        matches: |id| id == 741,
+    },
+];
+
+fn describe_events(forecast: &[WeatherData], offsets: &[f64]) -> Option<String> {
+    for event in &EVENTS {
+        if let Some(index) = forecast
+            .iter()
+            .position(|d| has_condition(d, event.matches))
+        {
+            return Some(if index == 0 {
+                event.current.to_string()
+            } else {
+                format!("{} {}.", event.future, format_hours(offsets[index]))
+            });
+        }
+    }
+    None
+}
+
+/// Summarizes a normalized forecast (the first item being the current weather) into a few sentences
+/// describing precipitation, temperature trends, wind changes and notable events.
+/// A forecast with only the current weather is described as it is for the next hour.
+pub fn get_forecast_sentence(forecast: &[WeatherData]) -> String {
+    if forecast.is_empty() {
+        return String::new();
+    }
+    let offsets = get_offsets(forecast);
+    let mut sentences = vec![describe_precipitation(forecast, &offsets)];
+    sentences.extend(describe_events(forecast, &offsets));
+    sentences.extend(describe_temperature(forecast, &offsets));
+    sentences.extend(describe_wind(forecast, &offsets));
+    sentences.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{WeatherCondition, WindData};
+
+    /// An hour of weather at `hour` hours after the start
+    fn hour(hour: i128, temperature: f32, wind: f64, condition_id: u16) -> WeatherData {
+        WeatherData {
+            time: 1_683_381_600_000 + hour * HOUR as i128,
+            temperature,
+            min_temp: temperature,
+            max_temp: temperature,
+            wind: WindData {
+                speed: wind,
+                heading: 0,
+            },
+            raw_data: String::new(),
+            dewpoint: temperature,
+            feels_like: temperature,
+            aqi: 0,
+            cloud_cover: 0,
+            precipitation: 0.0,
+            conditions: vec![WeatherCondition {
+                condition_id,
+                image_url: String::new(),
+                sentence: String::new(),
+                image_ascii: String::new(),
+            }],
+            condition_sentence: String::new(),
+        }
+    }
+
+    fn series(conditions: &[u16]) -> Vec<WeatherData> {
+        conditions
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| hour(i as i128, 60.0, 5.0, id))
+            .collect()
+    }
+
+    #[test]
+    fn precipitation() {
+        assert_eq!(
+            get_forecast_sentence(&series(&[800, 800, 801])),
+            "Conditions are predicted to be clear for the next 3 hours. \
+             Temperatures will stay around 60° for the next 3 hours."
+        );
+        let starting = get_forecast_sentence(&series(&[800, 800, 500, 501, 800]));
+        assert!(starting.starts_with("It will rain in 2 hours for 2 hours."));
+        let snowing = get_forecast_sentence(&series(&[600, 601, 800]));
+        assert!(snowing.starts_with("It will continue snowing for 2 hours."));
+        let raining = get_forecast_sentence(&series(&[500, 500]));
+        assert!(raining.starts_with("It will continue raining for at least the next 2 hours."));
+    }
+
+    #[test]
+    fn uneven_periods() {
+        // An hourly start followed by 3 hour periods, like the openweathermap forecast
+        let mut forecast = series(&[800, 800, 800, 500]);
+        for (i, data) in forecast.iter_mut().enumerate().skip(2) {
+            data.time = hour(1 + 3 * (i as i128 - 1), 0.0, 0.0, 800).time;
+        }
+        let sentence = get_forecast_sentence(&forecast);
+        assert!(sentence.starts_with("It will rain in 7 hours for 3 hours."));
+        // The offsets of the first entry are compared too
+        forecast[0].time = forecast[1].time;
+        assert!(get_forecast_sentence(&forecast).starts_with("It will rain in 3 hours for"));
+    }
+
+    #[test]
+    fn temperature() {
+        let mut forecast = series(&[800; 6]);
+        forecast[4].temperature = 70.0;
+        let sentence = get_forecast_sentence(&forecast);
+        assert!(sentence.ends_with("Temperatures will rise to 70° in 4 hours."));
+        forecast[4].temperature = 52.0;
+        let sentence = get_forecast_sentence(&forecast);
+        assert!(sentence.ends_with("Temperatures will drop to 52° in 4 hours."));
+        // Only the next day counts
+        let mut forecast = series(&[800; 30]);
+        forecast[28].temperature = 90.0;
+        let sentence = get_forecast_sentence(&forecast);
+        assert!(sentence.ends_with("Temperatures will stay around 60° for the next 25 hours."));
+    }
+
+    #[test]
+    fn wind() {
+        let mut forecast = series(&[800; 6]);
+        forecast[3].wind.speed = 20.0;
+        let sentence = get_forecast_sentence(&forecast);
+        assert!(sentence.ends_with("Winds will pick up to 20 in 3 hours."));
+        for (i, data) in forecast.iter_mut().enumerate() {
+            data.wind.speed = if i < 2 { 16.0 } else { 6.0 };
+        }
+        let sentence = get_forecast_sentence(&forecast);
+        assert!(sentence.ends_with("Winds will die down in 2 hours."));
+    }
+
+    #[test]
+    fn events() {
+        let sentence = get_forecast_sentence(&series(&[741, 800, 800]));
+        assert!(sentence.contains(" It is foggy. "));
+        let sentence = get_forecast_sentence(&series(&[800, 741, 211]));
+        assert!(sentence.contains(" Thunderstorms are expected in 2 hours. "));
+        assert!(!sentence.contains("Fog"));
+        let sentence = get_forecast_sentence(&series(&[800, 800, 800, 781]));
+        assert!(sentence.contains(" A tornado is possible in 3 hours. "));
+    }
+
+    #[test]
+    fn current_only() {
+        assert_eq!(get_forecast_sentence(&[]), "");
+        assert_eq!(
+            get_forecast_sentence(&series(&[501])),
+            "It will continue raining for at least the next 1 hour. \
+             Temperatures will stay around 60° for the next 1 hour."
+        );
+    }
+}
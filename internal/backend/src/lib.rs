@@ -1,16 +1,20 @@
 use serde::{Deserialize, Serialize};
 
+mod forecast_sentence;
 pub mod meteo;
+pub mod metno;
 pub mod nws;
 pub mod openweathermap;
 pub mod openweathermap_onecall;
 mod openweathermap_shared;
+mod time;
 mod weather_condition;
 mod weather_data;
 mod weather_forecast;
+pub use forecast_sentence::get_forecast_sentence;
 pub use weather_condition::WeatherCondition;
 pub use weather_data::{get_conditions_sentence, WeatherData};
-pub use weather_forecast::WeatherForecast;
+pub use weather_forecast::{DailyForecast, WeatherAlert, WeatherForecast};
 
 pub type Result<T> = std::result::Result<T, weather_error::Error>;
 
@@ -19,3 +23,11 @@ pub struct WindData {
     pub speed: f64,
     pub heading: u16,
 }
+
+/// The bodies of the responses of a weather provider, kept in `WeatherForecast::raw_data`
+pub(crate) fn raw_responses(responses: &[networking::Resp]) -> Vec<String> {
+    responses
+        .iter()
+        .map(|resp| String::from_utf8_lossy(&resp.bytes).into_owned())
+        .collect()
+}
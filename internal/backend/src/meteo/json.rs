@@ -9,6 +9,8 @@ pub struct MeteoHourlyJson {
     pub rain: Vec<f32>,
     pub showers: Vec<f32>,
     pub snowfall: Vec<f32>,
+    #[serde(default)]
+    pub precipitation: Vec<f32>,
     pub cloudcover: Vec<u8>,
     pub dewpoint_2m: Vec<f32>,
     pub apparent_temperature: Vec<f32>,
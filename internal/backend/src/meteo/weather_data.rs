@@ -1,8 +1,8 @@
 use crate::meteo::json::{MeteoAirQualityJson, MeteoForecastJson};
+use crate::time::parse_iso8601;
 use crate::WeatherCondition;
 use crate::WindData;
 use crate::{get_conditions_sentence, WeatherData};
-use local::now;
 use std::collections::HashMap;
 
 pub fn get_weather_data(
@@ -14,15 +14,30 @@ pub fn get_weather_data(
 ) -> crate::Result<WeatherData> {
     let cloud_cover = data.hourly.cloudcover[index];
     let conditions = get_conditions(data.clone(), metric, index, cloud_cover, weather_codes)?;
+    let is_current = data.hourly.time[index] == data.current_weather.time;
+    let (temperature, wind) = if is_current {
+        (
+            data.current_weather.temperature,
+            WindData {
+                speed: data.current_weather.windspeed,
+                heading: data.current_weather.winddirection as u16,
+            },
+        )
+    } else {
+        (
+            data.hourly.temperature_2m[index],
+            WindData {
+                speed: f64::from(data.hourly.windspeed_10m[index]),
+                heading: data.hourly.winddirection_10m[index] as u16,
+            },
+        )
+    };
     let d = WeatherData {
-        time: now() as i128,
-        temperature: data.current_weather.temperature,
+        time: parse_iso8601(&data.hourly.time[index], data.utc_offset_seconds)?,
+        temperature,
         min_temp: data.daily.temperature_2m_min[index / 24],
         max_temp: data.daily.temperature_2m_max[index / 24],
-        wind: WindData {
-            speed: data.current_weather.windspeed,
-            heading: data.current_weather.winddirection as u16,
-        },
+        wind,
         raw_data: simd_json::to_string_pretty(&data)?,
         dewpoint: data.hourly.dewpoint_2m[index],
         feels_like: data.hourly.apparent_temperature[index],
@@ -33,12 +48,23 @@ pub fn get_weather_data(
             .unwrap_or(&Some(0))
             .unwrap_or(0_u8),
         cloud_cover,
+        precipitation: get_precipitation(&data, index, metric),
         conditions: conditions.clone(),
         condition_sentence: get_conditions_sentence(conditions),
     };
     Ok(d)
 }
 
+/// Open-Meteo gives inches with imperial units
+fn get_precipitation(data: &MeteoForecastJson, index: usize, metric: bool) -> f32 {
+    let precipitation = data.hourly.precipitation.get(index).copied().unwrap_or(0.0);
+    if metric {
+        precipitation
+    } else {
+        precipitation * 25.4
+    }
+}
+
 fn get_conditions(
     data: MeteoForecastJson,
     metric: bool,
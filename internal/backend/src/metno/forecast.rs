@@ -0,0 +1,97 @@
+use crate::metno::get_combined_data_formatted;
+use crate::metno::weather_data::{get_daily, get_weather_data};
+use crate::time::parse_iso8601;
+use crate::WeatherForecast;
+use crate::{get_forecast_sentence, WeatherData};
+use local::location;
+use local::now;
+use local::settings::Settings;
+use local::weather_file::WeatherFile;
+use location::Coordinates;
+use std::collections::HashMap;
+
+fn get_day(time: &str) -> &str {
+    time.get(..10).unwrap_or(time)
+}
+
+/// The forecast is hourly for as long as met.no provides hourly data (about two and a half days),
+/// the 6-hourly periods after that are only summarized in the daily forecast.
+pub fn get_forecast(
+    coordinates: Coordinates,
+    settings: Settings,
+) -> crate::Result<WeatherForecast> {
+    let data = get_combined_data_formatted(coordinates)?;
+    let timeseries = data.weather.properties.timeseries;
+    if timeseries.is_empty() {
+        Err("met.no returned an empty forecast")?;
+    }
+    let weather_file = WeatherFile::weather_codes()?;
+    let weather_codes: HashMap<String, Vec<String>> = bincode::deserialize(&weather_file.data)?;
+    // Daily extremes keyed by the UTC date
+    let mut extremes: HashMap<&str, (f64, f64)> = HashMap::new();
+    for entry in &timeseries {
+        let temperature = entry.data.instant.details.air_temperature;
+        let day = extremes
+            .entry(get_day(&entry.time))
+            .or_insert((temperature, temperature));
+        day.0 = day.0.min(temperature);
+        day.1 = day.1.max(temperature);
+    }
+    // A cached response can be a few hours old, skip the periods that are already over
+    let now = now() as i128;
+    let mut start = 0;
+    for (index, entry) in timeseries.iter().enumerate().skip(1) {
+        if parse_iso8601(&entry.time, 0)? > now {
+            break;
+        }
+        start = index;
+    }
+    let mut forecast: Vec<WeatherData> = Vec::new();
+    let hourly = timeseries[start..]
+        .iter()
+        .enumerate()
+        .take_while(|(index, entry)| *index == 0 || entry.data.next_1_hours.is_some());
+    for (_, entry) in hourly {
+        let (min_temp, max_temp) = extremes[get_day(&entry.time)];
+        forecast.push(get_weather_data(
+            entry,
+            min_temp,
+            max_temp,
+            settings.metric_default,
+            &weather_codes,
+        )?);
+    }
+    let mut days: Vec<(&str, Vec<_>)> = Vec::new();
+    for entry in &timeseries[start..] {
+        let day = get_day(&entry.time);
+        match days.last_mut() {
+            Some((last, entries)) if *last == day => entries.push(entry),
+            _ => days.push((day, vec![entry])),
+        }
+    }
+    let mut daily = Vec::new();
+    for (day, entries) in &days {
+        let (min_temp, max_temp) = extremes[day];
+        daily.push(get_daily(
+            entries,
+            min_temp,
+            max_temp,
+            settings.metric_default,
+            &weather_codes,
+        )?);
+    }
+    let region_country = location::reverse_geocode(coordinates)?;
+    let forecast_sentence = get_forecast_sentence(&forecast);
+    let f = WeatherForecast {
+        region: region_country[0].clone(),
+        country: region_country[1].clone(),
+        forecast: forecast.clone(),
+        current_weather: forecast.into_iter().next().unwrap(),
+        forecast_sentence,
+        daily,
+        alerts: Vec::new(),
+        raw_data: Some(vec![data.raw_data]),
+        utc_offset: None,
+    };
+    Ok(f)
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoInstantDetailsJson {
+    pub air_pressure_at_sea_level: Option<f64>,
+    pub air_temperature: f64,
+    pub cloud_area_fraction: Option<f64>,
+    pub relative_humidity: Option<f64>,
+    pub wind_from_direction: Option<f64>,
+    pub wind_speed: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoInstantJson {
+    pub details: MetNoInstantDetailsJson,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoSummaryJson {
+    pub symbol_code: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoPeriodDetailsJson {
+    pub precipitation_amount: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoPeriodJson {
+    pub summary: MetNoSummaryJson,
+    pub details: Option<MetNoPeriodDetailsJson>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoDataJson {
+    pub instant: MetNoInstantJson,
+    pub next_1_hours: Option<MetNoPeriodJson>,
+    pub next_6_hours: Option<MetNoPeriodJson>,
+    pub next_12_hours: Option<MetNoPeriodJson>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoTimeseriesJson {
+    pub time: String,
+    pub data: MetNoDataJson,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoMetaJson {
+    pub updated_at: String,
+    pub units: HashMap<String, String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoPropertiesJson {
+    pub meta: MetNoMetaJson,
+    pub timeseries: Vec<MetNoTimeseriesJson>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MetNoForecastJson {
+    #[serde(rename = "type")]
+    pub geo_type: String,
+    pub properties: MetNoPropertiesJson,
+}
+
+/// The last response kept on disk, used to honor `Expires` and `If-Modified-Since`
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetNoCacheJson {
+    pub expires: String,
+    pub last_modified: String,
+    pub body: String,
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::metno::json::{MetNoCacheJson, MetNoForecastJson};
+use crate::time::parse_http_date;
+use local::location::Coordinates;
+use local::now;
+use local::weather_file::WeatherFile;
+use networking;
+
+pub mod forecast;
+mod json;
+mod weather_data;
+
+/// met.no's terms of service require an identifying user agent
+const USER_AGENT: &str = "weathercli/1 https://github.com/arihant2math/weathercli";
+
+/// met.no asks for coordinates to be truncated to 4 decimals, which also improves cache hits
+fn truncate(coordinate: f64) -> f64 {
+    (coordinate * 10_000.0).trunc() / 10_000.0
+}
+
+fn get_api_url(location: Coordinates) -> String {
+    let latitude = truncate(location.latitude);
+    let longitude = truncate(location.longitude);
+    format!(
+        "https://api.met.no/weatherapi/locationforecast/2.0/compact?lat={latitude}&lon={longitude}"
+    )
+}
+
+fn get_cache_file(location: Coordinates) -> crate::Result<WeatherFile> {
+    WeatherFile::new(format!(
+        "cache/metno/{},{}.json",
+        truncate(location.latitude),
+        truncate(location.longitude)
+    ))
+}
+
+/// Gets the forecast from the met.no api server, reusing the cached response until it expires
+/// and revalidating it with `If-Modified-Since` afterwards
+pub fn get_combined_data_formatted(coordinates: Coordinates) -> crate::Result<MetNoFormattedData> {
+    let mut file = get_cache_file(coordinates)?;
+    let mut cache: MetNoCacheJson =
+        unsafe { simd_json::from_str(&mut file.get_text()?) }.unwrap_or_default();
+    let fresh = !cache.body.is_empty()
+        && parse_http_date(&cache.expires).map_or(false, |expires| expires > now() as i128);
+    if !fresh {
+        let mut headers = HashMap::new();
+        if !cache.body.is_empty() && !cache.last_modified.is_empty() {
+            headers.insert("If-Modified-Since".to_string(), cache.last_modified.clone());
+        }
+        let resp = networking::get_url(
+            get_api_url(coordinates),
+            Some(USER_AGENT.to_string()),
+            Some(headers),
+            None,
+        )?;
+        match resp.status {
+            304 => {}
+            200..=299 => {
+                cache.body = resp.text;
+                cache.last_modified = resp
+                    .headers
+                    .get("last-modified")
+                    .cloned()
+                    .unwrap_or_default();
+            }
+            status => {
+                return Err(weather_error::Error::NetworkError(format!(
+                    "met.no returned a status code of {status}"
+                )))
+            }
+        }
+        cache.expires = resp.headers.get("expires").cloned().unwrap_or_default();
+        file.data = Vec::from(simd_json::to_string(&cache)?);
+        file.write()?;
+    }
+    let raw_data = cache.body.clone();
+    let r: MetNoForecastJson = unsafe { simd_json::from_str(&mut cache.body) }?;
+    Ok(MetNoFormattedData {
+        weather: r,
+        raw_data,
+    })
+}
+
+pub struct MetNoFormattedData {
+    pub weather: MetNoForecastJson,
+    /// The body of the response, which may have come from the cache
+    pub raw_data: String,
+}
@@ -0,0 +1,185 @@
+use crate::metno::json::MetNoTimeseriesJson;
+use crate::time::parse_iso8601;
+use crate::weather_condition::get_cloud_condition_id;
+use crate::WindData;
+use crate::{get_conditions_sentence, WeatherData};
+use crate::{DailyForecast, WeatherCondition};
+use std::collections::HashMap;
+
+/// Maps a met.no symbol code (e.g. `lightrainshowers_day`) to an openweathermap condition id
+fn get_condition_id(symbol_code: &str) -> Option<u16> {
+    let symbol = symbol_code.split('_').next().unwrap_or(symbol_code);
+    let id = match symbol {
+        "clearsky" => 800,
+        "fair" => 801,
+        "partlycloudy" => 802,
+        "cloudy" => 804,
+        "fog" => 741,
+        "lightrain" => 500,
+        "rain" => 501,
+        "heavyrain" => 502,
+        "lightrainshowers" => 520,
+        "rainshowers" => 521,
+        "heavyrainshowers" => 522,
+        "lightsleet" | "lightsleetshowers" => 612,
+        "sleet" => 611,
+        "heavysleet" | "sleetshowers" | "heavysleetshowers" => 613,
+        "lightsnow" => 600,
+        "snow" => 601,
+        "heavysnow" => 602,
+        "lightsnowshowers" => 620,
+        "snowshowers" => 621,
+        "heavysnowshowers" => 622,
+        s if s.ends_with("thunder") && s.starts_with("light") => 200,
+        s if s.ends_with("thunder") && s.starts_with("heavy") => 202,
+        s if s.ends_with("thunder") => 201,
+        _ => return None,
+    };
+    Some(id)
+}
+
+/// Dewpoint in celsius from the Magnus formula, compact forecasts don't include it
+fn get_dewpoint(temperature: f64, relative_humidity: f64) -> f64 {
+    let (a, b) = (17.625, 243.04);
+    let gamma = (relative_humidity.max(1.0) / 100.0).ln() + a * temperature / (b + temperature);
+    b * gamma / (a - gamma)
+}
+
+fn convert_temperature(celsius: f64, metric: bool) -> f32 {
+    if metric {
+        celsius as f32
+    } else {
+        (celsius * 9.0 / 5.0 + 32.0) as f32
+    }
+}
+
+fn convert_wind_speed(meters_per_second: f64, metric: bool) -> f64 {
+    if metric {
+        meters_per_second * 3.6
+    } else {
+        meters_per_second * 2.236_936
+    }
+}
+
+pub fn get_weather_data(
+    entry: &MetNoTimeseriesJson,
+    min_temp: f64,
+    max_temp: f64,
+    metric: bool,
+    weather_codes: &HashMap<String, Vec<String>>,
+) -> crate::Result<WeatherData> {
+    let details = &entry.data.instant.details;
+    let cloud_cover = details.cloud_area_fraction.unwrap_or(0.0).round() as u8;
+    let symbol_code = entry
+        .data
+        .next_1_hours
+        .as_ref()
+        .or(entry.data.next_6_hours.as_ref())
+        .or(entry.data.next_12_hours.as_ref())
+        .map(|period| period.summary.symbol_code.as_str());
+    let condition_id = symbol_code
+        .and_then(get_condition_id)
+        .unwrap_or_else(|| get_cloud_condition_id(cloud_cover));
+    let conditions = vec![WeatherCondition::new(condition_id, weather_codes)?];
+    let wind_speed = details.wind_speed.unwrap_or(0.0);
+    let temperature = convert_temperature(details.air_temperature, metric);
+    let dewpoint = details.relative_humidity.map_or(temperature, |humidity| {
+        convert_temperature(get_dewpoint(details.air_temperature, humidity), metric)
+    });
+    let d = WeatherData {
+        time: parse_iso8601(&entry.time, 0)?,
+        temperature,
+        min_temp: convert_temperature(min_temp, metric),
+        max_temp: convert_temperature(max_temp, metric),
+        wind: WindData {
+            speed: convert_wind_speed(wind_speed, metric),
+            heading: details.wind_from_direction.unwrap_or(0.0) as u16,
+        },
+        raw_data: simd_json::to_string_pretty(entry)?,
+        dewpoint,
+        feels_like: temperature,
+        aqi: 0,
+        cloud_cover,
+        precipitation: entry
+            .data
+            .next_1_hours
+            .as_ref()
+            .and_then(|period| period.details.as_ref())
+            .and_then(|details| details.precipitation_amount)
+            .unwrap_or(0.0) as f32,
+        conditions: conditions.clone(),
+        condition_sentence: get_conditions_sentence(conditions),
+    };
+    Ok(d)
+}
+
+/// Summarizes the entries of one UTC day, the conditions are the ones of the 6 hour period
+/// starting closest to noon
+pub fn get_daily(
+    entries: &[&MetNoTimeseriesJson],
+    min_temp: f64,
+    max_temp: f64,
+    metric: bool,
+    weather_codes: &HashMap<String, Vec<String>>,
+) -> crate::Result<DailyForecast> {
+    let first = entries.first().ok_or("No forecast entries for the day")?;
+    let date = first.time.get(..10).unwrap_or(&first.time);
+    let windiest = entries
+        .iter()
+        .map(|entry| &entry.data.instant.details)
+        .max_by(|a, b| {
+            a.wind_speed
+                .unwrap_or(0.0)
+                .total_cmp(&b.wind_speed.unwrap_or(0.0))
+        })
+        .unwrap();
+    let cloud_cover = entries
+        .iter()
+        .map(|entry| {
+            entry
+                .data
+                .instant
+                .details
+                .cloud_area_fraction
+                .unwrap_or(0.0)
+        })
+        .sum::<f64>()
+        / entries.len() as f64;
+    let cloud_cover = cloud_cover.round() as u8;
+    let precipitation = entries.iter().any(|entry| {
+        [&entry.data.next_1_hours, &entry.data.next_6_hours]
+            .into_iter()
+            .flatten()
+            .filter_map(|period| period.details.as_ref())
+            .any(|details| details.precipitation_amount.unwrap_or(0.0) > 0.0)
+    });
+    let noon = parse_iso8601(&format!("{date}T12:00Z"), 0)?;
+    let mut symbol_code = None;
+    let mut distance = i128::MAX;
+    for entry in entries {
+        if let Some(period) = &entry.data.next_6_hours {
+            let d = (parse_iso8601(&entry.time, 0)? - noon).abs();
+            if d < distance {
+                distance = d;
+                symbol_code = Some(period.summary.symbol_code.as_str());
+            }
+        }
+    }
+    let condition_id = symbol_code
+        .and_then(get_condition_id)
+        .unwrap_or_else(|| get_cloud_condition_id(cloud_cover));
+    let conditions = vec![WeatherCondition::new(condition_id, weather_codes)?];
+    Ok(DailyForecast {
+        time: parse_iso8601(&format!("{date}T00:00Z"), 0)?,
+        min_temp: convert_temperature(min_temp, metric),
+        max_temp: convert_temperature(max_temp, metric),
+        wind: WindData {
+            speed: convert_wind_speed(windiest.wind_speed.unwrap_or(0.0), metric),
+            heading: windiest.wind_from_direction.unwrap_or(0.0) as u16,
+        },
+        cloud_cover,
+        precipitation_probability: if precipitation { 100 } else { 0 },
+        conditions: conditions.clone(),
+        condition_sentence: get_conditions_sentence(conditions),
+    })
+}
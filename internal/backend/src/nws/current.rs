@@ -1,4 +1,4 @@
-use crate::nws::json::NWSJSON;
+use crate::nws::json::{NWSFloatDataJSON, NWSJSON};
 use crate::WeatherCondition;
 use crate::WindData;
 use crate::{get_conditions_sentence, WeatherData};
@@ -22,6 +22,50 @@ fn convert_speed(value: f64, metric: bool) -> f64 {
     }
 }
 
+/// Hours a value of a gridpoint series is for, from the ISO 8601 duration at the end of its
+/// `validTime` like `2023-05-25T12:00:00+00:00/PT6H`, 1 if there is no duration
+fn period_hours(valid_time: &str) -> f64 {
+    let duration = match valid_time.split_once("/P") {
+        Some((_, duration)) => duration,
+        None => return 1.0,
+    };
+    let mut hours = 0.0;
+    let mut number = String::new();
+    let mut is_time = false;
+    for c in duration.chars() {
+        match c {
+            'T' => is_time = true,
+            '0'..='9' | '.' => number.push(c),
+            _ => {
+                let value: f64 = number.parse().unwrap_or(0.0);
+                number.clear();
+                hours += match (is_time, c) {
+                    (false, 'W') => value * 168.0,
+                    (false, 'D') => value * 24.0,
+                    (true, 'H') => value,
+                    (true, 'M') => value / 60.0,
+                    (true, 'S') => value / 3600.0,
+                    _ => 0.0,
+                };
+            }
+        }
+    }
+    if hours > 0.0 {
+        hours
+    } else {
+        1.0
+    }
+}
+
+/// The amount of a series like precipitation per hour of the period at `index`, amounts are
+/// given for periods of several hours. 0 if the series has no period at `index`
+fn per_hour(series: &NWSFloatDataJSON, index: usize) -> f64 {
+    series
+        .values
+        .get(index)
+        .map_or(0.0, |value| value.value / period_hours(&value.valid_time))
+}
+
 fn get_conditions(
     data: NWSJSON,
     metric: bool,
@@ -42,8 +86,8 @@ fn get_conditions(
     } else {
         conditions.push(WeatherCondition::new(804, &weather_codes)?);
     }
-    if data.properties.quantitative_precipitation.values[index].value != 0.0 {
-        let rain = data.properties.quantitative_precipitation.values[index].value;
+    let rain = per_hour(&data.properties.quantitative_precipitation, index);
+    if rain != 0.0 {
         let metric = metric;
         if (0.0 < rain && rain < 0.098 && !metric) || (0.0 < rain && rain < 2.5 && metric) {
             conditions.push(WeatherCondition::new(500, &weather_codes)?);
@@ -55,13 +99,13 @@ fn get_conditions(
             conditions.push(WeatherCondition::new(503, &weather_codes)?);
         }
     }
-    if data.properties.snowfall_amount.values[index].value != 0.0 {
+    if per_hour(&data.properties.snowfall_amount, index) != 0.0 {
         conditions.push(WeatherCondition::new(601, &weather_codes)?);
     }
     Ok(conditions)
 }
 
-pub fn get_current(data: NWSJSON, metric: bool) -> crate::Result<WeatherData> {
+pub fn get_current(data: NWSJSON, raw_data: &str, metric: bool) -> crate::Result<WeatherData> {
     let cloud_cover = data.properties.sky_cover.values[0].value as u8;
     let conditions = get_conditions(data.clone(), metric, 0, cloud_cover)?;
     let d = WeatherData {
@@ -73,14 +117,28 @@ pub fn get_current(data: NWSJSON, metric: bool) -> crate::Result<WeatherData> {
             speed: convert_speed(data.properties.wind_speed.values[0].value, metric),
             heading: data.properties.wind_direction.values[0].value as u16,
         },
-        raw_data: String::new(),
+        raw_data: raw_data.to_string(),
         dewpoint: convert_temp(data.properties.dewpoint.values[0].value, metric) as f32,
         feels_like: convert_temp(data.properties.apparent_temperature.values[0].value, metric)
             as f32,
         aqi: 0,
         cloud_cover,
+        precipitation: per_hour(&data.properties.quantitative_precipitation, 0) as f32,
         conditions: vec![],
         condition_sentence: get_conditions_sentence(conditions),
     };
     Ok(d)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn periods() {
+        assert_eq!(period_hours("2023-05-25T12:00:00+00:00/PT6H"), 6.0);
+        assert_eq!(period_hours("2023-05-25T12:00:00+00:00/P1DT12H"), 36.0);
+        assert_eq!(period_hours("2023-05-25T12:00:00+00:00/PT30M"), 0.5);
+        assert_eq!(period_hours("2023-05-25T12:00:00+00:00"), 1.0);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::nws::current::get_current;
 use crate::nws::get_combined_data_formatted;
-use crate::WeatherForecast;
+use crate::{get_forecast_sentence, raw_responses, WeatherForecast};
 use local::location;
 use local::settings::Settings;
 use location::Coordinates;
@@ -10,14 +10,21 @@ pub fn get_forecast(
     settings: Settings,
 ) -> crate::Result<WeatherForecast> {
     let data = get_combined_data_formatted(coordinates, settings.metric_default)?;
-    let current = get_current(data, settings.metric_default)?;
+    let raw_data = raw_responses(&data.raw_data);
+    // The gridpoint forecast is what the hour is read from
+    let current = get_current(data.weather, &raw_data[1], settings.metric_default)?;
     let region_country = location::reverse_geocode(coordinates)?;
+    let forecast = vec![current.clone()];
+    let forecast_sentence = get_forecast_sentence(&forecast);
     Ok(WeatherForecast {
         region: region_country[0].clone(),
         country: region_country[1].clone(),
-        forecast: vec![current.clone()],
+        forecast,
         current_weather: current,
-        forecast_sentence: String::new(),
-        raw_data: None,
+        forecast_sentence,
+        daily: Vec::new(),
+        alerts: Vec::new(),
+        raw_data: Some(raw_data),
+        utc_offset: None,
     })
 }
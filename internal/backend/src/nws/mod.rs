@@ -1,13 +1,14 @@
 use crate::nws::json::{NWSPointJSON, NWSJSON};
 use local::location::Coordinates;
 use networking;
+use networking::Resp;
 
 mod current;
 pub mod forecast;
 mod json;
 
-fn get_api_url(location: Coordinates, _metric: bool) -> crate::Result<String> {
-    let mut get_point = networking::get_url(
+fn get_api_url(location: Coordinates, _metric: bool) -> crate::Result<(String, Resp)> {
+    let mut resp = networking::get_url(
         format!(
             "https://api.weather.gov/points/{},{}",
             location.latitude, location.longitude
@@ -15,14 +16,26 @@ fn get_api_url(location: Coordinates, _metric: bool) -> crate::Result<String> {
         None,
         None,
         None,
-    )?
-    .text;
-    let point_json: NWSPointJSON = unsafe { simd_json::from_str(&mut get_point) }?;
-    Ok(point_json.properties.forecast_grid_data)
+    )?;
+    let point_json: NWSPointJSON = unsafe { simd_json::from_str(&mut resp.text) }?;
+    Ok((point_json.properties.forecast_grid_data, resp))
 }
 
-pub fn get_combined_data_formatted(location: Coordinates, metric: bool) -> crate::Result<NWSJSON> {
-    let mut raw_data = networking::get_url(get_api_url(location, metric)?, None, None, None)?;
+/// Gets the point and then the gridpoint forecast from the NWS api server
+pub fn get_combined_data_formatted(
+    location: Coordinates,
+    metric: bool,
+) -> crate::Result<NWSFormattedData> {
+    let (url, point) = get_api_url(location, metric)?;
+    let mut raw_data = networking::get_url(url, None, None, None)?;
     let data: NWSJSON = unsafe { simd_json::from_str(&mut raw_data.text) }?;
-    Ok(data)
+    Ok(NWSFormattedData {
+        weather: data,
+        raw_data: vec![point, raw_data],
+    })
+}
+
+pub struct NWSFormattedData {
+    pub weather: NWSJSON,
+    pub raw_data: Vec<Resp>,
 }
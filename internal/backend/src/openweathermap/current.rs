@@ -1,4 +1,5 @@
 use crate::openweathermap::json::{OpenWeatherMapAirQualityJson, OpenWeatherMapJson};
+use crate::openweathermap_shared::{get_precipitation, get_wind_speed};
 use crate::WeatherCondition;
 use crate::WindData;
 use crate::{get_conditions_sentence, WeatherData};
@@ -9,6 +10,7 @@ pub fn get_current(
     data: OpenWeatherMapJson,
     aqi: OpenWeatherMapAirQualityJson,
     weather_codes: HashMap<String, Vec<String>>,
+    metric: bool,
 ) -> crate::Result<WeatherData> {
     let mut conditions: Vec<WeatherCondition> = Vec::new();
     for condition in data.weather.clone() {
@@ -20,7 +22,7 @@ pub fn get_current(
         min_temp: data.main.temp_min as f32,
         max_temp: data.main.temp_max as f32,
         wind: WindData {
-            speed: data.wind.speed,
+            speed: get_wind_speed(data.wind.speed, metric),
             heading: data.wind.deg,
         },
         raw_data: simd_json::to_string_pretty(&data).expect("dump to string failed"),
@@ -32,6 +34,7 @@ pub fn get_current(
             .expect("aqi not found")
             .abs_diff(0),
         cloud_cover: data.clouds.all,
+        precipitation: get_precipitation(data.rain.as_ref(), data.snow.as_ref()),
         conditions: conditions.clone(),
         condition_sentence: get_conditions_sentence(conditions.clone()),
     })
@@ -1,58 +1,12 @@
 use crate::openweathermap::current::get_current;
 use crate::openweathermap::future::get_future;
-use crate::WeatherData;
-use crate::WeatherForecast;
+use crate::{get_forecast_sentence, WeatherData};
+use crate::{raw_responses, WeatherForecast};
 use local::location::Coordinates;
 use local::settings::Settings;
 use local::weather_file::WeatherFile;
 use std::collections::HashMap;
 
-fn get_forecast_sentence(forecast: Vec<WeatherData>) -> String {
-    let data = forecast;
-    let mut rain: Vec<bool> = Vec::with_capacity(16);
-    let mut snow: Vec<bool> = Vec::with_capacity(16);
-    for period in &data {
-        if period.conditions[0].condition_id / 100 == 5 {
-            rain.push(true);
-            snow.push(false);
-        } else if period.conditions[0].condition_id / 100 == 6 {
-            snow.push(true);
-            rain.push(false);
-        } else {
-            rain.push(false);
-            snow.push(false);
-        }
-    }
-    if data[0].conditions[0].condition_id / 100 == 5 {
-        let mut t = 0;
-        for i in rain {
-            if !i {
-                break;
-            }
-            t += 1;
-        }
-        return format!("It will continue raining for {} hours.", t * 3);
-    } else if data[0].conditions[0].condition_id / 100 == 6 {
-        let mut t = 0;
-        for i in snow {
-            if !i {
-                break;
-            }
-            t += 1;
-        }
-        return format!("It will continue snowing for {} hours.", t * 3);
-    }
-    let t = rain.iter().position(|&b| b);
-    if let Some(h) = t {
-        return format!("It will rain in {} hours", h * 3);
-    }
-    let t_s = snow.iter().position(|&b| b);
-    if let Some(h_s) = t_s {
-        return format!("It will snow in {} hours", h_s * 3);
-    }
-    "Conditions are predicted to be clear for the next 3 days.".to_string()
-}
-
 pub fn get_forecast(
     coordinates: Coordinates,
     settings: Settings,
@@ -76,17 +30,25 @@ pub fn get_forecast(
         data.weather.clone(),
         data.air_quality.clone(),
         weather_codes.clone(),
+        settings.metric_default,
     )?);
     for item in data.forecast.list {
-        forecast.push(get_future(item, weather_codes.clone())?);
+        forecast.push(get_future(
+            item,
+            weather_codes.clone(),
+            settings.metric_default,
+        )?);
     }
-    let forecast_sentence = get_forecast_sentence(forecast.clone());
+    let forecast_sentence = get_forecast_sentence(&forecast);
     Ok(WeatherForecast {
         region: data.weather.name,
         country: data.weather.sys.country,
         forecast: forecast.clone(),
         current_weather: forecast.into_iter().next().unwrap(),
         forecast_sentence,
-        raw_data: None,
+        daily: Vec::new(),
+        alerts: Vec::new(),
+        raw_data: Some(raw_responses(&data.raw_data)),
+        utc_offset: Some(data.weather.timezone as i32),
     })
 }
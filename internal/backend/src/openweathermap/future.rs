@@ -1,25 +1,26 @@
 use crate::openweathermap::json::OpenWeatherMapForecastItemJson;
+use crate::openweathermap_shared::{get_precipitation, get_wind_speed};
 use crate::WeatherCondition;
 use crate::WindData;
 use crate::{get_conditions_sentence, WeatherData};
-use local::now;
 use std::collections::HashMap;
 
 pub fn get_future(
     data: OpenWeatherMapForecastItemJson,
     weather_codes: HashMap<String, Vec<String>>,
+    metric: bool,
 ) -> crate::Result<WeatherData> {
     let mut conditions: Vec<WeatherCondition> = Vec::new();
     for condition in data.weather.clone() {
         conditions.push(WeatherCondition::new(condition.id, &weather_codes)?);
     }
     Ok(WeatherData {
-        time: now() as i128,
+        time: i128::from(data.dt) * 1000,
         temperature: data.main.temp as f32,
         min_temp: data.main.temp_min as f32,
         max_temp: data.main.temp_max as f32,
         wind: WindData {
-            speed: data.wind.speed,
+            speed: get_wind_speed(data.wind.speed, metric),
             heading: data.wind.deg,
         },
         raw_data: simd_json::to_string_pretty(&data).expect("dump to string failed"),
@@ -27,6 +28,7 @@ pub fn get_future(
         feels_like: data.main.feels_like as f32,
         aqi: 0,
         cloud_cover: data.clouds.all,
+        precipitation: get_precipitation(data.rain.as_ref(), data.snow.as_ref()),
         conditions: conditions.clone(),
         condition_sentence: get_conditions_sentence(conditions.clone()),
     })
@@ -49,6 +49,8 @@ pub struct OpenWeatherMapJson {
     pub visibility: i32,
     pub wind: OpenWeatherMapWindJson,
     pub clouds: OpenWeatherMapCloudsJson,
+    pub rain: Option<HashMap<String, f64>>,
+    pub snow: Option<HashMap<String, f64>>,
     pub sys: OpenWeatherMapSysJson,
     pub timezone: i64,
     pub id: i64,
@@ -103,6 +105,8 @@ pub struct OpenWeatherMapForecastItemJson {
     pub wind: OpenWeatherMapForecastWindJson,
     pub visibility: i32,
     pub pop: f64,
+    pub rain: Option<HashMap<String, f64>>,
+    pub snow: Option<HashMap<String, f64>>,
     pub sys: OpenWeatherMapForecastSysJson,
     pub dt_txt: String,
 }
@@ -1,93 +1,105 @@
-use crate::openweathermap_onecall::get_combined_data_formatted;
-use crate::openweathermap_onecall::weather_data::get_weather_data;
-use crate::WeatherData;
-use crate::WeatherForecast;
+use crate::openweathermap_onecall::json::DailyJson;
+use crate::openweathermap_onecall::weather_data::{
+    get_alert, get_daily, get_day_summary as get_day_summary_forecast, get_weather_data,
+};
+use crate::openweathermap_onecall::{
+    get_combined_data_formatted, get_day_summary_data, get_timemachine_data, OneCallVersion,
+};
+use crate::{get_forecast_sentence, DailyForecast, WeatherData};
+use crate::{raw_responses, WeatherForecast};
 use local::location;
 use local::settings::Settings;
 use local::weather_file::WeatherFile;
 use location::Coordinates;
 use std::collections::HashMap;
 
-// TODO: add minute precision
-fn get_forecast_sentence(forecast: Vec<WeatherData>) -> String {
-    let data = forecast;
-    let mut rain: Vec<bool> = Vec::with_capacity(16);
-    let mut snow: Vec<bool> = Vec::with_capacity(16);
-    for period in &data {
-        if period.conditions[0].condition_id / 100 == 5 {
-            rain.push(true);
-            snow.push(false);
-        } else if period.conditions[0].condition_id / 100 == 6 {
-            snow.push(true);
-            rain.push(false);
-        } else {
-            rain.push(false);
-            snow.push(false);
-        }
+fn get_settings(settings: &Settings) -> crate::Result<(OneCallVersion, &str)> {
+    if settings.open_weather_map_api_key.is_empty() {
+        Err("One Call requires an openweathermap api key, set one with `weather backend open-weather-map-api-key`")?;
     }
-    if data[0].conditions[0].condition_id / 100 == 5 {
-        let mut t = 0;
-        for i in rain {
-            if !i {
-                break;
-            }
-            t += 1;
-        }
-        return format!("It will continue raining for {t} hours.");
-    } else if data[0].conditions[0].condition_id / 100 == 6 {
-        let mut t = 0;
-        for i in snow {
-            if !i {
-                break;
-            }
-            t += 1;
-        }
-        return format!("It will continue snowing for {t} hours.");
-    }
-    let t = rain.iter().position(|&b| b);
-    if let Some(h) = t {
-        return format!("It will rain in {h} hours");
-    }
-    let t_s = snow.iter().position(|&b| b);
-    if let Some(h_s) = t_s {
-        return format!("It will snow in {h_s} hours");
-    }
-    "Conditions are predicted to be clear for the next 3 days.".to_string()
+    let version = OneCallVersion::from_setting(&settings.open_weather_map_one_call_version)?;
+    Ok((version, &settings.open_weather_map_api_key))
+}
+
+fn get_weather_codes() -> crate::Result<HashMap<String, Vec<String>>> {
+    let weather_file = WeatherFile::weather_codes()?;
+    Ok(bincode::deserialize(&weather_file.data)?)
+}
+
+/// Finds the day `time` (seconds) falls on, days are compared in the location's timezone
+fn find_day(daily: &[DailyJson], time: i64, timezone_offset: i64) -> Option<&DailyJson> {
+    let day = |t: i64| (t + timezone_offset).div_euclid(86400);
+    daily.iter().find(|d| day(d.dt) == day(time))
 }
 
 pub fn get_forecast(
     coordinates: Coordinates,
     settings: Settings,
 ) -> crate::Result<WeatherForecast> {
-    let data = get_combined_data_formatted(
-        "https://openweathermap.org/data/2.5/",
-        "439d4b804bc8187953eb36d2a8c26a02".to_string(),
+    let (version, api_key) = get_settings(&settings)?;
+    let response = get_combined_data_formatted(
+        version,
+        api_key,
         coordinates,
         settings.metric_default,
+        &["minutely"],
     )?;
+    let data = &response.weather;
+    let weather_codes = get_weather_codes()?;
+    let current = data.current.as_ref().ok_or("No current weather returned")?;
     let mut forecast: Vec<WeatherData> = Vec::new();
-    let weather_file = WeatherFile::weather_codes()?;
-    let weather_codes: HashMap<String, Vec<String>> = bincode::deserialize(&weather_file.data)?;
     forecast.push(get_weather_data(
-        &data.current,
-        &data.daily[0],
-        weather_codes.clone(),
+        current,
+        find_day(&data.daily, current.dt, data.timezone_offset),
+        &weather_codes,
+        settings.metric_default,
     )?);
-    for (count, item) in data.hourly.iter().enumerate() {
+    for item in data.hourly.iter().filter(|item| item.dt > current.dt) {
         forecast.push(get_weather_data(
             item,
-            &data.daily[count / 24],
-            weather_codes.clone(),
-        )?); //TODO: Fix
+            find_day(&data.daily, item.dt, data.timezone_offset),
+            &weather_codes,
+            settings.metric_default,
+        )?);
+    }
+    let mut daily: Vec<DailyForecast> = Vec::new();
+    for item in &data.daily {
+        daily.push(get_daily(item, &weather_codes, settings.metric_default)?);
     }
     let region_country = location::reverse_geocode(coordinates)?;
-    let forecast_sentence = get_forecast_sentence(forecast.clone());
+    let forecast_sentence = get_forecast_sentence(&forecast);
     Ok(WeatherForecast {
         region: region_country[0].clone(),
         country: region_country[1].clone(),
         forecast: forecast.clone(),
         current_weather: forecast.into_iter().next().unwrap(),
         forecast_sentence,
-        raw_data: None,
+        daily,
+        alerts: data.alerts.iter().map(get_alert).collect(),
+        raw_data: Some(raw_responses(&response.raw_data)),
+        utc_offset: Some(data.timezone_offset as i32),
     })
 }
+
+/// Gets the weather at `time` (seconds since the unix epoch) from the timemachine endpoint
+pub fn get_historical(
+    coordinates: Coordinates,
+    settings: Settings,
+    time: i64,
+) -> crate::Result<WeatherData> {
+    let (version, api_key) = get_settings(&settings)?;
+    let data = get_timemachine_data(version, api_key, coordinates, settings.metric_default, time)?;
+    let moment = data.data.first().ok_or("No historical weather returned")?;
+    get_weather_data(moment, None, &get_weather_codes()?, settings.metric_default)
+}
+
+/// Gets the aggregated weather of `date` (YYYY-MM-DD) from the day_summary endpoint
+pub fn get_day_summary(
+    coordinates: Coordinates,
+    settings: Settings,
+    date: &str,
+) -> crate::Result<DailyForecast> {
+    let (version, api_key) = get_settings(&settings)?;
+    let data = get_day_summary_data(version, api_key, coordinates, settings.metric_default, date)?;
+    get_day_summary_forecast(&data, &get_weather_codes()?, settings.metric_default)
+}
@@ -13,9 +13,9 @@ pub struct MomentJson {
     pub pressure: i64,
     pub humidity: i64,
     pub dew_point: f64,
-    pub uvi: f64,
+    pub uvi: Option<f64>,
     pub clouds: u8,
-    pub visibility: u64,
+    pub visibility: Option<u64>,
     pub wind_speed: f64,
     pub wind_deg: u16,
     pub weather: Vec<OpenWeatherMapConditionJson>,
@@ -37,6 +37,7 @@ pub struct DailyJson {
     pub moonrise: i64,
     pub moonset: i64,
     pub moon_phase: f64,
+    pub summary: Option<String>,
     pub temp: HashMap<String, f64>, // TODO: Optimize this into actual structs
     pub feels_like: HashMap<String, f64>,
     pub pressure: i64,
@@ -59,18 +60,87 @@ pub struct AlertsJson {
     pub start: i64,
     pub end: i64,
     pub description: String,
+    #[serde(default)]
     pub tags: Vec<String>,
 }
 
+/// Response of the `onecall` endpoint, excluded parts are missing
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MainJson {
     pub lat: f64,
     pub lon: f64,
     pub timezone: String,
     pub timezone_offset: i64,
-    pub current: MomentJson,
+    pub current: Option<MomentJson>,
+    #[serde(default)]
     pub minutely: Vec<MinutelyJson>,
+    #[serde(default)]
     pub hourly: Vec<MomentJson>,
+    #[serde(default)]
     pub daily: Vec<DailyJson>,
-    pub alerts: Option<Vec<AlertsJson>>,
+    #[serde(default)]
+    pub alerts: Vec<AlertsJson>,
+}
+
+/// Response of the `onecall/timemachine` endpoint
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TimeMachineJson {
+    pub lat: f64,
+    pub lon: f64,
+    pub timezone: String,
+    pub timezone_offset: i64,
+    pub data: Vec<MomentJson>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DaySummaryAfternoonJson {
+    pub afternoon: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DaySummaryPrecipitationJson {
+    pub total: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DaySummaryTemperatureJson {
+    pub min: f64,
+    pub max: f64,
+    pub afternoon: f64,
+    pub night: f64,
+    pub evening: f64,
+    pub morning: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DaySummaryWindMaxJson {
+    pub speed: f64,
+    pub direction: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DaySummaryWindJson {
+    pub max: DaySummaryWindMaxJson,
+}
+
+/// Response of the `onecall/day_summary` endpoint (3.0 only)
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DaySummaryJson {
+    pub lat: f64,
+    pub lon: f64,
+    pub tz: String,
+    pub date: String,
+    pub units: String,
+    pub cloud_cover: DaySummaryAfternoonJson,
+    pub humidity: DaySummaryAfternoonJson,
+    pub precipitation: DaySummaryPrecipitationJson,
+    pub temperature: DaySummaryTemperatureJson,
+    pub pressure: DaySummaryAfternoonJson,
+    pub wind: DaySummaryWindJson,
+}
+
+/// Body of a failed request
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ErrorJson {
+    pub message: String,
 }
@@ -1,34 +1,173 @@
-use crate::openweathermap_onecall::json::MainJson;
+use serde::de::DeserializeOwned;
+
+use crate::openweathermap_onecall::json::{DaySummaryJson, ErrorJson, MainJson, TimeMachineJson};
 
 use local::location::Coordinates;
 use networking;
+use networking::Resp;
 
 pub mod forecast;
 pub mod json;
 mod weather_data;
 
-/// Gets the urls from the openweathermap api server
-fn get_api_url(url: &str, api_key: String, location: Coordinates, metric: bool) -> String {
+/// Version of the One Call api to use, 2.5 only works for keys created before it was deprecated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OneCallVersion {
+    V2_5,
+    V3_0,
+}
+
+impl OneCallVersion {
+    /// Parses the `OPEN_WEATHER_MAP_ONE_CALL_VERSION` setting
+    pub fn from_setting(version: &str) -> crate::Result<Self> {
+        match version.trim() {
+            "2.5" => Ok(Self::V2_5),
+            "" | "3" | "3.0" => Ok(Self::V3_0),
+            _ => Err(format!(
+                "Unknown One Call version {version}, expected 2.5 or 3.0"
+            ))?,
+        }
+    }
+
+    fn base_url(self) -> &'static str {
+        match self {
+            Self::V2_5 => "https://api.openweathermap.org/data/2.5/onecall",
+            Self::V3_0 => "https://api.openweathermap.org/data/3.0/onecall",
+        }
+    }
+}
+
+fn get_query(api_key: &str, location: Coordinates, metric: bool) -> String {
     let longitude = location.longitude;
     let latitude = location.latitude;
     let units = if metric { "metric" } else { "imperial" };
-    format!("{url}onecall?lat={latitude}&lon={longitude}&units={units}&appid={api_key}")
+    format!("lat={latitude}&lon={longitude}&units={units}&appid={api_key}")
+}
+
+/// Gets the url of the forecast, `exclude` can contain current, minutely, hourly, daily and alerts
+fn get_api_url(
+    version: OneCallVersion,
+    api_key: &str,
+    location: Coordinates,
+    metric: bool,
+    exclude: &[&str],
+) -> String {
+    let mut url = format!(
+        "{}?{}",
+        version.base_url(),
+        get_query(api_key, location, metric)
+    );
+    if !exclude.is_empty() {
+        url += &format!("&exclude={}", exclude.join(","));
+    }
+    url
+}
+
+/// Gets the url of the weather at `time` (seconds since the unix epoch)
+fn get_timemachine_url(
+    version: OneCallVersion,
+    api_key: &str,
+    location: Coordinates,
+    metric: bool,
+    time: i64,
+) -> String {
+    format!(
+        "{}/timemachine?{}&dt={time}",
+        version.base_url(),
+        get_query(api_key, location, metric)
+    )
+}
+
+/// Gets the url of the aggregated weather on `date` (YYYY-MM-DD)
+fn get_day_summary_url(
+    version: OneCallVersion,
+    api_key: &str,
+    location: Coordinates,
+    metric: bool,
+    date: &str,
+) -> crate::Result<String> {
+    if version != OneCallVersion::V3_0 {
+        Err("Day summaries are only available with One Call 3.0")?;
+    }
+    Ok(format!(
+        "{}/day_summary?{}&date={date}",
+        version.base_url(),
+        get_query(api_key, location, metric)
+    ))
+}
+
+/// Gets the response and its JSON, errors of the api are reported with their message
+fn get_json<T: DeserializeOwned>(url: &str) -> crate::Result<(T, Resp)> {
+    let mut n = networking::get_url(url, None, None, None)?;
+    if n.status != 200 {
+        let message = unsafe { simd_json::from_str::<ErrorJson>(&mut n.text) }
+            .map_or_else(|_| format!("status code {}", n.status), |e| e.message);
+        return Err(weather_error::Error::NetworkError(format!(
+            "openweathermap One Call request failed: {message}"
+        )));
+    }
+    let r: T = unsafe { simd_json::from_str(&mut n.text) }?;
+    Ok((r, n))
 }
 
-/// Gets the urls from the openweathermap api server and returns a `FormattedData` struct with the data
+/// Gets the forecast from the openweathermap api server
 pub fn get_combined_data_formatted(
-    open_weather_map_api_url: &str,
-    open_weather_map_api_key: String,
+    version: OneCallVersion,
+    open_weather_map_api_key: &str,
     coordinates: Coordinates,
     metric: bool,
-) -> crate::Result<MainJson> {
-    let url = get_api_url(
-        open_weather_map_api_url,
+    exclude: &[&str],
+) -> crate::Result<OneCallFormattedData> {
+    let (weather, resp) = get_json(&get_api_url(
+        version,
         open_weather_map_api_key,
         coordinates,
         metric,
-    );
-    let mut n = networking::get_url(&url, None, None, None)?;
-    let r: MainJson = unsafe { simd_json::from_str(&mut n.text) }?;
-    Ok(r)
+        exclude,
+    ))?;
+    Ok(OneCallFormattedData {
+        weather,
+        raw_data: vec![resp],
+    })
+}
+
+pub struct OneCallFormattedData {
+    pub weather: MainJson,
+    pub raw_data: Vec<Resp>,
+}
+
+/// Gets the historical (or future) weather at `time` in seconds since the unix epoch
+pub fn get_timemachine_data(
+    version: OneCallVersion,
+    open_weather_map_api_key: &str,
+    coordinates: Coordinates,
+    metric: bool,
+    time: i64,
+) -> crate::Result<TimeMachineJson> {
+    Ok(get_json(&get_timemachine_url(
+        version,
+        open_weather_map_api_key,
+        coordinates,
+        metric,
+        time,
+    ))?
+    .0)
+}
+
+/// Gets the aggregated weather of `date` (YYYY-MM-DD)
+pub fn get_day_summary_data(
+    version: OneCallVersion,
+    open_weather_map_api_key: &str,
+    coordinates: Coordinates,
+    metric: bool,
+    date: &str,
+) -> crate::Result<DaySummaryJson> {
+    Ok(get_json(&get_day_summary_url(
+        version,
+        open_weather_map_api_key,
+        coordinates,
+        metric,
+        date,
+    )?)?
+    .0)
 }
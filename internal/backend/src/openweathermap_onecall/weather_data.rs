@@ -1,34 +1,137 @@
-use crate::openweathermap_onecall::json::{DailyJson, MomentJson};
+use crate::openweathermap_onecall::json::{AlertsJson, DailyJson, DaySummaryJson, MomentJson};
+use crate::openweathermap_shared::json::OpenWeatherMapConditionJson;
+use crate::openweathermap_shared::{get_precipitation, get_wind_speed};
+use crate::time::parse_iso8601;
+use crate::weather_condition::get_cloud_condition_id;
 use crate::WeatherCondition;
 use crate::WindData;
-use crate::{get_conditions_sentence, WeatherData};
-use local::now;
+use crate::{get_conditions_sentence, DailyForecast, WeatherAlert, WeatherData};
 use std::collections::HashMap;
 
+fn get_conditions(
+    weather: &[OpenWeatherMapConditionJson],
+    weather_codes: &HashMap<String, Vec<String>>,
+) -> crate::Result<Vec<WeatherCondition>> {
+    let mut conditions: Vec<WeatherCondition> = Vec::new();
+    for condition in weather {
+        conditions.push(WeatherCondition::new(condition.id, weather_codes)?);
+    }
+    Ok(conditions)
+}
+
+/// `daily` is the day `data` belongs to, without it the min and max are the current temperature
 pub fn get_weather_data(
     data: &MomentJson,
-    daily: &DailyJson,
-    weather_codes: HashMap<String, Vec<String>>,
+    daily: Option<&DailyJson>,
+    weather_codes: &HashMap<String, Vec<String>>,
+    metric: bool,
 ) -> crate::Result<WeatherData> {
-    let mut conditions: Vec<WeatherCondition> = Vec::new();
-    for condition in data.weather.clone() {
-        conditions.push(WeatherCondition::new(condition.id, &weather_codes)?);
-    }
+    let conditions = get_conditions(&data.weather, weather_codes)?;
+    let temp = |key: &str| {
+        daily
+            .and_then(|daily| daily.temp.get(key))
+            .map_or(data.temp as f32, |&t| t as f32)
+    };
     Ok(WeatherData {
-        time: now() as i128,
+        time: i128::from(data.dt) * 1000,
         temperature: data.temp as f32,
-        min_temp: daily.temp["min"] as f32,
-        max_temp: daily.temp["max"] as f32,
+        min_temp: temp("min"),
+        max_temp: temp("max"),
         wind: WindData {
-            speed: data.wind_speed,
+            speed: get_wind_speed(data.wind_speed, metric),
             heading: data.wind_deg,
         },
-        raw_data: simd_json::to_string_pretty(data).expect("dump to string failed"),
-        dewpoint: data.humidity as f32,
+        raw_data: simd_json::to_string_pretty(data)?,
+        dewpoint: data.dew_point as f32,
         feels_like: data.feels_like as f32,
-        aqi: 42, // TODO: Fix
+        aqi: 0,
+        cloud_cover: data.clouds,
+        precipitation: get_precipitation(data.rain.as_ref(), data.snow.as_ref()),
+        conditions: conditions.clone(),
+        condition_sentence: get_conditions_sentence(conditions),
+    })
+}
+
+pub fn get_daily(
+    data: &DailyJson,
+    weather_codes: &HashMap<String, Vec<String>>,
+    metric: bool,
+) -> crate::Result<DailyForecast> {
+    let conditions = get_conditions(&data.weather, weather_codes)?;
+    Ok(DailyForecast {
+        time: i128::from(data.dt) * 1000,
+        min_temp: data.temp.get("min").copied().unwrap_or_default() as f32,
+        max_temp: data.temp.get("max").copied().unwrap_or_default() as f32,
+        wind: WindData {
+            speed: get_wind_speed(data.wind_speed, metric),
+            heading: data.wind_deg.rem_euclid(360) as u16,
+        },
         cloud_cover: data.clouds,
+        precipitation_probability: (data.pop * 100.0).round() as u8,
         conditions: conditions.clone(),
-        condition_sentence: get_conditions_sentence(conditions.clone()),
+        condition_sentence: data
+            .summary
+            .clone()
+            .unwrap_or_else(|| get_conditions_sentence(conditions)),
     })
 }
+
+/// Parses the `+HH:MM` timezone of a day summary into seconds
+fn parse_utc_offset(tz: &str) -> i32 {
+    let sign = if tz.starts_with('-') { -1 } else { 1 };
+    let mut parts = tz
+        .trim_start_matches(['+', '-'])
+        .split(':')
+        .map(|part| part.parse::<i32>().unwrap_or(0));
+    let hours = parts.next().unwrap_or(0);
+    let minutes = parts.next().unwrap_or(0);
+    sign * (hours * 3600 + minutes * 60)
+}
+
+pub fn get_day_summary(
+    data: &DaySummaryJson,
+    weather_codes: &HashMap<String, Vec<String>>,
+    metric: bool,
+) -> crate::Result<DailyForecast> {
+    let cloud_cover = data.cloud_cover.afternoon.round() as u8;
+    let mut conditions = vec![WeatherCondition::new(
+        get_cloud_condition_id(cloud_cover),
+        weather_codes,
+    )?];
+    // The total is always in mm
+    let precipitation = data.precipitation.total;
+    if precipitation > 0.0 {
+        let id = if precipitation < 10.0 {
+            500
+        } else if precipitation < 50.0 {
+            501
+        } else {
+            502
+        };
+        conditions.push(WeatherCondition::new(id, weather_codes)?);
+    }
+    Ok(DailyForecast {
+        time: parse_iso8601(&format!("{}T00:00", data.date), parse_utc_offset(&data.tz))?,
+        min_temp: data.temperature.min as f32,
+        max_temp: data.temperature.max as f32,
+        wind: WindData {
+            speed: get_wind_speed(data.wind.max.speed, metric),
+            heading: data.wind.max.direction.rem_euclid(360.0) as u16,
+        },
+        cloud_cover,
+        precipitation_probability: if precipitation > 0.0 { 100 } else { 0 },
+        conditions: conditions.clone(),
+        condition_sentence: get_conditions_sentence(conditions),
+    })
+}
+
+pub fn get_alert(data: &AlertsJson) -> WeatherAlert {
+    WeatherAlert {
+        sender: data.sender_name.clone(),
+        event: data.event.clone(),
+        start: i128::from(data.start) * 1000,
+        end: i128::from(data.end) * 1000,
+        description: data.description.clone(),
+        tags: data.tags.clone(),
+    }
+}
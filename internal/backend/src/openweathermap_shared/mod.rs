@@ -1 +1,26 @@
+use std::collections::HashMap;
+
 pub mod json;
+
+/// Wind speed in km/h with metric units, which openweathermap reports in m/s, and in mph otherwise
+pub fn get_wind_speed(speed: f64, metric: bool) -> f64 {
+    if metric {
+        speed * 3.6
+    } else {
+        speed
+    }
+}
+
+/// Millimeters of rain and snow in an hour, from the `1h` or `3h` amounts
+pub fn get_precipitation(
+    rain: Option<&HashMap<String, f64>>,
+    snow: Option<&HashMap<String, f64>>,
+) -> f32 {
+    let hourly = |amounts: Option<&HashMap<String, f64>>| {
+        amounts.map_or(0.0, |amounts| match amounts.get("1h") {
+            Some(amount) => *amount,
+            None => amounts.get("3h").map_or(0.0, |amount| amount / 3.0),
+        })
+    };
+    (hourly(rain) + hourly(snow)) as f32
+}
@@ -0,0 +1,130 @@
+/// Days since the unix epoch of a proleptic gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn to_millis(date: [i64; 3], clock: [i64; 3], utc_offset_seconds: i32) -> i128 {
+    let seconds = days_from_civil(date[0], date[1], date[2]) * 86_400
+        + clock[0] * 3600
+        + clock[1] * 60
+        + clock[2]
+        - i64::from(utc_offset_seconds);
+    i128::from(seconds) * 1000
+}
+
+fn parse_numbers(s: &str, separator: char) -> Option<Vec<i64>> {
+    s.split(separator).map(|n| n.parse().ok()).collect()
+}
+
+/// Converts an ISO 8601 time (`2023-05-06T14:00`, `2023-05-06T14:00:00Z`) into unix milliseconds,
+/// times without a `Z` suffix are interpreted with the given utc offset
+pub fn parse_iso8601(time: &str, utc_offset_seconds: i32) -> crate::Result<i128> {
+    let invalid = || format!("Invalid ISO 8601 time: {time}");
+    let (offset, trimmed) = match time.strip_suffix('Z') {
+        Some(t) => (0, t),
+        None => (utc_offset_seconds, time),
+    };
+    let (date, clock) = trimmed.split_once('T').ok_or_else(invalid)?;
+    let date = parse_numbers(date, '-').ok_or_else(invalid)?;
+    let clock = parse_numbers(clock, ':').ok_or_else(invalid)?;
+    if date.len() != 3 || !(2..=3).contains(&clock.len()) {
+        return Err(invalid())?;
+    }
+    Ok(to_millis(
+        [date[0], date[1], date[2]],
+        [clock[0], clock[1], clock.get(2).copied().unwrap_or(0)],
+        offset,
+    ))
+}
+
+/// Converts an HTTP date (`Sat, 06 May 2023 14:30:00 GMT`) into unix milliseconds
+pub fn parse_http_date(date: &str) -> crate::Result<i128> {
+    let invalid = || format!("Invalid HTTP date: {date}");
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    if parts.len() != 6 {
+        return Err(invalid())?;
+    }
+    let months = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month = months
+        .iter()
+        .position(|&m| m == parts[2])
+        .ok_or_else(invalid)? as i64
+        + 1;
+    let day = parts[1].parse().map_err(|_| invalid())?;
+    let year = parts[3].parse().map_err(|_| invalid())?;
+    let clock = parse_numbers(parts[4], ':').ok_or_else(invalid)?;
+    if clock.len() != 3 {
+        return Err(invalid())?;
+    }
+    Ok(to_millis(
+        [year, month, day],
+        [clock[0], clock[1], clock[2]],
+        0,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso8601() {
+        assert_eq!(parse_iso8601("1970-01-01T00:00", 0).unwrap(), 0);
+        assert_eq!(
+            parse_iso8601("2023-05-06T14:30:00Z", 0).unwrap(),
+            1_683_383_400_000
+        );
+        assert_eq!(
+            parse_iso8601("2023-05-06T14:30", 0).unwrap(),
+            parse_iso8601("2023-05-06T14:30:00Z", 0).unwrap()
+        );
+        // The offset applies to local times only
+        assert_eq!(
+            parse_iso8601("2023-05-06T16:30", 7200).unwrap(),
+            1_683_383_400_000
+        );
+        assert_eq!(
+            parse_iso8601("2023-05-06T14:30:00Z", 7200).unwrap(),
+            1_683_383_400_000
+        );
+        assert_eq!(
+            parse_iso8601("2024-02-29T00:00", 0).unwrap(),
+            1_709_164_800_000
+        );
+        assert_eq!(parse_iso8601("1969-12-31T23:00", 0).unwrap(), -3_600_000);
+        for invalid in [
+            "",
+            "2023-05-06",
+            "2023-05T14:30",
+            "2023-05-06T14",
+            "2023-05-06Tnoon",
+        ] {
+            assert!(parse_iso8601(invalid, 0).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn http_date() {
+        assert_eq!(
+            parse_http_date("Sat, 06 May 2023 14:30:00 GMT").unwrap(),
+            1_683_383_400_000
+        );
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT").unwrap(), 0);
+        for invalid in [
+            "",
+            "Sat, 06 May 2023 14:30 GMT",
+            "Sat, 06 Mai 2023 14:30:00 GMT",
+            "2023-05-06T14:30:00Z",
+        ] {
+            assert!(parse_http_date(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -6,6 +6,7 @@ pub struct WeatherCondition {
     pub condition_id: u16,
     pub image_url: String,
     pub sentence: String,
+    /// Name of the ASCII art in `weather_ascii_images.res`, like `Sunny`
     pub image_ascii: String,
 }
 
@@ -19,7 +20,10 @@ impl WeatherCondition {
             .ok_or("No such condition")?;
         let sentence = code[3].clone();
         let image_url = format!("https://openweathermap.org/img/wn/{}@4x.png", &code[2]);
-        let image_ascii = code[4].clone();
+        // A few codes have no art
+        let image_ascii = code
+            .get(4)
+            .map_or_else(|| "Unknown".to_string(), String::clone);
         Ok(Self {
             condition_id,
             image_url,
@@ -28,3 +32,18 @@ impl WeatherCondition {
         })
     }
 }
+
+/// Condition id describing the cloud cover (in percent)
+pub(crate) fn get_cloud_condition_id(cloud_cover: u8) -> u16 {
+    if cloud_cover == 0 {
+        800
+    } else if cloud_cover < 25 {
+        801
+    } else if cloud_cover < 50 {
+        802
+    } else if cloud_cover < 85 {
+        803
+    } else {
+        804
+    }
+}
@@ -32,6 +32,9 @@ pub struct WeatherData {
     pub feels_like: f32,
     pub aqi: u8,
     pub cloud_cover: u8,
+    /// Rain and snow in the hour in millimeters, also with imperial units
+    #[serde(default)]
+    pub precipitation: f32,
     pub conditions: Vec<WeatherCondition>,
     pub condition_sentence: String,
 }
@@ -1,6 +1,33 @@
-use crate::WeatherData;
+use crate::{WeatherCondition, WeatherData, WindData};
 use serde::{Deserialize, Serialize};
 
+/// Summary of a whole day, for backends that provide one
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DailyForecast {
+    /// Start of the day in milliseconds since the unix epoch
+    pub time: i128,
+    pub min_temp: f32,
+    pub max_temp: f32,
+    pub wind: WindData,
+    pub cloud_cover: u8,
+    /// Probability of precipitation in percent
+    pub precipitation_probability: u8,
+    pub conditions: Vec<WeatherCondition>,
+    pub condition_sentence: String,
+}
+
+/// A weather alert issued for the forecast location
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WeatherAlert {
+    pub sender: String,
+    pub event: String,
+    /// Start and end in milliseconds since the unix epoch
+    pub start: i128,
+    pub end: i128,
+    pub description: String,
+    pub tags: Vec<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct WeatherForecast {
     pub region: String,
@@ -8,5 +35,13 @@ pub struct WeatherForecast {
     pub forecast: Vec<WeatherData>,
     pub current_weather: WeatherData,
     pub forecast_sentence: String,
+    #[serde(default)]
+    pub daily: Vec<DailyForecast>,
+    #[serde(default)]
+    pub alerts: Vec<WeatherAlert>,
+    /// Bodies of the responses of the weather provider, if the backend keeps them
     pub raw_data: Option<Vec<String>>,
+    /// Offset of the location's time zone from UTC in seconds, if the provider reports it
+    #[serde(default)]
+    pub utc_offset: Option<i32>,
 }
@@ -3,7 +3,10 @@
 // - warnings and downloads in weather config
 mod global;
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use terminal::chart::Style;
+
+use crate::graph::Series;
 
 #[derive(Clone, Parser)]
 #[command(version, author, about, name = "weathercli")]
@@ -29,9 +32,17 @@ pub enum Command {
     #[command(subcommand)]
     Layout(LayoutOpts),
     #[command(subcommand)]
+    Theme(ThemeOpts),
+    #[command(subcommand)]
     Backend(BackendOpts),
     #[command(about = "Run the interactive terminal setup")]
     Setup,
+    #[command(about = "Chart the temperature, precipitation, wind or air quality by the hour")]
+    Graph(GraphOpts),
+    #[command(about = "Show a full screen dashboard that refreshes itself")]
+    Tui(TuiOpts),
+    #[command(about = "Get the weather of a past day or hour from OpenWeatherMap One Call")]
+    History(HistoryOpts),
     #[command(about = "Update weathercli")]
     Update(UpdateOpts),
     #[command(about = "Various Credits")]
@@ -40,11 +51,28 @@ pub enum Command {
 
 #[derive(Clone, Subcommand)]
 pub enum LayoutOpts {
-    #[command(about = "Install a layout")]
+    #[command(about = "Install a layout from a file or by its name in the gallery")]
     Install(InstallOpts),
+    #[command(about = "Show the layouts in the gallery")]
+    Browse,
+    #[command(about = "Download the installed layouts of the gallery again if they changed")]
+    Update,
     List,
     Select,
     Delete,
+    #[command(about = "Check a layout for problems and show it with a sample forecast")]
+    Check(CheckOpts),
+    #[command(about = "Show a layout with sample forecasts")]
+    Preview(PreviewOpts),
+}
+
+#[derive(Clone, Subcommand)]
+pub enum ThemeOpts {
+    #[command(about = "Install a theme")]
+    Install(InstallOpts),
+    #[command(about = "List the default theme and all installed themes")]
+    List,
+    Select,
 }
 
 #[derive(Clone, Subcommand)]
@@ -58,6 +86,10 @@ pub enum BackendOpts {
     BingMapsApiKey,
     #[command(about = "Delete an installed custom backend")]
     Delete,
+    #[command(about = "Create a crate for a new custom backend")]
+    New(NewBackendOpts),
+    #[command(about = "Check the forecasts returned by a custom backend")]
+    Test(TestBackendOpts),
 }
 
 #[derive(Clone, Args)]
@@ -65,6 +97,118 @@ pub struct InstallOpts {
     pub path: String,
 }
 
+#[derive(Clone, Args)]
+pub struct CheckOpts {
+    #[arg(help = "Path of the layout, or the name of an installed layout")]
+    pub path: String,
+}
+
+#[derive(Clone, Args)]
+pub struct PreviewOpts {
+    #[arg(help = "Path of the layout, or the name of an installed layout")]
+    pub path: String,
+    #[arg(
+        long,
+        short,
+        value_parser = crate::layout::sample::SAMPLES,
+        help = "Only show this sample forecast, all of them are shown by default"
+    )]
+    pub sample: Option<String>,
+    #[arg(
+        long,
+        short,
+        action,
+        help = "Show the layout again whenever the file changes"
+    )]
+    pub watch: bool,
+}
+
+#[derive(Clone, Args)]
+pub struct NewBackendOpts {
+    pub name: String,
+    #[arg(long, help = "Directory to create the crate in, defaults to ./<name>")]
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Args)]
+pub struct TestBackendOpts {
+    #[arg(help = "Name of an installed backend or path to a plugin file")]
+    pub backend: String,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SeriesChoice {
+    Temp,
+    Precip,
+    Wind,
+    Aqi,
+}
+
+impl From<SeriesChoice> for Series {
+    fn from(choice: SeriesChoice) -> Self {
+        match choice {
+            SeriesChoice::Temp => Self::Temperature,
+            SeriesChoice::Precip => Self::Precipitation,
+            SeriesChoice::Wind => Self::Wind,
+            SeriesChoice::Aqi => Self::Aqi,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StyleChoice {
+    Line,
+    Bars,
+}
+
+impl From<StyleChoice> for Style {
+    fn from(choice: StyleChoice) -> Self {
+        match choice {
+            StyleChoice::Line => Self::Line,
+            StyleChoice::Bars => Self::Bars,
+        }
+    }
+}
+
+#[derive(Clone, Args)]
+pub struct GraphOpts {
+    #[arg(value_enum, default_value_t = SeriesChoice::Temp)]
+    pub series: SeriesChoice,
+    #[arg(long, default_value_t = 24, help = "Number of hours to chart from now")]
+    pub hours: usize,
+    #[arg(long, default_value_t = 10, help = "Rows of the chart")]
+    pub height: usize,
+    #[arg(
+        long,
+        value_enum,
+        help = "Draw a line or bars, precipitation and air quality are bars by default"
+    )]
+    pub style: Option<StyleChoice>,
+}
+
+#[derive(Clone, Args)]
+pub struct TuiOpts {
+    #[arg(
+        long,
+        short,
+        help = "Another place to switch to besides the current location, can be repeated"
+    )]
+    pub location: Vec<String>,
+    #[arg(long, short, default_value_t = 10, help = "Minutes between refreshes")]
+    pub interval: u64,
+}
+
+#[derive(Clone, Args)]
+pub struct HistoryOpts {
+    #[arg(help = "The day, like 2023-05-01")]
+    pub date: String,
+    #[arg(
+        long,
+        help = "An hour of the day in UTC, like 14:00, to get the weather then instead of the whole day"
+    )]
+    pub time: Option<String>,
+}
+
 #[derive(Clone, Subcommand)]
 pub enum CacheOpts {
     #[command(about = "Trim the size of the cache")]
@@ -1,16 +1,67 @@
-use clap::Args;
+use clap::{Args, ValueEnum};
+use terminal::color::ColorMode;
+
+use crate::output::{Format, Output};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<ColorChoice> for ColorMode {
+    fn from(choice: ColorChoice) -> Self {
+        match choice {
+            ColorChoice::Auto => Self::Auto,
+            ColorChoice::Always => Self::Always,
+            ColorChoice::Never => Self::Never,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FormatChoice {
+    Json,
+    Ndjson,
+    Yaml,
+    /// Only the current weather and the hourly forecast, without days and alerts
+    Csv,
+    Toml,
+}
+
+impl From<FormatChoice> for Format {
+    fn from(choice: FormatChoice) -> Self {
+        match choice {
+            FormatChoice::Json => Self::Json,
+            FormatChoice::Ndjson => Self::Ndjson,
+            FormatChoice::Yaml => Self::Yaml,
+            FormatChoice::Csv => Self::Csv,
+            FormatChoice::Toml => Self::Toml,
+        }
+    }
+}
 
 #[derive(Clone, Args)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct GlobalOpts {
+    #[arg(long, short, action, global = true, help = "Same as --format json")]
+    pub json: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        help = "Print the forecast in a machine readable format that is the same for every backend, csv leaves out the days and alerts"
+    )]
+    pub format: Option<FormatChoice>,
     #[arg(
         long,
-        short,
         action,
         global = true,
-        help = "Print raw json output, useful for debugging"
+        conflicts_with_all = ["format", "json"],
+        help = "Print the responses of the weather provider, useful for debugging"
     )]
-    pub json: bool,
+    pub raw: bool,
     #[arg(
         long,
         short,
@@ -45,4 +96,27 @@ pub struct GlobalOpts {
     pub no_sys_loc: bool,
     #[arg(long, action, global = true, help = "Enables debugging")]
     pub debug: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        help = "When to use colors, auto uses them if the terminal supports them and NO_COLOR isn't set"
+    )]
+    pub color: ColorChoice,
+}
+
+impl GlobalOpts {
+    /// What is printed for the forecast, `--raw` can't be used with `--format` or `--json`
+    pub fn output(&self) -> Output {
+        if self.raw {
+            Output::Raw
+        } else if let Some(format) = self.format {
+            Output::Format(format.into())
+        } else if self.json {
+            Output::Format(Format::Json)
+        } else {
+            Output::Layout
+        }
+    }
 }
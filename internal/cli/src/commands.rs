@@ -1,14 +1,21 @@
 pub mod backend_commands;
 pub mod layout_commands;
+pub mod theme_commands;
+pub mod tui;
 pub mod util;
 
 use std::str::FromStr;
 use std::thread;
 
-use crate::arguments::CacheOpts;
+use crate::arguments::{CacheOpts, GraphOpts, HistoryOpts};
+use crate::graph::Series;
+use crate::output::{self, Output};
 use crate::{print_out, Datasource};
 use custom_backend::dynamic_library_loader::ExternalBackends;
-use backend::{meteo, nws, openweathermap, openweathermap_onecall, WeatherForecast};
+use backend::{
+    get_forecast_sentence, meteo, metno, nws, openweathermap, openweathermap_onecall, WeatherForecast,
+};
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
 use local::cache::prune;
 use local::settings::Settings;
 use local::weather_file::WeatherFile;
@@ -16,14 +23,15 @@ use local::location::Coordinates;
 use log::{debug, warn};
 use serde_json::Value;
 use std::path::Path;
-use terminal::prompt;
+use terminal::{color, prompt};
+use terminal::text::terminal_width;
 use weather_dirs::resources_dir;
 
 pub fn get_data_from_datasource(
     datasource: Datasource,
     coordinates: Coordinates,
     settings: Settings,
-    custom_backends: ExternalBackends,
+    custom_backends: &ExternalBackends,
 ) -> crate::Result<WeatherForecast> {
     let dir = resources_dir()?;
     let f1 = dir.join("weather_codes.res");
@@ -48,7 +56,14 @@ pub fn get_data_from_datasource(
         }
         Datasource::NWS => nws::forecast::get_forecast(coordinates, settings),
         Datasource::Meteo => meteo::forecast::get_forecast(coordinates, settings),
-        Datasource::Other(s) => custom_backends.call(&s, conv_coords, settings),
+        Datasource::MetNo => metno::forecast::get_forecast(coordinates, settings),
+        Datasource::Other(s) => {
+            let mut data = custom_backends.call(&s, conv_coords, settings)?;
+            if data.forecast_sentence.is_empty() {
+                data.forecast_sentence = get_forecast_sentence(&data.forecast);
+            }
+            Ok(data)
+        }
     }
 }
 
@@ -57,7 +72,7 @@ pub fn weather(
     coordinates: Coordinates,
     settings: Settings,
     true_metric: bool,
-    json: bool,
+    output: Output,
     custom_backends: ExternalBackends,
 ) -> crate::Result<()> {
     debug!(
@@ -65,15 +80,95 @@ pub fn weather(
         coordinates.latitude, coordinates.longitude
     );
     debug!("Metric: {true_metric}");
-    debug!("json: {json}");
+    debug!("output: {output:?}");
     let mut s = settings.clone();
     s.metric_default = true_metric;
+    let data = get_data_from_datasource(datasource, coordinates, s, &custom_backends)?;
+    print_out(settings.layout_file, &settings.theme, data, output, true_metric)?;
+    Ok(())
+}
+
+pub fn graph(
+    datasource: Datasource,
+    coordinates: Coordinates,
+    settings: Settings,
+    true_metric: bool,
+    opts: GraphOpts,
+    custom_backends: &ExternalBackends,
+) -> crate::Result<()> {
+    let mut s = settings;
+    s.metric_default = true_metric;
     let data = get_data_from_datasource(datasource, coordinates, s, custom_backends)?;
-    print_out(settings.layout_file, data, json, true_metric)?;
+    let series = Series::from(opts.series);
+    let style = opts.style.map_or_else(|| series.style(), Into::into);
+    let size = (terminal_width(), opts.height);
+    let chart = crate::graph::chart(&data, series, opts.hours, style, size, true_metric)?;
+    if data.region.is_empty() {
+        println!("{}", series.name());
+    } else {
+        println!("{} in {}", series.name(), data.region);
+    }
+    for line in chart {
+        println!("{}", color::downsample(&line));
+    }
+    Ok(())
+}
+
+/// Prints the weather of a past day, or of an hour of it with `--time`, from the timemachine and
+/// day_summary endpoints of One Call
+pub fn history(
+    coordinates: Coordinates,
+    settings: Settings,
+    true_metric: bool,
+    opts: HistoryOpts,
+) -> crate::Result<()> {
+    let date = NaiveDate::parse_from_str(&opts.date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date {}, use YYYY-MM-DD", opts.date))?;
+    let mut s = settings;
+    s.metric_default = true_metric;
+    let out = match opts.time {
+        Some(time) => {
+            let time = NaiveTime::parse_from_str(&time, "%H:%M")
+                .map_err(|_| format!("Invalid time {time}, use HH:MM"))?;
+            let time = Utc.from_utc_datetime(&date.and_time(time)).timestamp();
+            let data = openweathermap_onecall::forecast::get_historical(coordinates, s, time)?;
+            output::past_hour(&data, true_metric)?
+        }
+        None => {
+            let date = date.format("%Y-%m-%d").to_string();
+            let data = openweathermap_onecall::forecast::get_day_summary(coordinates, s, &date)?;
+            output::past_day(&data, true_metric)?
+        }
+    };
+    println!("{out}");
+    Ok(())
+}
+
+/// Reads or writes `<plugin>.<key>` in the plugin config
+fn plugin_config(plugin: &str, key: &str, value: Option<String>) -> crate::Result<()> {
+    let mut settings = Settings::new()?;
+    match value {
+        None => println!(
+            "{plugin}.{key}: {}",
+            settings.get_plugin_config(plugin, key).unwrap_or("not set")
+        ),
+        Some(real_value) => {
+            println!("Writing {plugin}.{key}={real_value} ...");
+            settings
+                .plugin_config
+                .entry(plugin.to_string())
+                .or_default()
+                .insert(key.to_string(), real_value);
+            settings.write()?;
+        }
+    }
     Ok(())
 }
 
 pub fn config(key_name: String, value: Option<String>) -> crate::Result<()> {
+    if let Some((plugin, key)) = key_name.split_once('.') {
+        return plugin_config(plugin, key, value);
+    }
     match value {
         None => {
             let f = WeatherFile::settings()?;
@@ -115,6 +210,7 @@ pub fn credits() {
     println!(
         "Backends:
     Meteo - https://open-meteo.com
+    MET Norway - https://api.met.no
     Open Weather Map - https://openweathermap.org/
     NWS - https://weather.gov"
     );
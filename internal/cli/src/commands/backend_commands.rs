@@ -1,46 +1,172 @@
-use crate::arguments::BackendOpts;
-use custom_backend::dynamic_library_loader::is_valid_ext;
+use crate::arguments::{BackendOpts, NewBackendOpts};
+use custom_backend::conformance::{validate_forecast, FIXTURE_COORDINATES};
+use custom_backend::manifest::{forget_verified, inspect_plugin, record_verified, PluginManifest};
+use custom_backend::{is_valid_ext, load_custom_backends, load_plugin, template, wasm_loader};
 use local::list_dir;
-use local::settings::Settings;
+use local::settings::{PluginConfigKey, Settings};
 use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
-use terminal::color;
+use terminal::color::{self, downsample};
 use terminal::prompt;
 use weather_dirs::custom_backends_dir;
 
 fn install(path: String) -> crate::Result<()> {
-    // TODO: Add validity checks (prompt user for permission first)
     let real_path = PathBuf::from_str(&path).unwrap();
     let file_name = real_path.file_name().ok_or("Not a file")?.to_str().unwrap();
     if !is_valid_ext(file_name) {
-        return Err("Not a valid system extension, aborting")?;
+        return Err("Not a native library or WASM module, aborting")?;
     }
-    fs::copy(&real_path, custom_backends_dir()?.join(file_name))?;
+    let manifest_path = PluginManifest::path_for(&real_path);
+    let manifest = PluginManifest::read(&manifest_path)?;
+    manifest.verify(&real_path)?;
+    let author = if manifest.author.is_empty() {
+        "an unknown author"
+    } else {
+        &manifest.author
+    };
+    println!(
+        "{}",
+        downsample(&format!(
+            "{}{} {}{} by {author}",
+            color::FORE_CYAN,
+            manifest.name,
+            manifest.version,
+            color::FORE_RESET
+        ))
+    );
+    if manifest.network_hosts.is_empty() {
+        println!("It does not declare any network access");
+    } else {
+        println!("It will connect to:");
+        for host in &manifest.network_hosts {
+            println!("    {host}");
+        }
+    }
+    if !wasm_loader::is_valid_ext(file_name) {
+        println!(
+            "{}",
+            downsample(&format!(
+                "{}Native plugins run with the same permissions as weather, only install plugins you trust",
+                color::FORE_YELLOW
+            ))
+        );
+    }
+    println!(
+        "{}",
+        downsample(&format!("{}Install {}?", color::FORE_RESET, manifest.name))
+    );
+    if !prompt::yes_no(false, None)? {
+        println!("Cancelled");
+        return Ok(());
+    }
+    let dir = custom_backends_dir()?;
+    fs::create_dir_all(&dir)?;
+    fs::copy(&real_path, dir.join(file_name))?;
+    fs::copy(
+        &manifest_path,
+        PluginManifest::path_for(dir.join(file_name)),
+    )?;
+    record_verified(dir.join(file_name))?;
     Ok(())
 }
 
+fn print_config_keys(settings: &Settings, backend: &str, keys: &[PluginConfigKey]) {
+    for key in keys {
+        let value = settings.get_plugin_config(backend, &key.name);
+        let color = if value.is_none() && key.required {
+            color::FORE_RED
+        } else {
+            color::FORE_RESET
+        };
+        println!(
+            "{}",
+            downsample(&format!(
+                "        {color}{backend}.{} ({:?}): {}{} {}",
+                key.name,
+                key.value_type,
+                value.unwrap_or("not set"),
+                color::FORE_RESET,
+                key.help
+            ))
+        );
+    }
+}
+
 fn list(settings: Settings) -> crate::Result<()> {
-    let paths = list_dir(custom_backends_dir()?)?;
-    for path in paths {
-        // TODO: Check which ones are valid (hard to do)
-        let file_name = &*path;
-        if is_valid_ext(file_name) {
-            let valid = settings.enable_custom_backends;
-            if valid {
-                println!("{}{file_name}", color::FORE_GREEN);
-            } else {
-                println!("{}{file_name}", color::FORE_RED);
+    if !settings.enable_custom_backends {
+        println!(
+            "{}",
+            downsample(&format!(
+                "{}Custom backends are disabled, enable them with `weather config ENABLE_CUSTOM_BACKENDS true`",
+                color::FORE_YELLOW
+            ))
+        );
+    }
+    let dir = custom_backends_dir()?;
+    let paths = if dir.exists() {
+        list_dir(dir.clone())?
+    } else {
+        Vec::new()
+    };
+    for path in paths.iter().filter(|path| is_valid_ext(path)) {
+        let report = inspect_plugin(dir.join(path));
+        let version = report
+            .manifest
+            .map(|manifest| format!(" {} {}", manifest.name, manifest.version))
+            .unwrap_or_default();
+        let status = match (report.error, report.warning) {
+            (Some(e), _) => format!("{}{}{version}: {e}", color::FORE_RED, report.file),
+            (None, Some(warning)) => {
+                format!("{}{}{version}: {warning}", color::FORE_YELLOW, report.file)
             }
+            (None, None) => format!("{}{}{version}", color::FORE_GREEN, report.file),
+        };
+        println!("{}", downsample(&status));
+        for backend in report.backends {
+            println!(
+                "{}",
+                downsample(&format!(
+                    "    {}{}{} {}",
+                    color::FORE_CYAN,
+                    backend.name,
+                    color::FORE_RESET,
+                    backend.help.unwrap_or_default()
+                ))
+            );
+            print_config_keys(&settings, &backend.name, &backend.config);
         }
     }
+    for (name, script) in &settings.script_backends {
+        println!(
+            "{}",
+            downsample(&format!(
+                "{}{name} (script: {})",
+                color::FORE_GREEN,
+                script.command
+            ))
+        );
+        if let Some(help) = &script.help {
+            println!(
+                "{}",
+                downsample(&format!("    {}{help}", color::FORE_RESET))
+            );
+        }
+        print_config_keys(&settings, name, &script.config);
+    }
     Ok(())
 }
 
 fn select(settings: Settings) -> crate::Result<()> {
     let selected = settings.default_backend;
     let mut settings = Settings::new()?;
-    let choices = ["openweathermap", "meteo", "nws", "openweathermap_onecall"];
+    let choices = [
+        "openweathermap",
+        "meteo",
+        "nws",
+        "openweathermap_onecall",
+        "metno",
+    ];
     let selected_usize = choices.iter().position(|&i| i == selected).unwrap_or(0);
     let choice = prompt::radio(&choices, selected_usize, None)?;
     settings.default_backend = choices[choice].to_string();
@@ -60,7 +186,10 @@ fn open_weather_map_api_key(settings: Settings) -> crate::Result<()> {
             "Length of api key is not 32, this likely means you have entered an invalid api key.",
         )?;
     }
-    println!("{}Saving api key as {s}", color::FORE_BLUE); // TODO: Fix
+    println!(
+        "{}",
+        downsample(&format!("{}Saving api key as {s}", color::FORE_BLUE))
+    ); // TODO: Fix
     let mut settings = Settings::new()?; // TODO: Fix excess read
     settings.open_weather_map_api_key = s;
     settings.write()?;
@@ -74,7 +203,10 @@ fn bing_maps_api_key(settings: Settings) -> crate::Result<()> {
         Some(original),
     )?;
     s = s.trim().to_string();
-    println!("{}Saving api key as {s}", color::FORE_BLUE);
+    println!(
+        "{}",
+        downsample(&format!("{}Saving api key as {s}", color::FORE_BLUE))
+    );
     let mut settings = Settings::new()?; // TODO: Fix excess read
     settings.bing_maps_api_key = s;
     settings.write()?;
@@ -82,9 +214,107 @@ fn bing_maps_api_key(settings: Settings) -> crate::Result<()> {
 }
 
 fn delete() -> crate::Result<()> {
-    let paths = list_dir(custom_backends_dir()?)?;
-    let choice = paths[prompt::radio(&paths, 0, None)?].to_string();
-    fs::remove_file(custom_backends_dir()?.join(&*choice))?;
+    let paths: Vec<String> = list_dir(custom_backends_dir()?)?
+        .into_iter()
+        .filter(|path| is_valid_ext(path))
+        .collect();
+    if paths.is_empty() {
+        return Err("No custom backends are installed")?;
+    }
+    let choice = custom_backends_dir()?.join(&paths[prompt::radio(&paths, 0, None)?]);
+    fs::remove_file(&choice)?;
+    forget_verified(&choice)?;
+    let manifest = PluginManifest::path_for(&choice);
+    if manifest.exists() {
+        fs::remove_file(manifest)?;
+    }
+    Ok(())
+}
+
+fn new(opts: NewBackendOpts) -> crate::Result<()> {
+    let dir = PathBuf::from(opts.path.unwrap_or_else(|| opts.name.clone()));
+    template::create_plugin(&dir, &opts.name)?;
+    println!(
+        "{}",
+        downsample(&format!(
+            "{}Created {} in {}{}",
+            color::FORE_GREEN,
+            opts.name,
+            dir.display(),
+            color::FORE_RESET
+        ))
+    );
+    println!("See {} for the next steps", dir.join("README.md").display());
+    Ok(())
+}
+
+/// Calls the backend at every fixture location and validates the forecasts
+fn test(backend: String, settings: Settings) -> crate::Result<()> {
+    let path = PathBuf::from(&backend);
+    let (backends, names) = if path.is_file() {
+        let manifest = PluginManifest::read(PluginManifest::path_for(&path))
+            .and_then(|manifest| manifest.verify(&path));
+        if let Err(e) = manifest {
+            println!(
+                "{}",
+                downsample(&format!(
+                    "{}{e}, the plugin can't be installed until this is fixed{}",
+                    color::FORE_YELLOW,
+                    color::FORE_RESET
+                ))
+            );
+        }
+        let backends = load_plugin(&path)?;
+        let names: Vec<String> = backends.list().into_iter().map(|b| b.name).collect();
+        if names.is_empty() {
+            return Err("The plugin does not provide any backends")?;
+        }
+        (backends, names)
+    } else {
+        let backends = load_custom_backends(&settings)?;
+        if !backends.list().iter().any(|b| b.name == backend) {
+            return Err(format!("No custom backend named {backend} is installed"))?;
+        }
+        (backends, vec![backend])
+    };
+    let mut failures = 0;
+    for name in &names {
+        for (place, coordinates) in FIXTURE_COORDINATES {
+            let problems = match backends.call(name, coordinates, settings.clone()) {
+                Ok(forecast) => validate_forecast(&forecast)?,
+                Err(e) => vec![format!("call failed: {e}")],
+            };
+            if problems.is_empty() {
+                println!(
+                    "{}",
+                    downsample(&format!(
+                        "{}PASS{} {name} at {place}",
+                        color::FORE_GREEN,
+                        color::FORE_RESET
+                    ))
+                );
+            } else {
+                failures += 1;
+                println!(
+                    "{}",
+                    downsample(&format!(
+                        "{}FAIL{} {name} at {place}",
+                        color::FORE_RED,
+                        color::FORE_RESET
+                    ))
+                );
+                for problem in problems {
+                    println!("    {problem}");
+                }
+            }
+        }
+    }
+    if failures > 0 {
+        return Err(format!(
+            "{failures} of {} checks failed",
+            names.len() * FIXTURE_COORDINATES.len()
+        ))?;
+    }
     Ok(())
 }
 
@@ -96,6 +326,8 @@ pub fn subcommand(arg: BackendOpts, settings: Settings) -> crate::Result<()> {
         BackendOpts::OpenWeatherMapApiKey => open_weather_map_api_key(settings)?,
         BackendOpts::BingMapsApiKey => bing_maps_api_key(settings)?,
         BackendOpts::Delete => delete()?,
+        BackendOpts::New(opts) => new(opts)?,
+        BackendOpts::Test(opts) => test(opts.backend, settings)?,
     }
     Ok(())
 }
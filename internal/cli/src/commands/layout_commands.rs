@@ -1,19 +1,45 @@
-use crate::arguments::LayoutOpts;
-use crate::layout::LayoutFile;
-use local::list_dir;
+use crate::arguments::{LayoutOpts, PreviewOpts};
+use crate::layout::theme::{self, Theme};
+use crate::layout::{check, read_layout, sample, LayoutFile, EXTENSIONS};
 use local::settings::Settings;
+use local::weather_file::WeatherFile;
+use local::{hash_file, list_dir};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use terminal::color::{FORE_BLUE, FORE_GREEN, FORE_LIGHTMAGENTA, RESET};
+use std::thread;
+use std::time::Duration;
+use terminal::color::{
+    downsample, FORE_BLUE, FORE_GREEN, FORE_LIGHTBLACK, FORE_LIGHTMAGENTA, FORE_RED, FORE_YELLOW,
+    RESET,
+};
+use updater::layout_index::{download_layout, get_layout_index, LayoutEntry};
 use weather_dirs::layouts_dir;
 
-fn install(path: String) -> crate::Result<()> {
+/// Checks a layout in the layouts directory, it is removed again if it is invalid
+fn validate(file_name: &str) -> crate::Result<()> {
+    println!("Checking validity ..."); // TODO: Tech debt (don't copy, check first)
+    let test = LayoutFile::new(file_name.to_string());
+    match test {
+        Err(e) => {
+            println!("Invalid layout, {e}");
+            fs::remove_file(layouts_dir()?.join(file_name))?;
+        }
+        Ok(_) => println!("Valid layout!"),
+    }
+    Ok(())
+}
+
+fn install(path: String, settings: &Settings) -> crate::Result<()> {
+    if !Path::new(&path).is_file() {
+        return install_from_gallery(&path, settings);
+    }
     let real_path = PathBuf::from_str(&path).unwrap();
     let file_name = real_path.file_name().ok_or("Not a file")?.to_str().unwrap();
     let ext = real_path.extension().unwrap_or_else(|| "".as_ref());
-    if ext != "res" {
-        return Err("File has to have an extension of .res")?;
+    if !EXTENSIONS.iter().any(|e| ext == *e) {
+        return Err("File has to have an extension of .json, .toml or .res")?;
     }
     if file_name == "default.res" {
         return Err("File name cannot be default.res,\
@@ -21,14 +47,196 @@ fn install(path: String) -> crate::Result<()> {
         please rename the file and try again.")?; // TODO: Prompt for a new name?
     }
     fs::copy(&real_path, layouts_dir()?.join(file_name))?;
-    println!("Checking validity ..."); // TODO: Tech debt (don't copy, check first)
-    let test = LayoutFile::new(file_name.to_string());
-    match test {
-        Err(e) => {
-            println!("Invalid layout, {e}");
-            fs::remove_file(layouts_dir()?.join(file_name))?;
+    validate(file_name)
+}
+
+/// Where a layout of the gallery is installed, only the file name in the index is used
+fn gallery_path(entry: &LayoutEntry) -> crate::Result<PathBuf> {
+    let file_name = Path::new(&entry.file)
+        .file_name()
+        .ok_or("Invalid file name in the layout index")?;
+    Ok(layouts_dir()?.join(file_name))
+}
+
+/// Hashes of the layouts installed from the gallery by file name, as they were installed
+fn installed_from_gallery() -> crate::Result<HashMap<String, String>> {
+    let file = WeatherFile::new("gallery_layouts.json")?;
+    Ok(serde_json::from_slice(&file.data).unwrap_or_default())
+}
+
+/// Loads a downloaded layout from a temporary file with the same extension, so an invalid layout
+/// or one for a newer version of weather never replaces the installed one
+fn check_download(path: &Path, data: &[u8]) -> crate::Result<()> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = std::env::temp_dir().join(format!("weather-gallery-{file_name}"));
+    fs::write(&temp, data)?;
+    let layout = LayoutFile::from_path(&temp);
+    fs::remove_file(&temp)?;
+    if let Err(e) = layout {
+        return Err(format!("Invalid layout, {e}"))?;
+    }
+    Ok(())
+}
+
+/// Writes a valid layout of the gallery and records it as installed from the gallery
+fn write_from_gallery(entry: &LayoutEntry, data: &[u8]) -> crate::Result<()> {
+    let path = gallery_path(entry)?;
+    check_download(&path, data)?;
+    fs::write(&path, data)?;
+    let mut installed = installed_from_gallery()?;
+    installed.insert(
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        local::hash(data),
+    );
+    let mut file = WeatherFile::new("gallery_layouts.json")?;
+    file.data = serde_json::to_vec(&installed)?;
+    file.write()
+}
+
+fn install_from_gallery(name: &str, settings: &Settings) -> crate::Result<()> {
+    let index = get_layout_index(&settings.update_server)?;
+    let entry = index
+        .iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| {
+            format!("{name} is not a file or a layout in the gallery, see weather layout browse")
+        })?;
+    let path = gallery_path(entry)?;
+    let file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    if matches!(
+        gallery_status(entry)?,
+        GalleryStatus::Modified | GalleryStatus::NotFromGallery
+    ) && !confirm_overwrite(&file_name)?
+    {
+        return Ok(());
+    }
+    println!("Downloading {name} ...");
+    write_from_gallery(entry, &download_layout(&settings.update_server, entry)?)?;
+    println!("Installed {file_name}, use weather layout select to use it");
+    Ok(())
+}
+
+/// How the installed file of a layout of the gallery compares to the gallery
+enum GalleryStatus {
+    NotInstalled,
+    /// The same as in the index
+    UpToDate,
+    /// Installed from the gallery and unchanged since, the index has a newer version
+    UpdateAvailable,
+    /// Installed from the gallery, but the file was changed since
+    Modified,
+    /// A file with the same name that wasn't installed from the gallery
+    NotFromGallery,
+}
+
+fn gallery_status(entry: &LayoutEntry) -> crate::Result<GalleryStatus> {
+    let path = gallery_path(entry)?;
+    if !path.is_file() {
+        return Ok(GalleryStatus::NotInstalled);
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let hash = hash_file(&path.display().to_string())?;
+    if hash == entry.hash {
+        return Ok(GalleryStatus::UpToDate);
+    }
+    Ok(match installed_from_gallery()?.get(&*file_name) {
+        None => GalleryStatus::NotFromGallery,
+        Some(installed) if *installed != hash => GalleryStatus::Modified,
+        Some(_) => GalleryStatus::UpdateAvailable,
+    })
+}
+
+/// Asks whether to replace a layout that wasn't installed from the gallery or was changed since
+fn confirm_overwrite(file_name: &str) -> crate::Result<bool> {
+    println!(
+        "{}",
+        downsample(&format!(
+            "{FORE_YELLOW}{file_name} was changed or not installed from the gallery, replace it?{RESET}"
+        ))
+    );
+    Ok(terminal::prompt::yes_no(false, None)?)
+}
+
+fn browse(settings: &Settings) -> crate::Result<()> {
+    for entry in get_layout_index(&settings.update_server)? {
+        let status = match gallery_status(&entry)? {
+            GalleryStatus::NotInstalled => "",
+            GalleryStatus::UpToDate => " (installed)",
+            GalleryStatus::UpdateAvailable => " (update available)",
+            GalleryStatus::Modified => " (installed, changed locally)",
+            GalleryStatus::NotFromGallery => " (a layout with the same file name is installed)",
+        };
+        let mut out = format!(
+            "{FORE_GREEN}{}{FORE_LIGHTBLACK}{status}\n{FORE_BLUE}{}\n",
+            entry.name, entry.description
+        );
+        for line in entry.preview.lines() {
+            out += &format!("{FORE_LIGHTBLACK}│ {RESET}{line}\n");
         }
-        Ok(_) => println!("Valid layout!"),
+        println!("{}", downsample(&out));
+    }
+    println!("Install a layout with weather layout install [name]");
+    Ok(())
+}
+
+/// Updates a layout if there is a newer version, whether it was updated
+fn update_entry(
+    entry: &LayoutEntry,
+    installed: &HashMap<String, String>,
+    settings: &Settings,
+) -> crate::Result<bool> {
+    match gallery_status(entry)? {
+        GalleryStatus::UpdateAvailable => {}
+        GalleryStatus::Modified => {
+            let path = gallery_path(entry)?;
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            // Only ask if the gallery changed since it was installed
+            if installed.get(&*file_name) == Some(&entry.hash) || !confirm_overwrite(&file_name)? {
+                return Ok(false);
+            }
+        }
+        _ => return Ok(false),
+    }
+    println!("Updating {} ...", entry.name);
+    let data = download_layout(&settings.update_server, entry)?;
+    write_from_gallery(entry, &data)?;
+    Ok(true)
+}
+
+/// Updates the layouts installed from the gallery, layouts changed since are only replaced if
+/// the user agrees. A layout that fails to update is kept as it is.
+fn update(settings: &Settings) -> crate::Result<()> {
+    let mut updated = 0;
+    let mut failed = 0;
+    let installed = installed_from_gallery()?;
+    for entry in get_layout_index(&settings.update_server)? {
+        match update_entry(&entry, &installed, settings) {
+            Ok(true) => updated += 1,
+            Ok(false) => {}
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "{}",
+                    downsample(&format!(
+                        "{FORE_RED}Failed to update {}, {e}{RESET}",
+                        entry.name
+                    ))
+                );
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} layouts could not be updated"))?;
+    }
+    if updated == 0 {
+        println!("All layouts from the gallery are up to date");
     }
     Ok(())
 }
@@ -40,9 +248,14 @@ fn list(settings: Settings) -> crate::Result<()> {
         let tmp = path?.file_name();
         let file_name = tmp.to_str().unwrap();
         if file_name == current_layout {
-            println!("{FORE_LIGHTMAGENTA}*{FORE_GREEN} {file_name}{RESET}");
+            println!(
+                "{}",
+                downsample(&format!(
+                    "{FORE_LIGHTMAGENTA}*{FORE_GREEN} {file_name}{RESET}"
+                ))
+            );
         } else {
-            println!("{FORE_BLUE}  {file_name}");
+            println!("{}", downsample(&format!("{FORE_BLUE}  {file_name}")));
         }
     }
     Ok(())
@@ -72,12 +285,108 @@ fn delete(settings: Settings) -> crate::Result<()> {
     Ok(())
 }
 
-pub fn subcommand(arg: LayoutOpts, settings: Settings) -> crate::Result<()> {
+/// A path to a layout, or the name of an installed one
+fn find_layout(path: &str) -> crate::Result<PathBuf> {
+    if Path::new(path).is_file() {
+        Ok(PathBuf::from(path))
+    } else {
+        Ok(layouts_dir()?.join(path))
+    }
+}
+
+fn check_layout(path: String, settings: Settings, metric: bool) -> crate::Result<()> {
+    // Colors are checked against the theme in use
+    theme::set_current(Theme::new(&settings.theme).unwrap_or_default());
+    let layout = read_layout(find_layout(&path)?)?;
+    let mut problems: Vec<String> = check::check(&layout, metric)?
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    for problem in &problems {
+        println!("{}", downsample(&format!("{FORE_YELLOW}{problem}{RESET}")));
+    }
+    println!(
+        "{}",
+        downsample(&format!("{FORE_BLUE}With a sample forecast:{RESET}"))
+    );
+    match LayoutFile::from_serde(layout)
+        .and_then(|layout| layout.to_string(sample::forecast("sunny", metric)?, metric))
+    {
+        Ok(out) => println!("{}", downsample(&out)),
+        Err(e) => {
+            println!("{}", downsample(&format!("{FORE_RED}{e}{RESET}")));
+            problems.push(e.to_string());
+        }
+    }
+    if !problems.is_empty() {
+        return Err(format!("Found {} problems in {path}", problems.len()))?;
+    }
+    println!(
+        "{}",
+        downsample(&format!("{FORE_GREEN}No problems found{RESET}"))
+    );
+    Ok(())
+}
+
+/// The layout rendered with each sample, errors are shown in place of the layout
+fn render_samples(path: &Path, samples: &[&str], metric: bool) -> String {
+    let layout = match LayoutFile::from_path(path) {
+        Ok(layout) => layout,
+        Err(e) => return format!("{FORE_RED}{e}{RESET}"),
+    };
+    let mut out = Vec::new();
+    for name in samples {
+        out.push(format!("{FORE_LIGHTBLACK}-- {name} --{RESET}"));
+        out.push(
+            sample::forecast(name, metric)
+                .and_then(|data| layout.to_string(data, metric))
+                .unwrap_or_else(|e| format!("{FORE_RED}{e}{RESET}")),
+        );
+    }
+    out.join("\n")
+}
+
+fn preview(opts: PreviewOpts, settings: Settings, metric: bool) -> crate::Result<()> {
+    theme::set_current(Theme::new(&settings.theme).unwrap_or_default());
+    let path = find_layout(&opts.path)?;
+    let samples = match &opts.sample {
+        Some(name) => vec![name.as_str()],
+        None => sample::SAMPLES.to_vec(),
+    };
+    if !opts.watch {
+        println!("{}", downsample(&render_samples(&path, &samples, metric)));
+        return Ok(());
+    }
+    // Polls the modification time, so editors that replace the file are noticed too
+    let mut last_modified = None;
+    loop {
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if modified != last_modified {
+            last_modified = modified;
+            print!("\x1b[2J\x1b[H");
+            println!("{}", downsample(&render_samples(&path, &samples, metric)));
+            println!(
+                "{}",
+                downsample(&format!(
+                    "{FORE_LIGHTBLACK}Watching {} for changes, press Ctrl+C to stop{RESET}",
+                    path.display()
+                ))
+            );
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+pub fn subcommand(arg: LayoutOpts, settings: Settings, metric: bool) -> crate::Result<()> {
     match arg {
-        LayoutOpts::Install(opts) => install(opts.path)?,
+        LayoutOpts::Install(opts) => install(opts.path, &settings)?,
+        LayoutOpts::Browse => browse(&settings)?,
+        LayoutOpts::Update => update(&settings)?,
         LayoutOpts::List => list(settings)?,
         LayoutOpts::Select => select(settings)?,
         LayoutOpts::Delete => delete(settings)?,
+        LayoutOpts::Check(opts) => check_layout(opts.path, settings, metric)?,
+        LayoutOpts::Preview(opts) => preview(opts, settings, metric)?,
     };
     Ok(())
 }
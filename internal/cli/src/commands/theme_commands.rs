@@ -0,0 +1,78 @@
+use crate::arguments::ThemeOpts;
+use crate::layout::theme::{Theme, EXTENSIONS};
+use local::list_dir;
+use local::settings::Settings;
+use std::fs;
+use std::path::PathBuf;
+use terminal::color::{downsample, FORE_BLUE, FORE_GREEN, FORE_LIGHTMAGENTA, RESET};
+use weather_dirs::themes_dir;
+
+fn install(path: String) -> crate::Result<()> {
+    let real_path = PathBuf::from(&path);
+    let file_name = real_path
+        .file_name()
+        .ok_or("Not a file")?
+        .to_string_lossy()
+        .to_string();
+    let ext = real_path.extension().unwrap_or_default();
+    if !EXTENSIONS.iter().any(|e| ext == *e) {
+        return Err("File has to have an extension of .json or .toml")?;
+    }
+    if real_path.file_stem().unwrap_or_default() == "default" {
+        return Err("File name cannot be default, as it is the name of the built in theme")?;
+    }
+    match Theme::from_path(&real_path) {
+        Err(e) => println!("Invalid theme, {e}"),
+        Ok(_) => {
+            fs::create_dir_all(themes_dir()?)?;
+            fs::copy(&real_path, themes_dir()?.join(&file_name))?;
+            println!("Installed {file_name}, use weather theme select to use it");
+        }
+    }
+    Ok(())
+}
+
+/// The built in theme followed by the installed themes
+fn themes() -> crate::Result<Vec<String>> {
+    let mut themes = vec!["default".to_string()];
+    if themes_dir()?.is_dir() {
+        themes.append(&mut list_dir(themes_dir()?)?);
+    }
+    Ok(themes)
+}
+
+fn list(settings: Settings) -> crate::Result<()> {
+    for theme in themes()? {
+        if theme == settings.theme {
+            println!(
+                "{}",
+                downsample(&format!("{FORE_LIGHTMAGENTA}*{FORE_GREEN} {theme}{RESET}"))
+            );
+        } else {
+            println!("{}", downsample(&format!("{FORE_BLUE}  {theme}{RESET}")));
+        }
+    }
+    Ok(())
+}
+
+fn select(settings: Settings) -> crate::Result<()> {
+    let themes = themes()?;
+    let current_index = themes
+        .iter()
+        .position(|theme| *theme == settings.theme)
+        .unwrap_or(0);
+    let choice = terminal::prompt::radio(&themes, current_index, None)?;
+    let mut settings = settings;
+    settings.theme = themes[choice].to_string();
+    settings.write()?;
+    Ok(())
+}
+
+pub fn subcommand(arg: ThemeOpts, settings: Settings) -> crate::Result<()> {
+    match arg {
+        ThemeOpts::Install(opts) => install(opts.path)?,
+        ThemeOpts::List => list(settings)?,
+        ThemeOpts::Select => select(settings)?,
+    };
+    Ok(())
+}
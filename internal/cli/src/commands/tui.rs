@@ -0,0 +1,226 @@
+//! `weather tui`, a dashboard that stays open and refreshes the forecast
+
+mod draw;
+
+use std::time::{Duration, Instant};
+
+use backend::WeatherForecast;
+use chrono::Local;
+use custom_backend::dynamic_library_loader::ExternalBackends;
+use local::location::Coordinates;
+use local::settings::Settings;
+use terminal::color;
+use terminal::screen::{KeyCode, KeyModifiers, Screen};
+
+use crate::arguments::TuiOpts;
+use crate::commands::get_data_from_datasource;
+use crate::datasource_from_str;
+
+/// The built in backends the dashboard switches between
+const BACKENDS: [&str; 5] = [
+    "METEO",
+    "METNO",
+    "NWS",
+    "OPENWEATHERMAP",
+    "OPENWEATHERMAP_ONECALL",
+];
+/// Hours scrolled by page up and page down
+const PAGE: usize = 12;
+
+struct Location {
+    name: String,
+    /// Place to geocode, the current location if it is `None`
+    query: Option<String>,
+    coordinates: Option<Coordinates>,
+}
+
+struct Dashboard<'a> {
+    settings: Settings,
+    custom_backends: &'a ExternalBackends,
+    no_sys_loc: bool,
+    locations: Vec<Location>,
+    location: usize,
+    backends: Vec<String>,
+    backend: usize,
+    metric: bool,
+    scroll: usize,
+    forecast: Option<WeatherForecast>,
+    status: String,
+}
+
+impl Dashboard<'_> {
+    /// Coordinates of the selected location, they are only looked up once
+    fn coordinates(&mut self) -> crate::Result<Coordinates> {
+        let location = &mut self.locations[self.location];
+        if let Some(coordinates) = location.coordinates {
+            return Ok(coordinates);
+        }
+        let coordinates = match &location.query {
+            Some(query) => {
+                local::location::geocode(query.clone(), self.settings.bing_maps_api_key.clone())?
+            }
+            None => local::location::get(self.no_sys_loc, self.settings.constant_location)?,
+        };
+        location.coordinates = Some(coordinates);
+        Ok(coordinates)
+    }
+
+    /// Gets the forecast again, the last one stays if it fails
+    fn refresh(&mut self) {
+        let result = self.coordinates().and_then(|coordinates| {
+            let mut settings = self.settings.clone();
+            settings.metric_default = self.metric;
+            get_data_from_datasource(
+                datasource_from_str(&self.backends[self.backend]),
+                coordinates,
+                settings,
+                self.custom_backends,
+            )
+        });
+        match result {
+            Ok(forecast) => {
+                self.forecast = Some(forecast);
+                self.status = format!("Updated {}", Local::now().format("%H:%M"));
+            }
+            Err(e) => self.status = e.to_string(),
+        }
+    }
+
+    /// Forgets the forecast when it no longer matches what is selected
+    fn switch(&mut self) {
+        self.forecast = None;
+        self.scroll = 0;
+    }
+
+    fn scroll_down(&mut self, hours: usize) {
+        self.scroll = self.scroll.saturating_add(hours);
+    }
+
+    fn scroll_up(&mut self, hours: usize) {
+        self.scroll = self.scroll.saturating_sub(hours);
+    }
+
+    fn draw(&mut self, screen: &Screen) -> crate::Result<()> {
+        let (width, height) = screen.size();
+        let header = draw::Header {
+            location: &self.locations[self.location].name,
+            backend: &self.backends[self.backend],
+            metric: self.metric,
+            status: &self.status,
+        };
+        let lines: Vec<String> = draw::frame(
+            &header,
+            self.forecast.as_ref(),
+            &mut self.scroll,
+            width,
+            height,
+        )
+        .iter()
+        .map(|line| color::downsample(line))
+        .collect();
+        screen.draw(&lines)?;
+        Ok(())
+    }
+}
+
+/// Index after `index` in a list of `len` items, going around at the ends
+fn cycle(index: usize, len: usize, forward: bool) -> usize {
+    if forward {
+        (index + 1) % len
+    } else {
+        (index + len - 1) % len
+    }
+}
+
+pub fn tui(
+    opts: TuiOpts,
+    settings: Settings,
+    backend: String,
+    metric: bool,
+    no_sys_loc: bool,
+    custom_backends: &ExternalBackends,
+) -> crate::Result<()> {
+    let mut locations = vec![Location {
+        name: "Current location".to_string(),
+        query: None,
+        coordinates: None,
+    }];
+    locations.extend(opts.location.into_iter().map(|query| Location {
+        name: query.clone(),
+        query: Some(query),
+        coordinates: None,
+    }));
+    let mut backends: Vec<String> = BACKENDS.iter().map(|name| name.to_string()).collect();
+    // A custom backend can only be switched back to, the others aren't loaded
+    let backend = backends
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(&backend))
+        .unwrap_or_else(|| {
+            backends.insert(0, backend);
+            0
+        });
+    let mut dashboard = Dashboard {
+        settings,
+        custom_backends,
+        no_sys_loc,
+        locations,
+        location: 0,
+        backends,
+        backend,
+        metric,
+        scroll: 0,
+        forecast: None,
+        status: String::new(),
+    };
+    let interval = Duration::from_secs(opts.interval.max(1) * 60);
+    let screen = Screen::new()?;
+    let mut refreshed = Instant::now();
+    let mut stale = true;
+    loop {
+        if stale {
+            dashboard.status = "Loading ...".to_string();
+            dashboard.draw(&screen)?;
+            dashboard.refresh();
+            refreshed = Instant::now();
+            stale = false;
+        }
+        dashboard.draw(&screen)?;
+        let key = match screen.key(interval.saturating_sub(refreshed.elapsed()))? {
+            Some(key) => key,
+            // Timed out or the terminal was resized
+            None => {
+                stale = refreshed.elapsed() >= interval;
+                continue;
+            }
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('r') => stale = true,
+            KeyCode::Char('l' | 'L') | KeyCode::Tab | KeyCode::BackTab => {
+                let forward = matches!(key.code, KeyCode::Char('l') | KeyCode::Tab);
+                dashboard.location = cycle(dashboard.location, dashboard.locations.len(), forward);
+                dashboard.switch();
+                stale = true;
+            }
+            KeyCode::Char('b' | 'B') => {
+                let forward = key.code == KeyCode::Char('b');
+                dashboard.backend = cycle(dashboard.backend, dashboard.backends.len(), forward);
+                dashboard.switch();
+                stale = true;
+            }
+            KeyCode::Char('u') => {
+                dashboard.metric = !dashboard.metric;
+                dashboard.switch();
+                stale = true;
+            }
+            KeyCode::Down | KeyCode::Char('j') => dashboard.scroll_down(1),
+            KeyCode::Up | KeyCode::Char('k') => dashboard.scroll_up(1),
+            KeyCode::PageDown => dashboard.scroll_down(PAGE),
+            KeyCode::PageUp => dashboard.scroll_up(PAGE),
+            KeyCode::Home | KeyCode::Char('g') => dashboard.scroll = 0,
+            _ => {}
+        }
+    }
+    Ok(())
+}
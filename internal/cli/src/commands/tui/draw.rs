@@ -0,0 +1,212 @@
+//! The frames of the dashboard, every line is cut to the width of the terminal
+
+use backend::{DailyForecast, WeatherAlert, WeatherData, WeatherForecast};
+use terminal::color::{FORE_BLUE, FORE_LIGHTBLACK, FORE_RESET, FORE_YELLOW, RESET};
+use terminal::text::{display_width, pad, truncate, Align};
+
+use crate::layout::functions::{time, units};
+
+/// Width of a day in the daily strip
+const DAY_WIDTH: usize = 16;
+/// Alerts shown at most, the rest are counted
+const MAX_ALERTS: usize = 3;
+const KEYS: &str = "↑↓ scroll  l location  b backend  u units  r refresh  q quit";
+
+/// What the dashboard shows besides the forecast
+pub struct Header<'a> {
+    pub location: &'a str,
+    pub backend: &'a str,
+    pub metric: bool,
+    pub status: &'a str,
+}
+
+/// Formats a time in milliseconds in the local time zone
+/// Formats a time at the location of the forecast
+fn local_time(time: i128, format: &str, utc_offset: Option<i32>) -> String {
+    time::format(time as f64, format, false, utc_offset).unwrap_or_default()
+}
+
+fn temperature(temperature: f32) -> String {
+    format!("{temperature:.0}°")
+}
+
+fn wind(data: &WeatherData, metric: bool) -> String {
+    format!(
+        "{} {:.0} {}",
+        units::arrow(f64::from(data.wind.heading)),
+        data.wind.speed,
+        if metric { "km/h" } else { "mph" }
+    )
+}
+
+/// A line with a title, filled to the width of the screen
+fn section(title: &str, width: usize) -> String {
+    pad(
+        &format!("{FORE_LIGHTBLACK}── {title} "),
+        width,
+        Align::Left,
+        '─',
+    )
+}
+
+fn title(header: &Header, width: usize) -> String {
+    let units = if header.metric { "metric" } else { "imperial" };
+    let left = format!(
+        "{FORE_BLUE}{}{FORE_RESET} · {} · {units}",
+        header.location, header.backend
+    );
+    let right = format!("{FORE_LIGHTBLACK}{}", header.status);
+    let space = width.saturating_sub(display_width(&left) + display_width(&right));
+    format!("{left}{}{right}", " ".repeat(space.max(1)))
+}
+
+fn current(data: &WeatherForecast, metric: bool) -> Vec<String> {
+    let now = &data.current_weather;
+    let place = [&data.region, &data.country]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| part.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    vec![
+        format!(
+            "{FORE_YELLOW}{}{RESET} {}  {FORE_LIGHTBLACK}{place}",
+            temperature(now.temperature),
+            now.condition_sentence
+        ),
+        format!(
+            "Feels like {}  Wind {}  Clouds {}%  AQI {}",
+            temperature(now.feels_like),
+            wind(now, metric),
+            now.cloud_cover,
+            now.aqi
+        ),
+        data.forecast_sentence.clone(),
+    ]
+}
+
+fn alerts(alerts: &[WeatherAlert], utc_offset: Option<i32>, width: usize) -> Vec<String> {
+    let mut lines = vec![section("Alerts", width)];
+    for alert in alerts.iter().take(MAX_ALERTS) {
+        lines.push(format!(
+            "{FORE_YELLOW}! {}{RESET} until {}  {FORE_LIGHTBLACK}{}",
+            alert.event,
+            local_time(alert.end, "%a %H:%M", utc_offset),
+            alert.sender
+        ));
+    }
+    if alerts.len() > MAX_ALERTS {
+        lines.push(format!("and {} more", alerts.len() - MAX_ALERTS));
+    }
+    lines
+}
+
+/// Days side by side, as many as fit
+fn daily(days: &[DailyForecast], utc_offset: Option<i32>, width: usize) -> Vec<String> {
+    let fit = (width / DAY_WIDTH).max(1);
+    let mut temperatures = String::new();
+    let mut conditions = String::new();
+    for day in days.iter().take(fit) {
+        let cell = format!(
+            "{} {FORE_BLUE}{}{FORE_RESET}/{FORE_YELLOW}{}{FORE_RESET}",
+            local_time(day.time, "%a", utc_offset),
+            temperature(day.min_temp),
+            temperature(day.max_temp)
+        );
+        temperatures += &pad(&cell, DAY_WIDTH, Align::Left, ' ');
+        let sentence = format!("{FORE_LIGHTBLACK}{}{FORE_RESET}", day.condition_sentence);
+        conditions += &pad(&sentence, DAY_WIDTH - 1, Align::Left, ' ');
+        conditions.push(' ');
+    }
+    vec![section("Daily", width), temperatures, conditions]
+}
+
+fn hour_row(data: &WeatherData, metric: bool, utc_offset: Option<i32>) -> String {
+    let columns = [
+        (local_time(data.time, "%a %H:%M", utc_offset), 10),
+        (temperature(data.temperature), 6),
+        (temperature(data.feels_like), 6),
+        (wind(data, metric), 11),
+        (format!("{}%", data.cloud_cover), 7),
+        (data.aqi.to_string(), 4),
+    ];
+    let mut row: String = columns
+        .iter()
+        .map(|(text, width)| pad(text, *width, Align::Left, ' '))
+        .collect();
+    row += &data.condition_sentence;
+    row
+}
+
+/// The hourly table with `rows` hours from `scroll` on
+fn hourly(
+    hours: &[WeatherData],
+    metric: bool,
+    utc_offset: Option<i32>,
+    scroll: &mut usize,
+    rows: usize,
+    width: usize,
+) -> Vec<String> {
+    // The last hour stays at the bottom
+    *scroll = (*scroll).min(hours.len().saturating_sub(rows));
+    let first = *scroll;
+    let mut lines = vec![
+        section(
+            &format!(
+                "Hourly {}-{} of {}",
+                first + 1,
+                (first + rows).min(hours.len()),
+                hours.len()
+            ),
+            width,
+        ),
+        format!("{FORE_LIGHTBLACK}Time      Temp  Feels Wind       Clouds AQI Conditions"),
+    ];
+    lines.extend(
+        hours
+            .iter()
+            .skip(first)
+            .take(rows)
+            .map(|hour| hour_row(hour, metric, utc_offset)),
+    );
+    lines
+}
+
+/// A frame of `height` lines for a terminal `width` columns wide, the hourly table gets the lines
+/// that are left and `scroll` is kept within it
+pub fn frame(
+    header: &Header,
+    forecast: Option<&WeatherForecast>,
+    scroll: &mut usize,
+    width: usize,
+    height: usize,
+) -> Vec<String> {
+    let mut lines = vec![title(header, width)];
+    if let Some(data) = forecast {
+        lines.extend(current(data, header.metric));
+        if !data.alerts.is_empty() {
+            lines.extend(alerts(&data.alerts, data.utc_offset, width));
+        }
+        if !data.daily.is_empty() {
+            lines.extend(daily(&data.daily, data.utc_offset, width));
+        }
+        // The section line, the column names and the footer
+        let rows = height.saturating_sub(lines.len() + 3);
+        if rows > 0 && !data.forecast.is_empty() {
+            lines.extend(hourly(
+                &data.forecast,
+                header.metric,
+                data.utc_offset,
+                scroll,
+                rows,
+                width,
+            ));
+        }
+    }
+    lines.truncate(height.saturating_sub(1));
+    while lines.len() + 1 < height {
+        lines.push(String::new());
+    }
+    lines.push(format!("{FORE_LIGHTBLACK}{KEYS}"));
+    lines.iter().map(|line| truncate(line, width)).collect()
+}
@@ -1,21 +1,25 @@
 use local::settings::Settings;
 use std::time::Duration;
 use std::thread;
-use terminal::color::{FORE_CYAN, FORE_LIGHTMAGENTA};
+use terminal::color::{downsample, FORE_CYAN, FORE_LIGHTMAGENTA};
 use terminal::prompt::{input, yes_no};
 use updater::component::update_component;
 
 pub fn setup(settings_s: Settings) -> crate::Result<()> {
     let mut settings = settings_s;
-    println!("{FORE_CYAN}===== Weather CLI Setup =====");
+    println!("{}", downsample(&format!("{FORE_CYAN}===== Weather CLI Setup =====")));
     updater::resource::update_web_resources(settings.update_server.clone(), None)?;
-    println!("{FORE_LIGHTMAGENTA}Choose the default weather backend: ");
+    println!(
+        "{}",
+        downsample(&format!("{FORE_LIGHTMAGENTA}Choose the default weather backend: "))
+    );
     let options = [
         "Meteo",
-        "Open Weather Map OneCall",
+        "Open Weather Map OneCall (API Key required)",
         "Open Weather Map (API Key required)",
         "National Weather Service",
         "The Weather Channel",
+        "MET Norway",
     ];
     let default = [
         "METEO",
@@ -23,6 +27,7 @@ pub fn setup(settings_s: Settings) -> crate::Result<()> {
         "OPENWEATHERMAP",
         "NWS",
         "THEWEATHERCHANNEL",
+        "METNO",
     ]
     .iter()
     .position(|&x| x == settings.default_backend.clone())
@@ -34,11 +39,19 @@ pub fn setup(settings_s: Settings) -> crate::Result<()> {
         "OPENWEATHERMAP",
         "NWS",
         "THEWEATHERCHANNEL",
+        "METNO",
     ][current];
     settings.default_backend = weather_backend_setting.to_string();
     settings.write()?;
-    if settings.default_backend == "OPENWEATHERMAP" {
-        println!("{FORE_LIGHTMAGENTA}Do you want to enter your openweathermap API key?");
+    if settings.default_backend == "OPENWEATHERMAP"
+        || settings.default_backend == "OPENWEATHERMAP_ONECALL"
+    {
+        println!(
+            "{}",
+            downsample(&format!(
+                "{FORE_LIGHTMAGENTA}Do you want to enter your openweathermap API key?"
+            ))
+        );
         let cont = yes_no(true, None)?;
         if cont {
             let resp = input(Some("Enter your openweathermap key: ".to_string()), None)?;
@@ -48,12 +61,15 @@ pub fn setup(settings_s: Settings) -> crate::Result<()> {
     }
     thread::sleep(Duration::from_millis(100));
     println!(
-        "{FORE_LIGHTMAGENTA}Is your location constant (i.e. is this computer stationary at all times)?"
+        "{}",
+        downsample(&format!(
+            "{FORE_LIGHTMAGENTA}Is your location constant (i.e. is this computer stationary at all times)?"
+        ))
     );
     settings.constant_location = yes_no(settings.constant_location, None)?;
     settings.write()?;
     thread::sleep(Duration::from_millis(100));
-    println!("{FORE_LIGHTMAGENTA}Should static resources (ascii art, weather code sentences, etc.) be auto-updated?");
+    println!("{}", downsample(&format!("{FORE_LIGHTMAGENTA}Should static resources (ascii art, weather code sentences, etc.) be auto-updated?")));
     settings.auto_update_internet_resources =
         yes_no(settings.auto_update_internet_resources, None)?;
     settings.write()?;
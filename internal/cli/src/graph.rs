@@ -0,0 +1,89 @@
+//! Charts of the hourly forecast, shown by `weather graph`
+
+use backend::{WeatherData, WeatherForecast};
+use terminal::chart::{Chart, Style};
+
+use crate::layout::functions::{chart, time};
+
+/// What can be charted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Series {
+    Temperature,
+    Precipitation,
+    Wind,
+    Aqi,
+}
+
+impl Series {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Temperature => "Temperature",
+            Self::Precipitation => "Precipitation",
+            Self::Wind => "Wind speed",
+            Self::Aqi => "Air quality index",
+        }
+    }
+
+    /// Precipitation is in millimeters in the forecast, it is shown in inches with imperial units
+    fn value(self, data: &WeatherData, metric: bool) -> f64 {
+        match self {
+            Self::Temperature => f64::from(data.temperature),
+            Self::Precipitation if metric => f64::from(data.precipitation),
+            Self::Precipitation => f64::from(data.precipitation) / 25.4,
+            Self::Wind => data.wind.speed,
+            Self::Aqi => f64::from(data.aqi),
+        }
+    }
+
+    fn unit(self, metric: bool) -> &'static str {
+        match (self, metric) {
+            (Self::Temperature, true) => "°C",
+            (Self::Temperature, false) => "°F",
+            (Self::Precipitation, true) => "mm",
+            (Self::Precipitation, false) => "in",
+            (Self::Wind, true) => "km/h",
+            (Self::Wind, false) => "mph",
+            (Self::Aqi, _) => "",
+        }
+    }
+
+    /// Amounts are bars, everything else a line
+    pub fn style(self) -> Style {
+        match self {
+            Self::Precipitation | Self::Aqi => Style::Bars,
+            Self::Temperature | Self::Wind => Style::Line,
+        }
+    }
+}
+
+/// A chart of `hours` hours of the forecast from the one now is in, `size` is the width and the
+/// height of the plot
+pub fn chart(
+    data: &WeatherForecast,
+    series: Series,
+    hours: usize,
+    style: Style,
+    size: (usize, usize),
+    metric: bool,
+) -> crate::Result<Vec<String>> {
+    let now = local::now() as f64;
+    let times: Vec<f64> = data.forecast.iter().map(|hour| hour.time as f64).collect();
+    let start = time::current_index(&times, now);
+    let end = (start + hours).min(data.forecast.len());
+    if start >= end {
+        return Err("There is no hourly forecast to chart".into());
+    }
+    let values: Vec<f64> = data.forecast[start..end]
+        .iter()
+        .map(|hour| series.value(hour, metric))
+        .collect();
+    let labels = chart::labels(&times[start..end], data.utc_offset);
+    let chart = Chart {
+        values: &values,
+        labels: &labels,
+        unit: series.unit(metric),
+        now: (times[start] <= now).then_some(0),
+        style,
+    };
+    Ok(chart.render(size.0, size.1))
+}
@@ -0,0 +1,350 @@
+//! Finds problems in layouts without rendering them, variables are checked against the full
+//! sample forecast and functions against the functions layouts can call
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::layout::expression::{self, Expr, Part};
+use crate::layout::layout_serde::{ItemSerde, LayoutSerde, RowSerde};
+use crate::layout::{check_version, functions, panel, parse_align, sample, theme, to_scope};
+use weather_error::Error;
+
+/// A problem and where it is, like `row 2, column 1, row 0, item 3`
+pub struct Problem {
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.location.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.location, self.message)
+        }
+    }
+}
+
+struct Checker {
+    location: Vec<String>,
+    problems: Vec<Problem>,
+}
+
+fn type_name(value: &Value) -> &'static str {
+    expression::Value::from_json(value).type_name()
+}
+
+impl Checker {
+    fn report<S: Into<String>>(&mut self, message: S) {
+        self.problems.push(Problem {
+            location: self.location.join(", "),
+            message: message.into(),
+        });
+    }
+
+    /// Reports the error of a result
+    fn check<T>(&mut self, result: crate::Result<T>) -> Option<T> {
+        match result {
+            Ok(t) => Some(t),
+            Err(Error::LayoutError(e)) => {
+                self.report(e.message);
+                None
+            }
+            Err(e) => {
+                self.report(e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Runs `f` with `location` added to the location of the problems it finds
+    fn at(&mut self, location: String, f: impl FnOnce(&mut Self)) {
+        self.location.push(location);
+        f(self);
+        self.location.pop();
+    }
+
+    fn color(&mut self, name: &str) {
+        if theme::with_current(|theme| theme.color(name)).is_none() {
+            self.report(format!("Unknown color or role {name}"));
+        }
+    }
+
+    fn call<'a>(&mut self, name: &str, args: usize, kwargs: impl Iterator<Item = &'a str>) {
+        match functions::get(name) {
+            Some(function) => {
+                self.check(function.check_arguments(args, kwargs));
+            }
+            None => self.report(format!("Function {name} not found")),
+        }
+    }
+
+    /// Follows a path like `forecast.[0].temperature` like `variable` items do
+    fn variable(&mut self, path: &str, scope: &Value) {
+        let mut current = scope;
+        for segment in path.split('.') {
+            let next = if segment.is_empty() {
+                return self.report(format!("Empty segment in variable {path}"));
+            } else if let Some(index) = segment.strip_prefix('[') {
+                if index
+                    .strip_suffix(']')
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .is_none()
+                {
+                    return self.report(format!("Invalid list index {segment} in variable {path}"));
+                }
+                // Lists in the sample can be shorter than real ones, all elements look alike
+                current.get(0)
+            } else {
+                current.get(segment)
+            };
+            current = match next {
+                Some(next) => next,
+                None => {
+                    return self.report(format!(
+                        "Variable {path} not found in the forecast ({segment} is missing)"
+                    ))
+                }
+            };
+        }
+        if !current.is_string() && !current.is_number() {
+            self.report(format!(
+                "Variable {path} is a {}, not a string or a number",
+                type_name(current)
+            ));
+        }
+    }
+
+    /// The sample value an expression stands for, `None` if it isn't a path in the forecast
+    fn shape(&mut self, expr: &Expr, scope: &Value) -> Option<Value> {
+        match expr {
+            Expr::Variable(name) => {
+                let value = scope.get(name).or_else(|| {
+                    scope
+                        .get("current_weather")
+                        .and_then(|current| current.get(name))
+                });
+                if value.is_none() {
+                    self.report(format!("Unknown variable {name}"));
+                }
+                value.cloned()
+            }
+            Expr::Field(expr, name) => match self.shape(expr, scope)? {
+                Value::Object(map) => {
+                    if !map.contains_key(name) {
+                        self.report(format!("Unknown field {name}"));
+                    }
+                    map.get(name).cloned()
+                }
+                // Fields of null are null
+                Value::Null => None,
+                value => {
+                    self.report(format!("a {} has no field {name}", type_name(&value)));
+                    None
+                }
+            },
+            Expr::Index(expr, index) => {
+                self.expr(index, scope);
+                match (self.shape(expr, scope)?, &**index) {
+                    (Value::Array(list), _) => list.first().cloned(),
+                    (Value::Object(map), Expr::Literal(expression::Value::String(key))) => {
+                        map.get(key).cloned()
+                    }
+                    _ => None,
+                }
+            }
+            _ => {
+                self.expr(expr, scope);
+                None
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, scope: &Value) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::Template(parts) => {
+                for part in parts {
+                    if let Part::Placeholder(expr, _) = part {
+                        self.expr(expr, scope);
+                    }
+                }
+            }
+            Expr::Variable(_) | Expr::Field(..) | Expr::Index(..) => {
+                self.shape(expr, scope);
+            }
+            Expr::Unary(_, expr) => self.expr(expr, scope),
+            Expr::Binary(_, left, right) => {
+                self.expr(left, scope);
+                self.expr(right, scope);
+            }
+            Expr::If(condition, then, otherwise) => {
+                self.expr(condition, scope);
+                self.expr(then, scope);
+                self.expr(otherwise, scope);
+            }
+            Expr::Call(name, args, kwargs) => {
+                self.call(name, args.len(), kwargs.iter().map(|(key, _)| key.as_str()));
+                for arg in args {
+                    self.expr(arg, scope);
+                }
+                for (_, arg) in kwargs {
+                    self.expr(arg, scope);
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, source: &str, scope: &Value) -> Option<Value> {
+        let expr = self.check(expression::parse(source))?;
+        self.shape(&expr, scope)
+    }
+
+    fn item(&mut self, item: &ItemSerde, scope: &Value) {
+        for color in [&item.color, &item.bg_color].into_iter().flatten() {
+            self.color(color);
+        }
+        match &*item.item_type {
+            "text" => {}
+            "variable" => self.variable(&item.value, scope),
+            "expression" => {
+                self.expression(&item.value, scope);
+            }
+            "function" => {
+                let args = item.args.as_deref().unwrap_or_default();
+                let kwargs = item.kwargs.iter().flatten();
+                self.call(
+                    &item.value,
+                    args.len(),
+                    kwargs.clone().map(|(key, _)| &**key),
+                );
+                for (i, arg) in args.iter().enumerate() {
+                    self.at(format!("argument {i}"), |checker| checker.item(arg, scope));
+                }
+                for (key, arg) in kwargs {
+                    self.at(format!("argument {key}"), |checker| {
+                        checker.item(arg, scope)
+                    });
+                }
+            }
+            other => self.report(format!(
+                "Unknown item type {other}, it has to be text, variable, function or expression"
+            )),
+        }
+    }
+
+    fn rows(&mut self, rows: &[RowSerde], scope: &Value) {
+        for (i, row) in rows.iter().enumerate() {
+            self.at(format!("row {i}"), |checker| checker.row(row, scope));
+        }
+    }
+
+    fn row(&mut self, row: &RowSerde, scope: &Value) {
+        match row {
+            RowSerde::Items(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.at(format!("item {i}"), |checker| checker.item(item, scope));
+                }
+            }
+            RowSerde::Repeat(repeat) => {
+                if repeat.step == 0 {
+                    self.report("The step of a repeat can't be 0");
+                }
+                // The rows are checked with the first element of the list
+                let element = match self.expression(&repeat.over, scope) {
+                    Some(Value::Array(list)) => list.first().cloned().unwrap_or_default(),
+                    Some(value) => {
+                        self.report(format!(
+                            "Can't repeat over {}, it is a {}",
+                            repeat.over,
+                            type_name(&value)
+                        ));
+                        Value::Null
+                    }
+                    None => Value::Null,
+                };
+                let mut scope = scope.clone();
+                if let Some(object) = scope.as_object_mut() {
+                    object.insert(repeat.name.clone(), element);
+                    object.insert(repeat.index.clone(), 0.into());
+                }
+                if let Some(filter) = &repeat.filter {
+                    self.expression(filter, &scope);
+                }
+                self.rows(&repeat.rows, &scope);
+            }
+            RowSerde::Columns(columns) => {
+                for (i, column) in columns.columns.iter().enumerate() {
+                    self.at(format!("column {i}"), |checker| {
+                        checker.check(parse_align(&column.align));
+                        if let Some(when) = &column.when {
+                            checker.expression(when, scope);
+                        }
+                        checker.rows(&column.rows, scope);
+                    });
+                }
+            }
+            RowSerde::Box(panel) => {
+                self.check(panel::border(&panel.border));
+                self.check(parse_align(&panel.align));
+                if let Some(color) = &panel.color {
+                    self.color(color);
+                }
+                self.rows(&panel.rows, scope);
+            }
+        }
+    }
+}
+
+/// Finds every problem of a layout instead of stopping at the first like rendering does, colors
+/// are checked against the current theme
+pub fn check(layout: &LayoutSerde, metric: bool) -> crate::Result<Vec<Problem>> {
+    let scope = to_scope(
+        sample::full(metric)?,
+        metric,
+        terminal::text::terminal_width(),
+    )?;
+    let mut checker = Checker {
+        location: Vec::new(),
+        problems: Vec::new(),
+    };
+    checker.check(check_version(layout.version));
+    let defaults = &layout.defaults;
+    for color in [
+        &defaults.variable_color,
+        &defaults.text_color,
+        &defaults.unit_color,
+        &defaults.variable_bg_color,
+        &defaults.text_bg_color,
+        &defaults.unit_bg_color,
+    ] {
+        checker.at("defaults".to_string(), |checker| checker.color(color));
+    }
+    checker.rows(&layout.layout, &scope);
+    Ok(checker.problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::source::compile;
+
+    #[test]
+    fn optional_lists() {
+        let layout = compile(
+            r#"{"version": 21, "layout": [
+                ["@alerts.[0].event", "=alerts[0].sender", "@raw_data.[0]"],
+                {"repeat": "alerts", "rows": [["=item.tags[0]"]]}
+            ]}"#,
+            "json",
+        )
+        .unwrap();
+        let problems: Vec<String> = check(&layout, true)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert!(problems.is_empty(), "{problems:?}");
+    }
+}
@@ -0,0 +1,116 @@
+use serde_json::Value;
+use terminal::color;
+use terminal::text::{display_width, pad, truncate};
+
+use crate::layout::expression;
+use crate::layout::layout_serde::{ColumnSerde, ColumnsSerde};
+use crate::layout::row::{render_rows, Row};
+use crate::layout::{parse_align, LayoutSettings};
+
+struct Column {
+    settings: ColumnSerde,
+    rows: Vec<Row>,
+}
+
+pub struct Columns {
+    columns: Vec<Column>,
+    separator: String,
+}
+
+impl Columns {
+    pub fn new(data: ColumnsSerde) -> Self {
+        let columns = data
+            .columns
+            .into_iter()
+            .map(|mut column| Column {
+                rows: std::mem::take(&mut column.rows)
+                    .into_iter()
+                    .map(Row::new)
+                    .collect(),
+                settings: column,
+            })
+            .collect();
+        Self {
+            columns,
+            separator: data.separator,
+        }
+    }
+
+    pub fn to_lines(
+        &self,
+        data: &Value,
+        settings: LayoutSettings,
+        metric: bool,
+        width: usize,
+    ) -> crate::Result<Vec<String>> {
+        let mut columns = Vec::new();
+        for column in &self.columns {
+            let shown = match &column.settings.when {
+                Some(when) => expression::eval_str(when, data)?.is_truthy(),
+                None => true,
+            };
+            if shown {
+                columns.push(column);
+            }
+        }
+        if columns.is_empty() {
+            return Ok(Vec::new());
+        }
+        let render = |column: &Column, width: usize| {
+            render_rows(&column.rows, data, settings.clone(), metric, width)
+        };
+        // Fixed and content sized columns are measured first, flexible columns share the rest
+        let mut widths = vec![0; columns.len()];
+        let mut cells = vec![None; columns.len()];
+        let mut used = display_width(&self.separator) * (columns.len() - 1);
+        let mut flex_total = 0;
+        for (i, column) in columns.iter().enumerate() {
+            match (column.settings.width, column.settings.flex) {
+                (Some(column_width), _) => widths[i] = column_width,
+                (None, Some(flex)) => flex_total += flex,
+                (None, None) => {
+                    let lines = render(column, width)?;
+                    widths[i] = lines.iter().map(|l| display_width(l)).max().unwrap_or(0);
+                    cells[i] = Some(lines);
+                }
+            }
+            used += widths[i];
+        }
+        let mut remaining = width.saturating_sub(used);
+        for (i, column) in columns.iter().enumerate() {
+            if let (None, Some(flex)) = (column.settings.width, column.settings.flex) {
+                widths[i] = (remaining * flex).checked_div(flex_total).unwrap_or(0);
+                remaining -= widths[i];
+                flex_total -= flex;
+            }
+        }
+        let mut rendered = Vec::new();
+        for ((column, cell), column_width) in columns.iter().zip(cells).zip(&widths) {
+            let lines = match cell {
+                Some(lines) => lines,
+                None => render(column, *column_width)?,
+            };
+            rendered.push((parse_align(&column.settings.align)?, lines));
+        }
+        let height = rendered
+            .iter()
+            .map(|(_, lines)| lines.len())
+            .max()
+            .unwrap_or(0);
+        Ok((0..height)
+            .map(|line| {
+                rendered
+                    .iter()
+                    .zip(&widths)
+                    .map(|((align, lines), column_width)| {
+                        let text = lines.get(line).map_or("", String::as_str);
+                        // Colors are reset so they don't spill into the padding
+                        let text = truncate(text, *column_width) + color::RESET;
+                        pad(&text, *column_width, *align, ' ')
+                    })
+                    .collect::<Vec<String>>()
+                    .join(&self.separator)
+            })
+            .collect())
+    }
+}
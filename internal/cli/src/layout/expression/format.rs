@@ -0,0 +1,102 @@
+use crate::layout::expression::Value;
+use crate::layout::layout_error;
+
+struct FormatSpec {
+    fill: char,
+    align: Option<char>,
+    width: usize,
+    precision: Option<usize>,
+}
+
+/// Parses `[[fill]align][width][.precision]`, like Rust's format specifiers
+fn parse_spec(spec: &str) -> crate::Result<FormatSpec> {
+    let chars: Vec<char> = spec.chars().collect();
+    let is_align = |c: Option<&char>| matches!(c, Some('<' | '>' | '^'));
+    let (fill, align, mut i) = if is_align(chars.get(1)) {
+        (chars[0], Some(chars[1]), 2)
+    } else if is_align(chars.first()) {
+        (' ', Some(chars[0]), 1)
+    } else {
+        (' ', None, 0)
+    };
+    let read_number = |i: &mut usize| {
+        let start = *i;
+        while chars.get(*i).map_or(false, char::is_ascii_digit) {
+            *i += 1;
+        }
+        chars[start..*i].iter().collect::<String>().parse().ok()
+    };
+    let width = read_number(&mut i).unwrap_or(0);
+    let mut precision = None;
+    if chars.get(i) == Some(&'.') {
+        i += 1;
+        precision = Some(
+            read_number(&mut i)
+                .ok_or_else(|| layout_error(format!("missing precision in format \"{spec}\"")))?,
+        );
+    }
+    if i != chars.len() {
+        return Err(layout_error(format!("invalid format \"{spec}\"")));
+    }
+    Ok(FormatSpec {
+        fill,
+        align,
+        width,
+        precision,
+    })
+}
+
+/// Formats a value with a format specifier, the precision is the number of decimals of
+/// a number or the maximum length of other values
+pub fn format_value(value: &Value, spec: &str) -> crate::Result<String> {
+    let spec = parse_spec(spec)?;
+    let text = match (value, spec.precision) {
+        (Value::Number(n), Some(precision)) => format!("{n:.precision$}"),
+        (_, Some(precision)) => value.to_string().chars().take(precision).collect(),
+        (_, None) => value.to_string(),
+    };
+    let padding = spec
+        .width
+        .saturating_sub(terminal::text::display_width(&text));
+    let align = spec.align.unwrap_or(match value {
+        Value::Number(_) => '>',
+        _ => '<',
+    });
+    let (left, right) = match align {
+        '>' => (padding, 0),
+        '^' => (padding / 2, padding - padding / 2),
+        _ => (0, padding),
+    };
+    let fill = |n: usize| spec.fill.to_string().repeat(n);
+    Ok(format!("{}{text}{}", fill(left), fill(right)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs() {
+        let number = Value::Number(3.25159);
+        let text = Value::String("abc".to_string());
+        assert_eq!(format_value(&number, ".2").unwrap(), "3.25");
+        assert_eq!(format_value(&number, "6.0").unwrap(), "     3");
+        assert_eq!(format_value(&number, "<5.1").unwrap(), "3.3  ");
+        assert_eq!(format_value(&text, "5").unwrap(), "abc  ");
+        assert_eq!(format_value(&text, ">5").unwrap(), "  abc");
+        assert_eq!(format_value(&text, "*^8").unwrap(), "**abc***");
+        assert_eq!(format_value(&text, ".2").unwrap(), "ab");
+        assert_eq!(format_value(&text, "2").unwrap(), "abc");
+        assert_eq!(format_value(&Value::Null, "-^3").unwrap(), "---");
+    }
+
+    #[test]
+    fn invalid_specs() {
+        let message = |spec: &str| format_value(&Value::Null, spec).unwrap_err().to_string();
+        assert_eq!(
+            message("5."),
+            "Layout Error: Error: missing precision in format \"5.\""
+        );
+        assert_eq!(message("5x"), "Layout Error: Error: invalid format \"5x\"");
+    }
+}
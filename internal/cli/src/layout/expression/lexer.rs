@@ -0,0 +1,128 @@
+use crate::layout::expression::syntax_error;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Number(f64),
+    /// A string literal, `{expression:format}` placeholders are kept as is
+    String(String),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+/// A token and the column (0 based, in chars) it starts at
+pub type Spanned = (Token, usize);
+
+const SYMBOLS: [&str; 22] = [
+    "??", "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "(", ")", "[",
+    "]", ".", ",", "=", "!",
+];
+
+fn read_string(chars: &[char], start: usize, source: &str) -> crate::Result<(String, usize)> {
+    let quote = chars[start];
+    let mut s = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                s.push(match chars[i + 1] {
+                    'n' => '\n',
+                    't' => '\t',
+                    c => c,
+                });
+                i += 2;
+            }
+            c if c == quote => return Ok((s, i + 1)),
+            c => {
+                s.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(syntax_error(source, start, "unterminated string"))
+}
+
+pub fn tokenize(source: &str) -> crate::Result<Vec<Spanned>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                // `forecast.0` isn't a number with a fraction, only `1.5` is
+                if chars[i] == '.' && !chars.get(i + 1).map_or(false, char::is_ascii_digit) {
+                    break;
+                }
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse()
+                .map_err(|_| syntax_error(source, start, "invalid number"))?;
+            tokens.push((Token::Number(number), start));
+        } else if c == '"' || c == '\'' {
+            let (s, end) = read_string(&chars, i, source)?;
+            tokens.push((Token::String(s), i));
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(**symbol))
+                .ok_or_else(|| syntax_error(source, i, &format!("unexpected character '{c}'")))?;
+            tokens.push((Token::Symbol(symbol), i));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let tokens = tokenize("forecast.0.wind >= 1.5 ?? 'a b'").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::Ident("forecast".to_string()), 0),
+                (Token::Symbol("."), 8),
+                (Token::Number(0.0), 9),
+                (Token::Symbol("."), 10),
+                (Token::Ident("wind".to_string()), 11),
+                (Token::Symbol(">="), 16),
+                (Token::Number(1.5), 19),
+                (Token::Symbol("??"), 23),
+                (Token::String("a b".to_string()), 26),
+            ]
+        );
+        assert_eq!(
+            tokenize(r#""a\"b\n""#).unwrap(),
+            vec![(Token::String("a\"b\n".to_string()), 0)]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let message = |source: &str| tokenize(source).unwrap_err().to_string();
+        assert_eq!(
+            message("1 + \"abc"),
+            "Layout Error: Error: unterminated string at column 5 of `1 + \"abc`"
+        );
+        assert_eq!(
+            message("a # b"),
+            "Layout Error: Error: unexpected character '#' at column 3 of `a # b`"
+        );
+    }
+}
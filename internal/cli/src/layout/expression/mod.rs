@@ -0,0 +1,328 @@
+//! Expressions used by `expression` layout items, for example
+//! `if current_weather.cloud_cover > 50 then "Cloudy" else "Clear"` or `"{temperature:.0}°"`.
+//!
+//! Identifiers are looked up in the forecast and then in `current_weather`, missing values are
+//! `null` so they can be given a default with `??`.
+
+mod format;
+mod lexer;
+mod parser;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::Value as Json;
+
+use crate::layout::functions;
+use crate::layout::layout_error;
+pub use format::format_value;
+pub use parser::{parse, parse_template, Expr, Part};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// A list or an object from the forecast
+    Json(Json),
+}
+
+impl Value {
+    pub fn from_json(json: &Json) -> Self {
+        match json {
+            Json::Null => Self::Null,
+            Json::Bool(b) => Self::Bool(*b),
+            Json::Number(n) => n.as_f64().map_or(Self::Null, Self::Number),
+            Json::String(s) => Self::String(s.clone()),
+            _ => Self::Json(json.clone()),
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Bool(_) => "boolean",
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+            Self::Json(Json::Array(_)) => "list",
+            Self::Json(_) => "object",
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Null => false,
+            Self::Bool(b) => *b,
+            Self::Number(n) => *n != 0.0,
+            Self::String(s) => !s.is_empty(),
+            Self::Json(Json::Array(a)) => !a.is_empty(),
+            Self::Json(_) => true,
+        }
+    }
+
+    /// Numbers and strings containing numbers
+    pub fn as_number(&self) -> crate::Result<f64> {
+        match self {
+            Self::Number(n) => Ok(*n),
+            Self::String(s) => s
+                .trim()
+                .parse()
+                .map_err(|_| layout_error(format!("expected a number, found \"{s}\""))),
+            _ => Err(layout_error(format!(
+                "expected a number, found {}",
+                self.type_name()
+            ))),
+        }
+    }
+}
+
+/// Whole numbers are shown without decimals, others with one decimal like `variable` items
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => Ok(()),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Number(n) if n.fract() == 0.0 => write!(f, "{n:.0}"),
+            Self::Number(n) => write!(f, "{n:.1}"),
+            Self::String(s) => write!(f, "{s}"),
+            Self::Json(json) => write!(f, "{json}"),
+        }
+    }
+}
+
+pub(crate) fn syntax_error(source: &str, column: usize, message: &str) -> weather_error::Error {
+    layout_error(format!("{message} at column {} of `{source}`", column + 1))
+}
+
+fn lookup(data: &Json, name: &str) -> Value {
+    match data.get(name) {
+        Some(value) => Value::from_json(value),
+        None => data
+            .get("current_weather")
+            .and_then(|current| current.get(name))
+            .map_or(Value::Null, Value::from_json),
+    }
+}
+
+/// Whether the forecast is in metric units, set by `LayoutFile::to_string`
+pub(crate) fn is_metric(data: &Json) -> bool {
+    data.get("metric").and_then(Json::as_bool).unwrap_or(false)
+}
+
+/// Offset of the forecast location's time zone from UTC in seconds, if the backend reported it
+pub(crate) fn utc_offset(data: &Json) -> Option<i32> {
+    data.get("utc_offset")
+        .and_then(Json::as_i64)
+        .and_then(|offset| i32::try_from(offset).ok())
+}
+
+fn index(value: Value, index: Value) -> crate::Result<Value> {
+    Ok(match (&value, &index) {
+        (Value::Null, _) => Value::Null,
+        (Value::Json(Json::Array(list)), Value::Number(n)) => {
+            if n.fract() != 0.0 {
+                return Err(layout_error(format!(
+                    "list index {n} is not a whole number"
+                )));
+            }
+            // Negative indexes count from the end
+            let i = if *n < 0.0 { list.len() as f64 + n } else { *n };
+            if i < 0.0 {
+                Value::Null
+            } else {
+                list.get(i as usize).map_or(Value::Null, Value::from_json)
+            }
+        }
+        (Value::Json(Json::Object(map)), Value::String(key)) => {
+            map.get(key).map_or(Value::Null, Value::from_json)
+        }
+        _ => {
+            return Err(layout_error(format!(
+                "can't index a {} with a {}",
+                value.type_name(),
+                index.type_name()
+            )))
+        }
+    })
+}
+
+fn compare(op: &str, left: &Value, right: &Value) -> crate::Result<bool> {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => {
+            return Err(layout_error(format!(
+                "can't compare a {} with a {} using {op}",
+                left.type_name(),
+                right.type_name()
+            )))
+        }
+    };
+    let ordering = ordering.ok_or_else(|| layout_error("can't compare NaN"))?;
+    Ok(match op {
+        "<" => ordering.is_lt(),
+        "<=" => ordering.is_le(),
+        ">" => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    })
+}
+
+fn arithmetic(op: &str, left: Value, right: Value) -> crate::Result<Value> {
+    if op == "+" {
+        if let (Value::String(_), _) | (_, Value::String(_)) = (&left, &right) {
+            return Ok(Value::String(format!("{left}{right}")));
+        }
+    }
+    let (a, b) = match (&left, &right) {
+        (Value::Number(a), Value::Number(b)) => (*a, *b),
+        _ => {
+            return Err(layout_error(format!(
+                "can't use {op} on a {} and a {}",
+                left.type_name(),
+                right.type_name()
+            )))
+        }
+    };
+    if (op == "/" || op == "%") && b == 0.0 {
+        return Err(layout_error("division by zero"));
+    }
+    Ok(Value::Number(match op {
+        "+" => a + b,
+        "-" => a - b,
+        "*" => a * b,
+        "/" => a / b,
+        _ => a % b,
+    }))
+}
+
+fn binary(op: &str, left: &Expr, right: &Expr, data: &Json) -> crate::Result<Value> {
+    let left = evaluate(left, data)?;
+    // Short circuiting operators only evaluate the right side when needed
+    match op {
+        "??" if left != Value::Null => return Ok(left),
+        "??" => return evaluate(right, data),
+        "and" if !left.is_truthy() => return Ok(Value::Bool(false)),
+        "or" if left.is_truthy() => return Ok(Value::Bool(true)),
+        "and" | "or" => return Ok(Value::Bool(evaluate(right, data)?.is_truthy())),
+        _ => {}
+    }
+    let right = evaluate(right, data)?;
+    match op {
+        "==" => Ok(Value::Bool(left == right)),
+        "!=" => Ok(Value::Bool(left != right)),
+        "<" | "<=" | ">" | ">=" => Ok(Value::Bool(compare(op, &left, &right)?)),
+        _ => arithmetic(op, left, right),
+    }
+}
+
+/// Evaluates a parsed expression against the forecast
+pub fn evaluate(expr: &Expr, data: &Json) -> crate::Result<Value> {
+    Ok(match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Template(parts) => {
+            let mut s = String::new();
+            for part in parts {
+                match part {
+                    Part::Text(text) => s += text,
+                    Part::Placeholder(expr, format) => {
+                        let value = evaluate(expr, data)?;
+                        s += &match format {
+                            Some(format) => format_value(&value, format)?,
+                            None => value.to_string(),
+                        };
+                    }
+                }
+            }
+            Value::String(s)
+        }
+        Expr::Variable(name) => lookup(data, name),
+        Expr::Field(expr, name) => match evaluate(expr, data)? {
+            Value::Null => Value::Null,
+            Value::Json(Json::Object(map)) => map.get(name).map_or(Value::Null, Value::from_json),
+            value => {
+                return Err(layout_error(format!(
+                    "a {} has no field {name}",
+                    value.type_name()
+                )))
+            }
+        },
+        Expr::Index(expr, i) => index(evaluate(expr, data)?, evaluate(i, data)?)?,
+        Expr::Unary("-", expr) => Value::Number(-evaluate(expr, data)?.as_number()?),
+        Expr::Unary(_, expr) => Value::Bool(!evaluate(expr, data)?.is_truthy()),
+        Expr::Binary(op, left, right) => binary(op, left, right, data)?,
+        Expr::If(condition, then, otherwise) => {
+            if evaluate(condition, data)?.is_truthy() {
+                evaluate(then, data)?
+            } else {
+                evaluate(otherwise, data)?
+            }
+        }
+        Expr::Call(name, args, kwargs) => {
+            let args = args
+                .iter()
+                .map(|arg| evaluate(arg, data))
+                .collect::<crate::Result<Vec<Value>>>()?;
+            let kwargs = kwargs
+                .iter()
+                .map(|(key, arg)| Ok((key.clone(), evaluate(arg, data)?)))
+                .collect::<crate::Result<HashMap<String, Value>>>()?;
+            functions::call(name, args, kwargs, is_metric(data), utc_offset(data))?
+        }
+    })
+}
+
+/// Parses and evaluates an expression
+pub fn eval_str(source: &str, data: &Json) -> crate::Result<Value> {
+    evaluate(&parse(source)?, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval(source: &str) -> Value {
+        let data = json!({
+            "region": "Oslo",
+            "forecast": [{"temperature": 1.5}, {"temperature": 2.25}],
+            "current_weather": {"temperature": 12.34, "cloud_cover": 80},
+        });
+        eval_str(source, &data).unwrap()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Value::Number(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Value::Number(9.0));
+        assert_eq!(eval("10 - 4 - 3"), Value::Number(3.0));
+        assert_eq!(eval("16 / 4 / 2"), Value::Number(2.0));
+        assert_eq!(eval("-2 * 3 + 7 % 4"), Value::Number(-3.0));
+        assert_eq!(eval("1 + 1 == 2 and not 3 < 2"), Value::Bool(true));
+        assert_eq!(eval("false and true or true"), Value::Bool(true));
+        assert_eq!(eval("!false && false"), Value::Bool(false));
+        assert_eq!(eval("missing ?? 1 + 1"), Value::Number(2.0));
+        assert_eq!(
+            eval("if cloud_cover > 50 then 'Cloudy' else 'Clear'"),
+            Value::String("Cloudy".to_string())
+        );
+        assert_eq!(eval("forecast[-1].temperature"), Value::Number(2.25));
+        assert_eq!(eval("forecast.0.temperature"), Value::Number(1.5));
+    }
+
+    #[test]
+    fn templates() {
+        assert_eq!(
+            eval("\"{region}: {temperature:.1}° {{ok}}\""),
+            Value::String("Oslo: 12.3° {ok}".to_string())
+        );
+        assert_eq!(
+            eval("\"[{region:^8}]\""),
+            Value::String("[  Oslo  ]".to_string())
+        );
+        assert_eq!(
+            eval("\"{'{forecast.1.temperature:.2}'}\""),
+            Value::String("2.25".to_string())
+        );
+    }
+}
@@ -0,0 +1,494 @@
+use crate::layout::expression::lexer::{tokenize, Spanned, Token};
+use crate::layout::expression::{syntax_error, Value};
+
+#[derive(Clone, Debug)]
+pub enum Part {
+    Text(String),
+    /// An expression and its format specifier
+    Placeholder(Expr, Option<String>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Literal(Value),
+    Template(Vec<Part>),
+    Variable(String),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>, Vec<(String, Expr)>),
+}
+
+const KEYWORDS: [&str; 9] = [
+    "if", "then", "else", "and", "or", "not", "true", "false", "null",
+];
+
+/// Expressions nested deeper are rejected, evaluating them could overflow the stack. Operators,
+/// brackets, calls, branches and templates in strings are all a level
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Spanned>,
+    pos: usize,
+    /// How deep the expression being parsed is nested
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.source.chars().count(), |(_, column)| *column)
+    }
+
+    fn error(&self, message: &str) -> weather_error::Error {
+        syntax_error(self.source, self.column(), message)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s == keyword)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> crate::Result<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{symbol}'")))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> crate::Result<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{keyword}'")))
+        }
+    }
+
+    /// Goes a level deeper, callers reset `depth` when they are done
+    fn descend(&mut self) -> crate::Result<()> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(&format!(
+                "expression is nested more than {MAX_DEPTH} levels deep"
+            )));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn expression(&mut self) -> crate::Result<Expr> {
+        if self.eat_keyword("if") {
+            self.descend()?;
+            let condition = self.expression()?;
+            self.expect_keyword("then")?;
+            let then = self.expression()?;
+            self.expect_keyword("else")?;
+            let otherwise = self.expression()?;
+            self.depth -= 1;
+            return Ok(Expr::If(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+        self.coalesce()
+    }
+
+    /// Operators of the same precedence, they are left associative so every one of them nests
+    /// the ones before it a level deeper
+    fn binary_level(
+        &mut self,
+        ops: &[(&str, &'static str)],
+        next: fn(&mut Self) -> crate::Result<Expr>,
+    ) -> crate::Result<Expr> {
+        let depth = self.depth;
+        let mut left = next(self)?;
+        'outer: loop {
+            for (symbol, op) in ops {
+                let found = if symbol.chars().all(char::is_alphabetic) {
+                    self.eat_keyword(symbol)
+                } else {
+                    self.eat_symbol(symbol)
+                };
+                if found {
+                    self.descend()?;
+                    left = Expr::Binary(op, Box::new(left), Box::new(next(self)?));
+                    continue 'outer;
+                }
+            }
+            self.depth = depth;
+            return Ok(left);
+        }
+    }
+
+    fn coalesce(&mut self) -> crate::Result<Expr> {
+        self.binary_level(&[("??", "??")], Self::or)
+    }
+
+    fn or(&mut self) -> crate::Result<Expr> {
+        self.binary_level(&[("or", "or"), ("||", "or")], Self::and)
+    }
+
+    fn and(&mut self) -> crate::Result<Expr> {
+        self.binary_level(&[("and", "and"), ("&&", "and")], Self::not)
+    }
+
+    fn not(&mut self) -> crate::Result<Expr> {
+        if self.eat_keyword("not") || self.eat_symbol("!") {
+            self.descend()?;
+            let expr = Expr::Unary("not", Box::new(self.not()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> crate::Result<Expr> {
+        let left = self.additive()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat_symbol(op) {
+                self.descend()?;
+                let expr = Expr::Binary(op, Box::new(left), Box::new(self.additive()?));
+                self.depth -= 1;
+                return Ok(expr);
+            }
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> crate::Result<Expr> {
+        self.binary_level(&[("+", "+"), ("-", "-")], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> crate::Result<Expr> {
+        self.binary_level(&[("*", "*"), ("/", "/"), ("%", "%")], Self::unary)
+    }
+
+    fn unary(&mut self) -> crate::Result<Expr> {
+        if self.eat_symbol("-") {
+            self.descend()?;
+            let expr = Expr::Unary("-", Box::new(self.unary()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> crate::Result<Expr> {
+        let depth = self.depth;
+        let mut expr = self.primary()?;
+        loop {
+            if self.is_symbol(".") || self.is_symbol("[") {
+                self.descend()?;
+            }
+            if self.eat_symbol(".") {
+                match self.tokens.get(self.pos).map(|(token, _)| token.clone()) {
+                    Some(Token::Ident(name)) => expr = Expr::Field(Box::new(expr), name),
+                    // `forecast.0` is the same as `forecast[0]`
+                    Some(Token::Number(n)) => {
+                        expr =
+                            Expr::Index(Box::new(expr), Box::new(Expr::Literal(Value::Number(n))))
+                    }
+                    _ => return Err(self.error("expected a field name after '.'")),
+                }
+                self.pos += 1;
+            } else if self.eat_symbol("[") {
+                let index = self.expression()?;
+                self.expect_symbol("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                self.depth = depth;
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn call(&mut self, name: String) -> crate::Result<Expr> {
+        let mut args = Vec::new();
+        let mut kwargs = Vec::new();
+        while !self.eat_symbol(")") {
+            if !args.is_empty() || !kwargs.is_empty() {
+                self.expect_symbol(",")?;
+            }
+            let is_kwarg = matches!(self.peek(), Some(Token::Ident(_)))
+                && matches!(self.tokens.get(self.pos + 1), Some((Token::Symbol("="), _)));
+            if is_kwarg {
+                let key = match self.peek() {
+                    Some(Token::Ident(key)) => key.clone(),
+                    _ => unreachable!(),
+                };
+                self.pos += 2;
+                kwargs.push((key, self.expression()?));
+            } else if kwargs.is_empty() {
+                args.push(self.expression()?);
+            } else {
+                return Err(self.error("positional arguments can't follow keyword arguments"));
+            }
+        }
+        Ok(Expr::Call(name, args, kwargs))
+    }
+
+    fn primary(&mut self) -> crate::Result<Expr> {
+        let column = self.column();
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|(token, _)| token.clone())
+            .ok_or_else(|| self.error("unexpected end of expression"))?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Literal(Value::Number(n))),
+            Token::String(s) if s.contains('{') => {
+                self.descend()?;
+                let expr = template(&s, self.depth);
+                self.depth -= 1;
+                expr
+            }
+            Token::String(s) => template(&s, self.depth),
+            Token::Ident(name) => match &*name {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if KEYWORDS.contains(&&*name) => Err(syntax_error(
+                    self.source,
+                    column,
+                    &format!("unexpected '{name}'"),
+                )),
+                _ if self.eat_symbol("(") => {
+                    self.descend()?;
+                    let expr = self.call(name);
+                    self.depth -= 1;
+                    expr
+                }
+                _ => Ok(Expr::Variable(name)),
+            },
+            Token::Symbol("(") => {
+                self.descend()?;
+                let expr = self.expression()?;
+                self.expect_symbol(")")?;
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Token::Symbol(symbol) => Err(syntax_error(
+                self.source,
+                column,
+                &format!("unexpected '{symbol}'"),
+            )),
+        }
+    }
+}
+
+/// Parses an expression
+pub fn parse(source: &str) -> crate::Result<Expr> {
+    parse_nested(source, 0)
+}
+
+/// Parses an expression inside a string of an expression that is nested `depth` levels deep
+fn parse_nested(source: &str, depth: usize) -> crate::Result<Expr> {
+    let mut parser = Parser {
+        source,
+        tokens: tokenize(source)?,
+        pos: 0,
+        depth,
+    };
+    let expr = parser.expression()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(parser.error("unexpected input after the expression"));
+    }
+    Ok(expr)
+}
+
+/// Splits a placeholder into the expression and the format specifier after the last
+/// `:` outside of a string
+fn split_format(placeholder: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    let mut split = None;
+    for (i, c) in placeholder.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == ':' => split = Some(i),
+            None => {}
+        }
+    }
+    match split {
+        Some(i) => (&placeholder[..i], Some(&placeholder[i + 1..])),
+        None => (placeholder, None),
+    }
+}
+
+/// Parses text with `{expression}` or `{expression:format}` placeholders, `{{` and `}}` are
+/// literal braces
+pub fn parse_template(source: &str) -> crate::Result<Expr> {
+    template(source, 0)
+}
+
+fn template(source: &str, depth: usize) -> crate::Result<Expr> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '{' if chars.get(i + 1) == Some(&'{') => {
+                text.push('{');
+                i += 2;
+            }
+            '}' if chars.get(i + 1) == Some(&'}') => {
+                text.push('}');
+                i += 2;
+            }
+            '{' => {
+                let start = i + 1;
+                let mut end = start;
+                let mut quote = None;
+                while end < chars.len() {
+                    match (quote, chars[end]) {
+                        (Some(q), c) if c == q => quote = None,
+                        (None, '"' | '\'') => quote = Some(chars[end]),
+                        (None, '}') => break,
+                        _ => {}
+                    }
+                    end += 1;
+                }
+                if end == chars.len() {
+                    return Err(syntax_error(source, i, "unclosed '{'"));
+                }
+                let placeholder: String = chars[start..end].iter().collect();
+                let (expr, format) = split_format(&placeholder);
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push(Part::Placeholder(
+                    parse_nested(expr, depth)?,
+                    format.map(str::to_string),
+                ));
+                i = end + 1;
+            }
+            '}' => return Err(syntax_error(source, i, "unmatched '}', use '}}'")),
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    if parts.is_empty() {
+        return Ok(Expr::Literal(Value::String(text)));
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    Ok(Expr::Template(parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(result: crate::Result<Expr>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn templates() {
+        match parse_template("{{a}} {temperature:>5.1}°{'}}'}").unwrap() {
+            Expr::Template(parts) => {
+                assert!(matches!(&parts[0], Part::Text(text) if text == "{a} "));
+                assert!(matches!(
+                    &parts[1],
+                    Part::Placeholder(Expr::Variable(name), Some(format))
+                        if name == "temperature" && format == ">5.1"
+                ));
+                assert!(matches!(&parts[2], Part::Text(text) if text == "°"));
+                assert!(matches!(
+                    &parts[3],
+                    Part::Placeholder(Expr::Literal(Value::String(s)), None) if s == "}"
+                ));
+            }
+            expr => panic!("{expr:?} is not a template"),
+        }
+        assert!(matches!(
+            parse_template("no placeholders").unwrap(),
+            Expr::Literal(Value::String(s)) if s == "no placeholders"
+        ));
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(
+            message(parse("1 +")),
+            "Layout Error: Error: unexpected end of expression at column 4 of `1 +`"
+        );
+        assert_eq!(
+            message(parse("(1 + 2")),
+            "Layout Error: Error: expected ')' at column 7 of `(1 + 2`"
+        );
+        assert_eq!(
+            message(parse("1 2")),
+            "Layout Error: Error: unexpected input after the expression at column 3 of `1 2`"
+        );
+        assert_eq!(
+            message(parse("if a then b")),
+            "Layout Error: Error: expected 'else' at column 12 of `if a then b`"
+        );
+        assert_eq!(
+            message(parse("a + then")),
+            "Layout Error: Error: unexpected 'then' at column 5 of `a + then`"
+        );
+        assert_eq!(
+            message(parse("f(a = 1, 2)")),
+            "Layout Error: Error: positional arguments can't follow keyword arguments \
+             at column 10 of `f(a = 1, 2)`"
+        );
+        assert_eq!(
+            message(parse_template("a {1")),
+            "Layout Error: Error: unclosed '{' at column 3 of `a {1`"
+        );
+        assert_eq!(
+            message(parse_template("a }")),
+            "Layout Error: Error: unmatched '}', use '}}' at column 3 of `a }`"
+        );
+    }
+
+    #[test]
+    fn nesting() {
+        let brackets = |n: usize| format!("{}1{}", "(".repeat(n), ")".repeat(n));
+        assert!(parse(&brackets(MAX_DEPTH)).is_ok());
+        assert!(message(parse(&brackets(MAX_DEPTH + 1))).contains("nested more than 64 levels"));
+        assert!(message(parse(&format!("{}1", "-".repeat(10_000)))).contains("nested"));
+        assert!(message(parse(&vec!["1"; 10_000].join(" + "))).contains("nested"));
+        assert!(message(parse(&format!("{}1", "not ".repeat(10_000)))).contains("nested"));
+        assert!(message(parse(&format!("a{}", ".b".repeat(10_000)))).contains("nested"));
+        // Strings are templates, every placeholder in one is a level
+        assert!(parse_nested("'{1}'", MAX_DEPTH - 1).is_ok());
+        assert!(message(parse_nested("'{1}'", MAX_DEPTH)).contains("nested"));
+    }
+}
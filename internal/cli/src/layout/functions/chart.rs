@@ -0,0 +1,95 @@
+//! Charts of a field of a list in the forecast, like the temperature of every hour
+
+use serde_json::Value as Json;
+use terminal::chart::{self, Chart, Style};
+
+use crate::layout::expression::Value;
+use crate::layout::functions::time;
+use crate::layout::layout_error;
+
+const DAY: f64 = 86_400_000.0;
+
+/// Follows a path like `wind.speed` into an element, missing fields are `NaN`
+fn field(element: &Json, path: &str) -> f64 {
+    path.split('.')
+        .try_fold(element, |json, key| json.get(key))
+        .and_then(Json::as_f64)
+        .unwrap_or(f64::NAN)
+}
+
+/// Times of the elements in milliseconds
+fn times(elements: &[Json]) -> Vec<f64> {
+    elements
+        .iter()
+        .map(|element| field(element, "time"))
+        .collect()
+}
+
+/// Labels for the x axis, week days if the times are a day or more apart
+pub fn labels(times: &[f64], utc_offset: Option<i32>) -> Vec<String> {
+    let format = match times {
+        [first, second, ..] if second - first >= DAY => "%a",
+        _ => "%H:%M",
+    };
+    times
+        .iter()
+        .map(|time| time::format(*time, format, false, utc_offset).unwrap_or_default())
+        .collect()
+}
+
+/// `path` of `count` elements of `list` from the one now is in, and their times
+fn series(list: &Value, path: &str, count: usize) -> crate::Result<(Vec<f64>, Vec<f64>)> {
+    let elements = match list {
+        Value::Json(Json::Array(elements)) => elements,
+        other => {
+            return Err(layout_error(format!(
+                "Charts need a list like forecast, not a {}",
+                other.type_name()
+            )))
+        }
+    };
+    let times = times(elements);
+    let start = time::current_index(&times, local::now() as f64);
+    let end = (start + count).min(elements.len());
+    let values = elements[start..end]
+        .iter()
+        .map(|element| field(element, path))
+        .collect();
+    Ok((values, times[start..end].to_vec()))
+}
+
+/// A single line of blocks as high as the values
+pub fn sparkline(list: &Value, path: &str, count: usize) -> crate::Result<String> {
+    Ok(chart::sparkline(&series(list, path, count)?.0))
+}
+
+/// A chart with axes, `style` is `line` or `bars`
+pub fn chart(
+    list: &Value,
+    path: &str,
+    count: usize,
+    size: (usize, usize),
+    unit: &str,
+    style: &str,
+    utc_offset: Option<i32>,
+) -> crate::Result<String> {
+    let style = Style::from_string(style).ok_or_else(|| {
+        layout_error(format!(
+            "Unknown chart style {style}, it can be line or bars"
+        ))
+    })?;
+    let (values, times) = series(list, path, count)?;
+    let labels = labels(&times, utc_offset);
+    let now = times
+        .first()
+        .filter(|time| **time <= local::now() as f64)
+        .map(|_| 0);
+    let chart = Chart {
+        values: &values,
+        labels: &labels,
+        unit,
+        now,
+        style,
+    };
+    Ok(chart.render(size.0, size.1).join("\n"))
+}
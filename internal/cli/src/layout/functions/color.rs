@@ -0,0 +1,58 @@
+use terminal::color;
+
+use crate::layout::layout_error;
+
+fn parse_color(name: &str) -> crate::Result<[f64; 3]> {
+    let name = name.trim().to_lowercase();
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .map(f64::from)
+        };
+        if let (6, Some(r), Some(g), Some(b)) = (hex.len(), channel(0), channel(2), channel(4)) {
+            return Ok([r, g, b]);
+        }
+    }
+    Ok(match &*name {
+        "black" => [0., 0., 0.],
+        "white" => [255., 255., 255.],
+        "red" => [220., 50., 47.],
+        "orange" => [255., 140., 0.],
+        "yellow" => [240., 210., 40.],
+        "green" => [70., 190., 70.],
+        "cyan" => [40., 190., 210.],
+        "blue" => [50., 100., 230.],
+        "purple" => [150., 70., 200.],
+        "magenta" => [210., 60., 170.],
+        _ => return Err(layout_error(format!("Unknown color {name}"))),
+    })
+}
+
+/// Colors `text` with the color at the position of `value` between `min` and `max` in a gradient
+/// of comma separated colors
+pub fn scale(value: f64, min: f64, max: f64, colors: &str, text: &str) -> crate::Result<String> {
+    let stops = colors
+        .split(',')
+        .map(parse_color)
+        .collect::<crate::Result<Vec<[f64; 3]>>>()?;
+    let position = if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let rgb = if stops.len() == 1 {
+        stops[0]
+    } else {
+        let scaled = position * (stops.len() - 1) as f64;
+        let i = (scaled as usize).min(stops.len() - 2);
+        let t = scaled - i as f64;
+        let (a, b) = (stops[i], stops[i + 1]);
+        [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t)
+    };
+    Ok(format!(
+        "{}{text}{}",
+        color::rgb(rgb[0] as u8, rgb[1] as u8, rgb[2] as u8),
+        color::FORE_RESET
+    ))
+}
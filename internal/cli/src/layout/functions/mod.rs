@@ -0,0 +1,434 @@
+//! Functions that can be called from layouts, see `FUNCTIONS`
+
+pub(crate) mod chart;
+mod color;
+mod text;
+pub(crate) mod time;
+pub(crate) mod units;
+
+use std::collections::HashMap;
+
+use crate::layout::expression::Value;
+use crate::layout::{layout_error, theme, util};
+use terminal::color::FORE_RESET;
+
+/// Arguments of a function call, arguments can be passed by position or by name
+pub struct Arguments {
+    name: String,
+    args: Vec<Value>,
+    kwargs: HashMap<String, Value>,
+    /// Whether the forecast is in metric units
+    pub metric: bool,
+    /// Offset of the forecast location's time zone from UTC in seconds, if it is known
+    pub utc_offset: Option<i32>,
+}
+
+impl Arguments {
+    pub fn new(
+        name: &str,
+        args: Vec<Value>,
+        kwargs: HashMap<String, Value>,
+        metric: bool,
+        utc_offset: Option<i32>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            args,
+            kwargs,
+            metric,
+            utc_offset,
+        }
+    }
+
+    /// The argument at `position` or named `key`
+    pub fn get(&self, position: usize, key: &str) -> Option<&Value> {
+        self.kwargs.get(key).or_else(|| self.args.get(position))
+    }
+
+    pub fn required(&self, position: usize, key: &str) -> crate::Result<&Value> {
+        self.get(position, key)
+            .ok_or_else(|| layout_error(format!("{} is missing the argument {key}", self.name)))
+    }
+
+    pub fn number(&self, position: usize, key: &str) -> crate::Result<f64> {
+        self.required(position, key)?.as_number()
+    }
+
+    pub fn number_or(&self, position: usize, key: &str, default: f64) -> crate::Result<f64> {
+        self.get(position, key)
+            .map_or(Ok(default), Value::as_number)
+    }
+
+    pub fn string(&self, position: usize, key: &str) -> crate::Result<String> {
+        Ok(self.required(position, key)?.to_string())
+    }
+
+    pub fn string_or(&self, position: usize, key: &str, default: &str) -> String {
+        self.get(position, key)
+            .map_or_else(|| default.to_string(), Value::to_string)
+    }
+
+    /// All positional arguments as numbers
+    pub fn numbers(&self) -> crate::Result<Vec<f64>> {
+        if self.args.is_empty() {
+            return Err(layout_error(format!(
+                "{} needs at least one argument",
+                self.name
+            )));
+        }
+        self.args.iter().map(Value::as_number).collect()
+    }
+
+    fn check(&self, function: &LayoutFunction) -> crate::Result<()> {
+        function.check_arguments(self.args.len(), self.kwargs.keys().map(String::as_str))
+    }
+}
+
+pub struct LayoutFunction {
+    pub name: &'static str,
+    /// Names of the arguments, in order
+    pub args: &'static [&'static str],
+    /// Whether any number of positional arguments can be passed
+    pub variadic: bool,
+    pub help: &'static str,
+    function: fn(&Arguments) -> crate::Result<Value>,
+}
+
+const fn function(
+    name: &'static str,
+    args: &'static [&'static str],
+    help: &'static str,
+    function: fn(&Arguments) -> crate::Result<Value>,
+) -> LayoutFunction {
+    LayoutFunction {
+        name,
+        args,
+        variadic: false,
+        help,
+        function,
+    }
+}
+
+const fn variadic(
+    name: &'static str,
+    help: &'static str,
+    function: fn(&Arguments) -> crate::Result<Value>,
+) -> LayoutFunction {
+    LayoutFunction {
+        name,
+        args: &[],
+        variadic: true,
+        help,
+        function,
+    }
+}
+
+impl LayoutFunction {
+    /// Fails on arguments the function doesn't take
+    pub fn check_arguments<'a>(
+        &self,
+        args: usize,
+        mut kwargs: impl Iterator<Item = &'a str>,
+    ) -> crate::Result<()> {
+        if !self.variadic && args > self.args.len() {
+            return Err(layout_error(format!(
+                "{} takes at most {} arguments, but got {args}",
+                self.name,
+                self.args.len()
+            )));
+        }
+        match kwargs.find(|key| !self.args.contains(key)) {
+            Some(key) => Err(layout_error(format!(
+                "{} has no argument named {key}",
+                self.name
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn string(s: String) -> crate::Result<Value> {
+    Ok(Value::String(s))
+}
+
+fn number(n: f64) -> crate::Result<Value> {
+    Ok(Value::Number(n))
+}
+
+fn round(a: &Arguments) -> crate::Result<Value> {
+    let digits = a.number_or(1, "digits", 0.)?.clamp(0.0, 10.0) as usize;
+    string(format!("{:.digits$}", a.number(0, "value")?))
+}
+
+fn convert(a: &Arguments) -> crate::Result<Value> {
+    let (from, to) = (a.string(1, "from")?, a.string(2, "to")?);
+    number(units::convert(a.number(0, "value")?, &from, &to)?)
+}
+
+fn pad(a: &Arguments) -> crate::Result<Value> {
+    string(text::pad(
+        &a.string(0, "text")?,
+        a.number(1, "width")?.max(0.0) as usize,
+        &a.string_or(2, "align", "left"),
+        &a.string_or(3, "fill", " "),
+    )?)
+}
+
+fn truncate(a: &Arguments) -> crate::Result<Value> {
+    string(text::truncate(
+        &a.string(0, "text")?,
+        a.number(1, "length")?.max(0.0) as usize,
+        &a.string_or(2, "ellipsis", "…"),
+    ))
+}
+
+fn compass(a: &Arguments) -> crate::Result<Value> {
+    let points = a.number_or(1, "points", 16.)? as usize;
+    string(units::compass(a.number(0, "degrees")?, points)?.to_string())
+}
+
+fn beaufort(a: &Arguments) -> crate::Result<Value> {
+    let unit = a.string_or(1, "unit", if a.metric { "km/h" } else { "mph" });
+    string(units::beaufort(a.number(0, "speed")?, &unit)?.to_string())
+}
+
+fn format_time(a: &Arguments) -> crate::Result<Value> {
+    let format = a.string_or(1, "format", "%H:%M");
+    let utc = a.get(2, "utc").map_or(false, Value::is_truthy);
+    string(time::format(
+        a.number(0, "time")?,
+        &format,
+        utc,
+        a.utc_offset,
+    )?)
+}
+
+fn sparkline(a: &Arguments) -> crate::Result<Value> {
+    string(chart::sparkline(
+        a.required(0, "series")?,
+        &a.string_or(1, "field", "temperature"),
+        a.number_or(2, "count", 24.)?.max(0.0) as usize,
+    )?)
+}
+
+fn draw_chart(a: &Arguments) -> crate::Result<Value> {
+    let size = (
+        a.number_or(4, "width", 60.)?.max(0.0) as usize,
+        a.number_or(3, "height", 6.)?.max(0.0) as usize,
+    );
+    string(chart::chart(
+        a.required(0, "series")?,
+        &a.string_or(1, "field", "temperature"),
+        a.number_or(2, "count", 24.)?.max(0.0) as usize,
+        size,
+        &a.string_or(5, "unit", ""),
+        &a.string_or(6, "style", "line"),
+        a.utc_offset,
+    )?)
+}
+
+fn color_aqi(a: &Arguments) -> crate::Result<Value> {
+    let aqi = a.number(0, "aqi")?.clamp(0.0, f64::from(u8::MAX)) as u8;
+    string(util::color_aqi(aqi)?)
+}
+
+fn to_ascii(a: &Arguments) -> crate::Result<Value> {
+    let night = match a.get(1, "night") {
+        Some(night) => night.is_truthy(),
+        None => time::is_night(local::now() as f64, a.utc_offset)?,
+    };
+    string(util::to_ascii(a.required(0, "condition")?, night)?)
+}
+
+fn color_condition(a: &Arguments) -> crate::Result<Value> {
+    let condition = a.number(0, "condition")?.clamp(0.0, f64::from(u16::MAX)) as u16;
+    let text = a.string_or(1, "text", &condition.to_string());
+    let color = theme::with_current(|theme| theme.condition_color(condition));
+    string(format!("{color}{text}{FORE_RESET}"))
+}
+
+fn color_text(a: &Arguments) -> crate::Result<Value> {
+    let name = a.string(0, "color")?;
+    let color = theme::with_current(|theme| theme.color(&name))
+        .ok_or_else(|| layout_error(format!("Unknown color or role {name}")))?;
+    string(format!("{color}{}{FORE_RESET}", a.string(1, "text")?))
+}
+
+fn color_scale(a: &Arguments) -> crate::Result<Value> {
+    let value = a.number(0, "value")?;
+    let (min, max) = (a.number(1, "min")?, a.number(2, "max")?);
+    let stops = theme::with_current(|theme| theme.temperature.join(","));
+    let colors = a.string_or(3, "colors", &stops);
+    let text = a.string_or(4, "text", &a.required(0, "value")?.to_string());
+    string(color::scale(value, min, max, &colors, &text)?)
+}
+
+/// Every function that can be used in layouts
+pub const FUNCTIONS: &[LayoutFunction] = &[
+    // Numbers
+    function(
+        "round",
+        &["value", "digits"],
+        "Rounds to a number of decimals (0 by default)",
+        round,
+    ),
+    function("abs", &["value"], "Absolute value", |a| {
+        number(a.number(0, "value")?.abs())
+    }),
+    variadic("min", "Smallest of the arguments", |a| {
+        number(a.numbers()?.into_iter().fold(f64::INFINITY, f64::min))
+    }),
+    variadic("max", "Largest of the arguments", |a| {
+        number(a.numbers()?.into_iter().fold(f64::NEG_INFINITY, f64::max))
+    }),
+    function(
+        "convert",
+        &["value", "from", "to"],
+        "Converts between units, like convert(temperature, \"f\", \"c\")",
+        convert,
+    ),
+    // Text
+    function("upper", &["text"], "Uppercase text", |a| {
+        string(a.string(0, "text")?.to_uppercase())
+    }),
+    function("lower", &["text"], "Lowercase text", |a| {
+        string(a.string(0, "text")?.to_lowercase())
+    }),
+    function(
+        "pad",
+        &["text", "width", "align", "fill"],
+        "Pads text to a width, align is left, right or center",
+        pad,
+    ),
+    function(
+        "truncate",
+        &["text", "length", "ellipsis"],
+        "Shortens text to a length, ending it with an ellipsis",
+        truncate,
+    ),
+    // Wind
+    function(
+        "compass",
+        &["degrees", "points"],
+        "Compass direction of a heading, with 4, 8 or 16 (default) points",
+        compass,
+    ),
+    function(
+        "arrow",
+        &["degrees"],
+        "Arrow pointing where wind from a heading blows to",
+        |a| string(units::arrow(a.number(0, "degrees")?).to_string()),
+    ),
+    function(
+        "beaufort",
+        &["speed", "unit"],
+        "Beaufort scale name of a wind speed, in km/h or mph depending on the units by default",
+        beaufort,
+    ),
+    // Time
+    function(
+        "is_night",
+        &["time"],
+        "Whether a time is between 18:00 and 6:00 at the forecast location",
+        |a| {
+            Ok(Value::Bool(time::is_night(
+                a.number(0, "time")?,
+                a.utc_offset,
+            )?))
+        },
+    ),
+    function(
+        "relative_time",
+        &["time"],
+        "Time relative to now, like \"in 3h\" or \"5m ago\", times are in milliseconds",
+        |a| string(time::relative(a.number(0, "time")?, local::now() as f64)),
+    ),
+    function(
+        "format_time",
+        &["time", "format", "utc"],
+        "Formats a time in milliseconds, format is strftime-like (%H:%M by default)",
+        format_time,
+    ),
+    // Charts
+    function(
+        "sparkline",
+        &["series", "field", "count"],
+        "Blocks as high as a field of a list from now on, the temperature of 24 hours by default",
+        sparkline,
+    ),
+    function(
+        "chart",
+        &[
+            "series", "field", "count", "height", "width", "unit", "style",
+        ],
+        "Line or bars chart of a field of a list from now on, with axes and the min and max",
+        draw_chart,
+    ),
+    // Colors and images
+    function(
+        "color",
+        &["color", "text"],
+        "Colors text with a theme role like \"warning\" or a color",
+        color_text,
+    ),
+    function(
+        "color_aqi",
+        &["aqi"],
+        "Colors an air quality index",
+        color_aqi,
+    ),
+    function(
+        "color_condition",
+        &["condition", "text"],
+        "Colors text by the group of a condition id, using the theme",
+        color_condition,
+    ),
+    function(
+        "color_scale",
+        &["value", "min", "max", "colors", "text"],
+        "Colors text with a gradient of comma separated colors, by value between min and max",
+        color_scale,
+    ),
+    function(
+        "rainbow",
+        &["text"],
+        "Colors every character of text differently",
+        |a| string(util::rainbow(&a.string(0, "text")?)),
+    ),
+    function(
+        "image",
+        &["source", "scale", "protocol"],
+        "Renders an image from a URL with a terminal graphics protocol or half blocks",
+        |a| {
+            string(util::image(
+                a.string(0, "source")?,
+                a.number_or(1, "scale", 1.)?,
+                &a.string_or(2, "protocol", "auto"),
+            )?)
+        },
+    ),
+    function(
+        "to_ascii",
+        &["condition", "night"],
+        "ASCII art of a condition or condition id, night is whether it is night now by default",
+        to_ascii,
+    ),
+];
+
+pub fn get(name: &str) -> Option<&'static LayoutFunction> {
+    FUNCTIONS.iter().find(|function| function.name == name)
+}
+
+/// Calls a layout function
+pub fn call(
+    name: &str,
+    args: Vec<Value>,
+    kwargs: HashMap<String, Value>,
+    metric: bool,
+    utc_offset: Option<i32>,
+) -> crate::Result<Value> {
+    let function = get(name).ok_or_else(|| layout_error(format!("Function {name} not found")))?;
+    let args = Arguments::new(name, args, kwargs, metric, utc_offset);
+    args.check(function)?;
+    (function.function)(&args)
+}
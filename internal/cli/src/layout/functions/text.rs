@@ -0,0 +1,25 @@
+use terminal::text;
+
+use crate::layout::parse_align;
+
+/// Pads `text` with `fill` to `width` columns
+pub fn pad(text: &str, width: usize, align: &str, fill: &str) -> crate::Result<String> {
+    let align = parse_align(align)?;
+    // Text that is already wider is left as is
+    let width = width.max(text::display_width(text));
+    Ok(text::pad(
+        text,
+        width,
+        align,
+        fill.chars().next().unwrap_or(' '),
+    ))
+}
+
+/// Shortens `text` to `length` columns including the ellipsis
+pub fn truncate(text: &str, length: usize, ellipsis: &str) -> String {
+    if text::display_width(text) <= length {
+        return text.to_string();
+    }
+    let kept = length.saturating_sub(text::display_width(ellipsis));
+    text::truncate(text, kept) + ellipsis
+}
@@ -0,0 +1,93 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{FixedOffset, Local, TimeZone, Timelike, Utc};
+
+use crate::layout::layout_error;
+
+/// Describes how far `time` is from `now` (both in milliseconds), like "in 3h" or "5m ago"
+pub fn relative(time: f64, now: f64) -> String {
+    let minutes = ((time - now) / 60_000.0).round();
+    let distance = minutes.abs();
+    let amount = if distance < 1.0 {
+        return "now".to_string();
+    } else if distance < 60.0 {
+        format!("{distance}m")
+    } else if distance < 60.0 * 48.0 {
+        format!("{}h", (distance / 60.0).round())
+    } else {
+        format!("{}d", (distance / 60.0 / 24.0).round())
+    };
+    if minutes > 0.0 {
+        format!("in {amount}")
+    } else {
+        format!("{amount} ago")
+    }
+}
+
+/// Formats a time in milliseconds in UTC, or at a location `utc_offset` seconds from UTC,
+/// in the local time zone if the offset isn't known
+pub fn format(
+    time: f64,
+    format: &str,
+    utc: bool,
+    utc_offset: Option<i32>,
+) -> crate::Result<String> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(layout_error(format!("Invalid time format {format}")));
+    }
+    let time = Utc
+        .timestamp_millis_opt(time as i64)
+        .single()
+        .ok_or_else(|| layout_error(format!("Invalid time {time}")))?;
+    let offset = if utc { Some(0) } else { utc_offset };
+    Ok(match offset.and_then(FixedOffset::east_opt) {
+        Some(offset) => time
+            .with_timezone(&offset)
+            .format_with_items(items.into_iter())
+            .to_string(),
+        None => time
+            .with_timezone(&Local)
+            .format_with_items(items.into_iter())
+            .to_string(),
+    })
+}
+
+/// Whether a time in milliseconds is between 18:00 and 6:00 at a location `utc_offset` seconds
+/// from UTC, in the local time zone if the offset isn't known
+pub fn is_night(time: f64, utc_offset: Option<i32>) -> crate::Result<bool> {
+    let time = Utc
+        .timestamp_millis_opt(time as i64)
+        .single()
+        .ok_or_else(|| layout_error(format!("Invalid time {time}")))?;
+    let hour = match utc_offset.and_then(FixedOffset::east_opt) {
+        Some(offset) => time.with_timezone(&offset).hour(),
+        None => time.with_timezone(&Local).hour(),
+    };
+    Ok(!(6..18).contains(&hour))
+}
+
+/// Index of the time now is in, the last one that isn't after now, 0 if they are all later
+pub fn current_index(times: &[f64], now: f64) -> usize {
+    times.iter().rposition(|time| *time <= now).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2023-05-06 14:30 UTC
+    const TIME: f64 = 1_683_383_400_000.0;
+
+    #[test]
+    fn utc_offsets() {
+        assert_eq!(format(TIME, "%H:%M", true, Some(7200)).unwrap(), "14:30");
+        assert_eq!(format(TIME, "%H:%M", false, Some(7200)).unwrap(), "16:30");
+        assert_eq!(
+            format(TIME, "%a %H:%M", false, Some(-36_000)).unwrap(),
+            "Sat 04:30"
+        );
+        assert!(!is_night(TIME, Some(0)).unwrap());
+        assert!(is_night(TIME, Some(-36_000)).unwrap());
+        assert!(format(TIME, "%Q", false, None).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::layout::layout_error;
+
+enum Unit {
+    Temperature,
+    /// A unit of a kind and its size in the base unit of the kind
+    Linear(&'static str, f64),
+}
+
+fn get_unit(unit: &str) -> crate::Result<Unit> {
+    Ok(match &*unit.to_lowercase() {
+        "c" | "°c" | "f" | "°f" | "k" => Unit::Temperature,
+        "m/s" => Unit::Linear("speed", 1.0),
+        "km/h" | "kmh" => Unit::Linear("speed", 1.0 / 3.6),
+        "mph" => Unit::Linear("speed", 0.447_04),
+        "kn" | "knots" => Unit::Linear("speed", 0.514_444),
+        "mm" => Unit::Linear("length", 0.001),
+        "cm" => Unit::Linear("length", 0.01),
+        "m" => Unit::Linear("length", 1.0),
+        "km" => Unit::Linear("length", 1000.0),
+        "in" => Unit::Linear("length", 0.0254),
+        "ft" => Unit::Linear("length", 0.3048),
+        "mi" => Unit::Linear("length", 1609.344),
+        "hpa" | "mb" => Unit::Linear("pressure", 100.0),
+        "kpa" => Unit::Linear("pressure", 1000.0),
+        "inhg" => Unit::Linear("pressure", 3386.389),
+        _ => return Err(layout_error(format!("Unknown unit {unit}"))),
+    })
+}
+
+fn to_celsius(value: f64, unit: &str) -> f64 {
+    match unit.trim_start_matches('°') {
+        "f" => (value - 32.0) * 5.0 / 9.0,
+        "k" => value - 273.15,
+        _ => value,
+    }
+}
+
+fn from_celsius(value: f64, unit: &str) -> f64 {
+    match unit.trim_start_matches('°') {
+        "f" => value * 9.0 / 5.0 + 32.0,
+        "k" => value + 273.15,
+        _ => value,
+    }
+}
+
+/// Converts temperatures (c, f, k), speeds (m/s, km/h, mph, kn), lengths (mm, cm, m, km, in,
+/// ft, mi) and pressures (hpa, mb, kpa, inhg)
+pub fn convert(value: f64, from: &str, to: &str) -> crate::Result<f64> {
+    let (from, to) = (from.to_lowercase(), to.to_lowercase());
+    match (get_unit(&from)?, get_unit(&to)?) {
+        (Unit::Temperature, Unit::Temperature) => Ok(from_celsius(to_celsius(value, &from), &to)),
+        (Unit::Linear(from_kind, from_size), Unit::Linear(to_kind, to_size))
+            if from_kind == to_kind =>
+        {
+            Ok(value * from_size / to_size)
+        }
+        _ => Err(layout_error(format!("Can't convert {from} to {to}"))),
+    }
+}
+
+const COMPASS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+/// Index of the sector a heading is in, when the circle is split into `sectors` sectors
+fn sector(degrees: f64, sectors: usize) -> usize {
+    let size = 360.0 / sectors as f64;
+    ((degrees.rem_euclid(360.0) + size / 2.0) / size) as usize % sectors
+}
+
+pub fn compass(degrees: f64, points: usize) -> crate::Result<&'static str> {
+    if ![4, 8, 16].contains(&points) {
+        return Err(layout_error("compass points have to be 4, 8 or 16"));
+    }
+    Ok(COMPASS[sector(degrees, points) * (16 / points)])
+}
+
+/// Wind headings are where the wind comes from, so a north wind gets an arrow pointing down
+pub fn arrow(degrees: f64) -> &'static str {
+    ["↓", "↙", "←", "↖", "↑", "↗", "→", "↘"][sector(degrees, 8)]
+}
+
+const BEAUFORT: [(f64, &str); 12] = [
+    (0.5, "Calm"),
+    (1.6, "Light air"),
+    (3.4, "Light breeze"),
+    (5.5, "Gentle breeze"),
+    (8.0, "Moderate breeze"),
+    (10.8, "Fresh breeze"),
+    (13.9, "Strong breeze"),
+    (17.2, "Near gale"),
+    (20.8, "Gale"),
+    (24.5, "Strong gale"),
+    (28.5, "Storm"),
+    (32.7, "Violent storm"),
+];
+
+pub fn beaufort(speed: f64, unit: &str) -> crate::Result<&'static str> {
+    let speed = convert(speed, unit, "m/s")?;
+    Ok(BEAUFORT
+        .iter()
+        .find(|(limit, _)| speed < *limit)
+        .map_or("Hurricane", |(_, name)| name))
+}
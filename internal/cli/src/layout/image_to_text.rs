@@ -1,28 +1,247 @@
 extern crate image;
 
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::io::Cursor;
 
-use image::{imageops, GenericImageView};
-use terminal::color::rgb;
+use image::{imageops, DynamicImage, GenericImageView, ImageOutputFormat, Rgba, RgbaImage};
+use terminal::color::{rgb, rgb_back, BACK_RESET, RESET};
+use terminal::graphics::Graphics;
 
-pub fn ascii_image(input_path: &str, scale: f64) -> crate::Result<String> {
-    let img = image::open(Path::new(input_path)).map_err(|_e| "Failed to open image")?;
-    let img_width = img.dimensions().0;
-    let img_height = img.dimensions().1;
+/// Size of a terminal cell in pixels, terminals don't tell it so a common one is assumed
+const CELL_WIDTH: u32 = 10;
+const CELL_HEIGHT: u32 = 20;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.len() * 4 / 3 + 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | u32::from(*byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(char::from(BASE64[(n >> (18 - 6 * i) & 63) as usize]));
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+fn png(img: RgbaImage) -> crate::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    DynamicImage::ImageRgba8(img)
+        .write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png)
+        .map_err(|_e| "Failed to encode image")?;
+    Ok(data)
+}
+
+fn is_opaque(pixel: &Rgba<u8>) -> bool {
+    pixel[3] >= 128
+}
+
+/// Every pixel is half a character, `▀` is colored with the top pixel and its background
+/// with the bottom one
+fn half_blocks(img: &RgbaImage) -> String {
+    let (width, height) = img.dimensions();
+    let mut lines = Vec::new();
+    for y in (0..height).step_by(2) {
+        let mut line = String::new();
+        for x in 0..width {
+            let top = Some(img.get_pixel(x, y)).filter(|p| is_opaque(p));
+            let bottom = (y + 1 < height)
+                .then(|| img.get_pixel(x, y + 1))
+                .filter(|p| is_opaque(p));
+            line += &match (top, bottom) {
+                (Some(top), Some(bottom)) => format!(
+                    "{}{}▀",
+                    rgb(top[0], top[1], top[2]),
+                    rgb_back(bottom[0], bottom[1], bottom[2])
+                ),
+                (Some(top), None) => format!("{}{BACK_RESET}▀", rgb(top[0], top[1], top[2])),
+                (None, Some(bottom)) => {
+                    format!("{}{BACK_RESET}▄", rgb(bottom[0], bottom[1], bottom[2]))
+                }
+                (None, None) => format!("{BACK_RESET} "),
+            };
+        }
+        lines.push(line + RESET);
+    }
+    lines.join("\n")
+}
+
+/// Kitty graphics protocol, the PNG is sent in chunks of 4096 bytes
+fn kitty(png: &[u8], columns: u32, rows: u32) -> String {
+    let data = base64(png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut s = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        // q=2 stops the terminal from answering
+        let control = if i == 0 {
+            format!("f=100,a=T,q=2,c={columns},r={rows},m={more}")
+        } else {
+            format!("m={more}")
+        };
+        s += &format!("\x1b_G{control};{}\x1b\\", String::from_utf8_lossy(chunk));
+    }
+    s
+}
+
+/// iTerm2 inline image
+fn iterm2(png: &[u8], columns: u32, rows: u32) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={columns};height={rows};preserveAspectRatio=0:{}\x07",
+        png.len(),
+        base64(png)
+    )
+}
+
+/// Index in a palette of 6 levels per channel, transparent pixels have none
+fn sixel_color(pixel: &Rgba<u8>) -> Option<usize> {
+    let level = |c: u8| (usize::from(c) * 5 + 127) / 255;
+    is_opaque(pixel).then(|| level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+}
+
+fn push_sixels(s: &mut String, sixel: u8, count: usize) {
+    let c = char::from(63 + sixel);
+    if count > 3 {
+        *s += &format!("!{count}{c}");
+    } else {
+        for _ in 0..count {
+            s.push(c);
+        }
+    }
+}
+
+/// Sixel graphics, drawn in bands of 6 rows with one pass per color
+fn sixel(img: &RgbaImage) -> String {
+    let (width, height) = img.dimensions();
+    let colors: Vec<Option<usize>> = img.pixels().map(sixel_color).collect();
+    let color_at = |x: u32, y: u32| colors[(y * width + x) as usize];
+    // Transparent pixels are left as they are
+    let mut s = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    let mut used = [false; 216];
+    for color in colors.iter().flatten() {
+        used[*color] = true;
+    }
+    let percent = |level: usize| level * 20;
+    for color in (0..216).filter(|color| used[*color]) {
+        s += &format!(
+            "#{color};2;{};{};{}",
+            percent(color / 36),
+            percent(color / 6 % 6),
+            percent(color % 6)
+        );
+    }
+    for top in (0..height).step_by(6) {
+        let bottom = (top + 6).min(height);
+        // The sixels of every color in the band
+        let mut lines: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+        for y in top..bottom {
+            for x in 0..width {
+                if let Some(color) = color_at(x, y) {
+                    lines
+                        .entry(color)
+                        .or_insert_with(|| vec![0; width as usize])[x as usize] |= 1 << (y - top);
+                }
+            }
+        }
+        for (i, (color, sixels)) in lines.into_iter().enumerate() {
+            if i > 0 {
+                // Back to the start of the band
+                s.push('$');
+            }
+            s += &format!("#{color}");
+            let mut run = (sixels[0], 0);
+            for sixel in sixels {
+                if sixel != run.0 {
+                    push_sixels(&mut s, run.0, run.1);
+                    run = (sixel, 0);
+                }
+                run.1 += 1;
+            }
+            // Empty sixels at the end of a line don't have to be drawn
+            if run.0 != 0 {
+                push_sixels(&mut s, run.0, run.1);
+            }
+        }
+        if bottom < height {
+            s.push('-');
+        }
+    }
+    s + "\x1b\\"
+}
+
+/// `rows` lines of `columns` spaces with the image drawn over them, so the image is measured like
+/// text in columns and boxes. The end of the last line moves the cursor to the top left of the
+/// spaces to draw the image, ESC 7 and ESC 8 keep the cursor where the spaces end
+fn reserve_cells(image: &str, columns: u32, rows: u32) -> String {
+    let mut lines = vec![" ".repeat(columns as usize); rows as usize];
+    let up = if rows > 1 {
+        format!("\x1b[{}A", rows - 1)
+    } else {
+        String::new()
+    };
+    if let Some(last) = lines.last_mut() {
+        *last += &format!("\x1b7{up}\x1b[{columns}D{image}\x1b8");
+    }
+    lines.join("\n")
+}
+
+/// Renders an image for the terminal, `scale` is the number of columns for every pixel of the
+/// image, so the size is the same for all protocols
+pub fn to_text(data: &[u8], scale: f64, graphics: Graphics) -> crate::Result<String> {
+    let img = image::load_from_memory(data).map_err(|_e| "Failed to open image")?;
+    let (img_width, img_height) = img.dimensions();
     #[allow(clippy::cast_sign_loss)]
-    let new_img = img.resize(
-        (f64::from(img_width) * scale) as u32,
-        (f64::from(img_height) * scale) as u32,
-        imageops::FilterType::Nearest,
+    let size = |pixels: u32| (f64::from(pixels) * scale).round().max(1.) as u32;
+    let (columns, pixel_rows) = (size(img_width), size(img_height));
+    let rows = pixel_rows / 2 + pixel_rows % 2;
+    if graphics == Graphics::HalfBlocks {
+        let img = imageops::resize(
+            &img.to_rgba8(),
+            columns,
+            pixel_rows,
+            imageops::FilterType::Triangle,
+        );
+        return Ok(half_blocks(&img));
+    }
+    let img = imageops::resize(
+        &img.to_rgba8(),
+        columns * CELL_WIDTH,
+        rows * CELL_HEIGHT,
+        imageops::FilterType::Triangle,
     );
-    let mut text = String::new();
-    for p in new_img.pixels() {
-        if p.1 == 0 {
-            text += "\n";
+    let image = match graphics {
+        Graphics::Kitty => kitty(&png(img)?, columns, rows),
+        Graphics::Iterm2 => iterm2(&png(img)?, columns, rows),
+        _ => sixel(&img),
+    };
+    Ok(reserve_cells(&image, columns, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminal::text::display_width;
+
+    #[test]
+    fn image_cells() {
+        let data = png(RgbaImage::from_pixel(6, 8, Rgba([255, 0, 0, 255]))).unwrap();
+        for graphics in [
+            Graphics::HalfBlocks,
+            Graphics::Kitty,
+            Graphics::Iterm2,
+            Graphics::Sixel,
+        ] {
+            let text = to_text(&data, 0.5, graphics).unwrap();
+            let lines: Vec<&str> = text.lines().collect();
+            assert_eq!(lines.len(), 2);
+            assert!(lines.iter().all(|line| display_width(line) == 3));
         }
-        let pixel = p.2 .0;
-        text += &rgb(pixel[0], pixel[1], pixel[2]);
-        text += "█";
     }
-    Ok(text)
 }
@@ -1,9 +1,10 @@
+use std::collections::HashMap;
+
 use serde_json::Value;
 
+use crate::layout::expression::{self, Value as ExprValue};
 use crate::layout::layout_serde::ItemSerde;
-use crate::layout::{util, LayoutSettings};
-use terminal::color;
-use weather_error::LayoutErr;
+use crate::layout::{functions, layout_error, theme, LayoutSettings};
 
 pub struct Item {
     data: ItemSerde,
@@ -18,84 +19,84 @@ impl Item {
         Self { data: i }
     }
 
+    /// Follows a path like `forecast.[0].temperature` through the forecast
     fn get_variable_value(&self, data: &Value) -> crate::Result<String> {
-        let mut split: Vec<&str> = self.data.value.split('.').collect();
+        let path = &self.data.value;
         let mut current = data;
-        while !split.is_empty() {
-            if split[0]
-                .chars()
-                .next()
-                .expect("0th element expected don't place two dots in a row, like: \"..\"")
-                == '['
-            {
+        for segment in path.split('.') {
+            let next = if segment.is_empty() {
+                return Err(layout_error(format!("Empty segment in variable {path}")));
+            } else if let Some(index) = segment.strip_prefix('[') {
                 // list item
-                let place = split[0][1..split.clone()[0].len() - 1]
-                    .parse::<usize>()
-                    .unwrap();
-                current = &current[place];
+                let index = index
+                    .strip_suffix(']')
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .ok_or_else(|| {
+                        layout_error(format!("Invalid list index {segment} in variable {path}"))
+                    })?;
+                current.get(index)
             } else {
                 // normal variable
-                if current.is_null() {
-                    return Err(weather_error::Error::LayoutError(LayoutErr {
-                        message: "Variable not found in data".to_string(),
-                        row: None,
-                        item: None,
-                    }));
-                }
-                current = &current[split[0]];
-            }
-            split.remove(0);
+                current.get(segment)
+            };
+            current = next.ok_or_else(|| {
+                layout_error(format!(
+                    "Variable {path} not found in data ({segment} is missing)"
+                ))
+            })?;
         }
         match current.as_str() {
             Some(t) => Ok(t.to_string()),
             None => match current.as_f64() {
                 Some(t) => Ok(round(t)),
-                None => Ok(current
-                    .as_i64()
-                    .ok_or_else(|| {
-                        weather_error::Error::LayoutError(LayoutErr {
-                            message: "Json type not supported".to_string(),
-                            row: None,
-                            item: None,
-                        })
-                    })?
-                    .to_string()),
+                None => Err(layout_error(format!(
+                    "Variable {path} is not a string or a number"
+                ))),
             },
         }
     }
 
     fn get_function_value(&self, data: &Value) -> crate::Result<String> {
-        let args = self.data.args.clone().unwrap_or_default();
-        let _kwargs = self.data.kwargs.clone().unwrap_or_default();
-        match &*self.data.value {
-            "color_aqi" => util::color_aqi(
-                Self::new(args[0].clone())
-                    .get_value(data)?
-                    .parse()
-                    .unwrap_or(0),
-            ),
-            "image" => util::image(
-                Self::new(args[0].clone()).get_value(data)?.parse().unwrap(),
-                Self::new(args[1].clone())
-                    .get_value(data)?
-                    .parse()
-                    .unwrap_or(1.),
-            ),
-            _ => Err(weather_error::Error::LayoutError(LayoutErr {
-                message: "Function not found".to_string(),
-                row: None,
-                item: None,
-            })), // TODO: add more functions
-        }
+        let args = self
+            .data
+            .args
+            .iter()
+            .flatten()
+            .map(|arg| Ok(ExprValue::String(Self::new(arg.clone()).get_value(data)?)))
+            .collect::<crate::Result<Vec<ExprValue>>>()?;
+        let kwargs = self
+            .data
+            .kwargs
+            .iter()
+            .flatten()
+            .map(|(key, arg)| {
+                Ok((
+                    key.clone(),
+                    ExprValue::String(Self::new(arg.clone()).get_value(data)?),
+                ))
+            })
+            .collect::<crate::Result<HashMap<String, ExprValue>>>()?;
+        Ok(functions::call(
+            &self.data.value,
+            args,
+            kwargs,
+            expression::is_metric(data),
+            expression::utc_offset(data),
+        )?
+        .to_string())
+    }
+
+    fn get_expression_value(&self, data: &Value) -> crate::Result<String> {
+        Ok(expression::eval_str(&self.data.value, data)?.to_string())
     }
 
     pub fn get_value(&self, data: &Value) -> crate::Result<String> {
-        if self.data.item_type == "variable" {
-            return self.get_variable_value(data);
-        } else if self.data.item_type == "function" {
-            return self.get_function_value(data);
+        match &*self.data.item_type {
+            "variable" => self.get_variable_value(data),
+            "function" => self.get_function_value(data),
+            "expression" => self.get_expression_value(data),
+            _ => Ok(self.data.value.clone()),
         }
-        Ok(self.data.value.clone())
     }
 
     pub fn to_string(
@@ -110,18 +111,20 @@ impl Item {
         let variable_bg_color = settings.variable_bg_color;
         let unit_color = settings.unit_color;
         let unit_bg_color = settings.unit_bg_color;
-        let item_color =
-            color::from_string(self.data.color.clone().unwrap_or_default()).unwrap_or_default();
-        let item_bg_color =
-            color::from_string(self.data.bg_color.clone().unwrap_or_default()).unwrap_or_default();
-        let item_color_string = item_color + &item_bg_color;
+        // Item colors can be theme roles like `warning` as well as colors
+        let item_color_string = theme::with_current(|theme| {
+            [&self.data.color, &self.data.bg_color]
+                .iter()
+                .filter_map(|name| name.as_deref().and_then(|name| theme.color(name)))
+                .collect::<String>()
+        });
         if self.data.item_type == "text" {
             return Ok(format!(
                 "{text_color}{text_bg_color}{item_color_string}{}",
                 &self.data.value
             ));
-        } else if self.data.item_type == "variable" {
-            let value = self.get_variable_value(data)?;
+        } else if self.data.item_type == "variable" || self.data.item_type == "expression" {
+            let value = self.get_value(data)?;
             let s = format!("{variable_color}{variable_bg_color}{item_color_string}{value}{unit_color}{unit_bg_color}");
             return if metric {
                 Ok(s + &self.data.metric.clone().unwrap_or_default())
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ItemSerde {
     #[serde(rename = "type")]
     pub item_type: String,
@@ -27,9 +28,116 @@ pub struct LayoutDefaultsSerde {
     pub unit_bg_color: String,
 }
 
+impl Default for LayoutDefaultsSerde {
+    fn default() -> Self {
+        Self {
+            variable_color: "variable".to_string(),
+            text_color: "text".to_string(),
+            unit_color: "unit".to_string(),
+            variable_bg_color: "variable_bg".to_string(),
+            text_bg_color: "text_bg".to_string(),
+            unit_bg_color: "unit_bg".to_string(),
+        }
+    }
+}
+
+/// Repeats rows for every element of a list in the forecast
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RepeatSerde {
+    /// Expression for the list, like `forecast` or `daily`
+    pub over: String,
+    /// Variable the current element is stored in
+    pub name: String,
+    /// Variable the position of the current element in the list is stored in
+    pub index: String,
+    pub start: usize,
+    /// The element at `end` is not included, by default the list is repeated until its end
+    pub end: Option<usize>,
+    pub step: usize,
+    /// Expression deciding whether an element is shown
+    pub filter: Option<String>,
+    pub rows: Vec<RowSerde>,
+}
+
+/// A column of a `ColumnsSerde` row, without a width or flex it is as wide as its content
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ColumnSerde {
+    /// Width in terminal columns
+    pub width: Option<usize>,
+    /// Share of the width the other columns leave over
+    pub flex: Option<usize>,
+    /// left, right or center
+    pub align: String,
+    /// Expression deciding whether the column is shown
+    pub when: Option<String>,
+    pub rows: Vec<RowSerde>,
+}
+
+/// Rows placed next to each other
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ColumnsSerde {
+    pub columns: Vec<ColumnSerde>,
+    pub separator: String,
+}
+
+/// Rows with a border around them
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BoxSerde {
+    /// Shown in the top border
+    pub title: String,
+    /// single, rounded, double, heavy or ascii
+    pub border: String,
+    /// Color of the border
+    pub color: Option<String>,
+    /// Width including the border, by default the box is as wide as its content
+    pub width: Option<usize>,
+    /// Whether the box takes up the whole width
+    pub fill: bool,
+    pub align: String,
+    /// Spaces between the border and the content
+    pub padding: usize,
+    pub rows: Vec<RowSerde>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RowSerde {
+    Items(Vec<ItemSerde>),
+    Repeat(RepeatSerde),
+    Columns(ColumnsSerde),
+    Box(BoxSerde),
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct LayoutSerde {
     pub version: u64,
     pub defaults: LayoutDefaultsSerde,
-    pub layout: Vec<Vec<ItemSerde>>,
+    pub layout: Vec<RowSerde>,
+}
+
+/// Compiled layouts before version 21, which only had rows of items
+#[derive(Deserialize)]
+struct LegacyLayoutSerde {
+    version: u64,
+    defaults: LayoutDefaultsSerde,
+    layout: Vec<Vec<ItemSerde>>,
+}
+
+#[derive(Deserialize)]
+struct VersionSerde {
+    version: u64,
+}
+
+impl LayoutSerde {
+    /// Reads a compiled layout of any version
+    pub fn from_bincode(data: &[u8]) -> crate::Result<Self> {
+        if bincode::deserialize::<VersionSerde>(data)?.version >= 21 {
+            return Ok(bincode::deserialize(data)?);
+        }
+        let legacy: LegacyLayoutSerde = bincode::deserialize(data)?;
+        Ok(Self {
+            version: legacy.version,
+            defaults: legacy.defaults,
+            layout: legacy.layout.into_iter().map(RowSerde::Items).collect(),
+        })
+    }
 }
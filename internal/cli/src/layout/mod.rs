@@ -1,17 +1,31 @@
+use std::fs;
+use std::path::Path;
+
 use crate::layout::layout_serde::LayoutDefaultsSerde;
 use crate::layout::row::Row;
 use backend::WeatherForecast;
-use local::weather_file::WeatherFile;
-use terminal::color;
+use terminal::text::Align;
+use weather_dirs::layouts_dir;
 use weather_error::{Error, LayoutErr};
 
+pub mod check;
+mod columns;
+pub mod expression;
+pub mod functions;
 mod image_to_text;
 pub mod item;
 pub mod layout_serde;
+mod panel;
 mod row;
+pub mod sample;
+pub mod source;
+pub mod theme;
 pub mod util;
 
-pub const VERSION: u64 = 20;
+/// Extensions of layout files, `.res` files are compiled layouts
+pub const EXTENSIONS: [&str; 3] = ["json", "toml", "res"];
+
+pub const VERSION: u64 = 21;
 
 #[derive(Clone)]
 pub struct LayoutSettings {
@@ -25,7 +39,26 @@ pub struct LayoutSettings {
 
 pub struct LayoutFile {
     layout: Vec<Row>,
-    settings: LayoutSettings,
+    defaults: LayoutDefaultsSerde,
+}
+
+/// A layout error without a location, the location is added by the row and the layout
+pub(crate) fn layout_error<S: Into<String>>(message: S) -> Error {
+    Error::LayoutError(LayoutErr {
+        message: message.into(),
+        row: None,
+        item: None,
+        line: None,
+        column: None,
+    })
+}
+
+pub(crate) fn parse_align(align: &str) -> crate::Result<Align> {
+    Align::from_string(align).ok_or_else(|| {
+        layout_error(format!(
+            "align has to be left, right or center, not {align}"
+        ))
+    })
 }
 
 fn reemit_layout_error(e: Error, count: usize) -> Error {
@@ -34,17 +67,21 @@ fn reemit_layout_error(e: Error, count: usize) -> Error {
             message: e.message,
             row: Some(count as u64),
             item: e.item,
+            line: e.line,
+            column: e.column,
         }),
         _ => e,
     }
 }
 
-fn check_version(version: u64) -> crate::Result<()> {
+pub(crate) fn check_version(version: u64) -> crate::Result<()> {
     if version > VERSION {
         return Err(Error::LayoutError(LayoutErr {
             message: format!("Version of layout file, {version}, is greater than the highest supported version {VERSION}"),
             row: None,
             item: None,
+            line: None,
+            column: None,
         }));
     } else if version <= 10 {
         return Err(Error::LayoutError(LayoutErr {
@@ -52,66 +89,96 @@ fn check_version(version: u64) -> crate::Result<()> {
                 .to_string(),
             row: None,
             item: None,
+            line: None,
+            column: None,
         }));
     }
     Ok(())
 }
 
-fn get_layout_settings(data: LayoutDefaultsSerde) -> LayoutSettings {
-    let retrieved_settings = data;
-    let variable_color =
-        color::from_string(retrieved_settings.clone().variable_color).unwrap_or_default();
-    let text_color = color::from_string(retrieved_settings.clone().text_color).unwrap_or_default();
-    let unit_color = color::from_string(retrieved_settings.clone().unit_color).unwrap_or_default();
-    let variable_bg_color =
-        color::from_string(retrieved_settings.clone().variable_bg_color).unwrap_or_default();
-    let text_bg_color =
-        color::from_string(retrieved_settings.clone().text_bg_color).unwrap_or_default();
-    let unit_bg_color = color::from_string(retrieved_settings.unit_bg_color).unwrap_or_default();
+/// Resolves a default color through the current theme, the colors layouts used before themes
+/// existed are treated as the role they were the default of, so themes apply to older layouts
+fn resolve_default(name: &str, role: &str, legacy: &str) -> String {
+    let name = if name == legacy { role } else { name };
+    theme::with_current(|theme| theme.color(name)).unwrap_or_default()
+}
+
+fn get_layout_settings(data: &LayoutDefaultsSerde) -> LayoutSettings {
     LayoutSettings {
-        variable_color,
-        text_color,
-        unit_color,
-        variable_bg_color,
-        text_bg_color,
-        unit_bg_color,
+        variable_color: resolve_default(&data.variable_color, "variable", "FORE_LIGHTGREEN"),
+        text_color: resolve_default(&data.text_color, "text", "FORE_LIGHTBLUE"),
+        unit_color: resolve_default(&data.unit_color, "unit", "FORE_MAGENTA"),
+        variable_bg_color: resolve_default(&data.variable_bg_color, "variable_bg", "BACK_RESET"),
+        text_bg_color: resolve_default(&data.text_bg_color, "text_bg", "BACK_RESET"),
+        unit_bg_color: resolve_default(&data.unit_bg_color, "unit_bg", "BACK_RESET"),
+    }
+}
+
+/// Reads a JSON, TOML or compiled (`.res`) layout without checking it
+pub fn read_layout<P: AsRef<Path>>(path: P) -> crate::Result<layout_serde::LayoutSerde> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return Err(format!("Layout file {} not found", path.display()).into());
+    }
+    let data = fs::read(path)?;
+    let ext = path
+        .extension()
+        .unwrap_or_else(|| "res".as_ref())
+        .to_string_lossy();
+    match &*ext {
+        "res" => layout_serde::LayoutSerde::from_bincode(&data),
+        "json" | "toml" => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            source::compile_cached(&name, &data, &ext)
+        }
+        _ => Err("Layout file does not have an extension of .json, .toml or .res")?,
+    }
+}
+
+/// The forecast as layouts see it, functions like `beaufort` use the units of the forecast, and
+/// layouts can adapt to the width of the terminal
+pub(crate) fn to_scope(
+    data: WeatherForecast,
+    metric: bool,
+    width: usize,
+) -> crate::Result<serde_json::Value> {
+    let mut scope = serde_json::to_value(data)?;
+    if let Some(object) = scope.as_object_mut() {
+        object.insert("metric".to_string(), metric.into());
+        object.insert("terminal_width".to_string(), width.into());
     }
+    Ok(scope)
 }
 
 impl LayoutFile {
+    /// Loads a layout from the layouts directory
     pub fn new(path: String) -> crate::Result<Self> {
-        let file = WeatherFile::new("layouts/".to_string() + &path)?;
-        let ext = file
-            .path
-            .extension()
-            .unwrap_or_else(|| "res".as_ref())
-            .to_str()
-            .unwrap();
-        if ext == "res" {
-            return Self::from_serde(bincode::deserialize(&file.data)?);
-        }
-        Err("Layout file does not have an extension of .res")?
+        Self::from_path(layouts_dir()?.join(path))
     }
 
-    fn from_serde(file_data: layout_serde::LayoutSerde) -> crate::Result<Self> {
+    /// Loads a JSON, TOML or compiled (`.res`) layout
+    pub fn from_path<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        Self::from_serde(read_layout(path)?)
+    }
+
+    pub fn from_serde(file_data: layout_serde::LayoutSerde) -> crate::Result<Self> {
         check_version(file_data.version)?;
-        let layout = file_data.layout;
-        let mut internal_layout: Vec<Row> = Vec::new();
-        for row in layout.into_iter() {
-            internal_layout.push(Row::new(row));
-        }
         Ok(Self {
-            layout: internal_layout,
-            settings: get_layout_settings(file_data.defaults),
+            layout: file_data.layout.into_iter().map(Row::new).collect(),
+            defaults: file_data.defaults,
         })
     }
 
     pub fn to_string(&self, data: WeatherForecast, metric: bool) -> crate::Result<String> {
         let mut s = Vec::new();
-        let data_value = serde_json::to_value(data)?;
+        let width = terminal::text::terminal_width();
+        let data_value = to_scope(data, metric, width)?;
+        // Resolved here rather than when loading, so the layout follows the current theme
+        let settings = get_layout_settings(&self.defaults);
         for (count, row) in self.layout.iter().enumerate() {
-            s.push(
-                row.to_string(&data_value, self.settings.clone(), metric)
+            s.append(
+                &mut row
+                    .to_lines(&data_value, settings.clone(), metric, width)
                     .map_err(|e| reemit_layout_error(e, count))?,
             );
         }
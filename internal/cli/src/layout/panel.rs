@@ -0,0 +1,117 @@
+use serde_json::Value;
+use terminal::color;
+use terminal::text::{display_width, pad, truncate};
+
+use crate::layout::layout_error;
+use crate::layout::layout_serde::BoxSerde;
+use crate::layout::row::{render_rows, Row};
+use crate::layout::{parse_align, theme, LayoutSettings};
+
+/// Top left, top right, bottom left and bottom right corners, horizontal and vertical lines
+const BORDERS: [(&str, [&str; 6]); 5] = [
+    ("single", ["┌", "┐", "└", "┘", "─", "│"]),
+    ("rounded", ["╭", "╮", "╰", "╯", "─", "│"]),
+    ("double", ["╔", "╗", "╚", "╝", "═", "║"]),
+    ("heavy", ["┏", "┓", "┗", "┛", "━", "┃"]),
+    ("ascii", ["+", "+", "+", "+", "-", "|"]),
+];
+
+pub(crate) fn border(name: &str) -> crate::Result<[&'static str; 6]> {
+    BORDERS
+        .iter()
+        .find(|(border, _)| *border == name)
+        .map(|(_, border)| *border)
+        .ok_or_else(|| {
+            layout_error(format!(
+                "border has to be single, rounded, double, heavy or ascii, not {name}"
+            ))
+        })
+}
+
+/// Rows with a border around them
+pub struct Panel {
+    settings: BoxSerde,
+    rows: Vec<Row>,
+}
+
+impl Panel {
+    pub fn new(mut data: BoxSerde) -> Self {
+        let rows = std::mem::take(&mut data.rows)
+            .into_iter()
+            .map(Row::new)
+            .collect();
+        Self {
+            settings: data,
+            rows,
+        }
+    }
+
+    pub fn to_lines(
+        &self,
+        data: &Value,
+        settings: LayoutSettings,
+        metric: bool,
+        width: usize,
+    ) -> crate::Result<Vec<String>> {
+        let panel = &self.settings;
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] =
+            border(&panel.border)?;
+        let align = parse_align(&panel.align)?;
+        let frame = 2 + 2 * panel.padding;
+        let outer_width = match panel.width {
+            Some(outer_width) => Some(outer_width),
+            None if panel.fill => Some(width),
+            None => None,
+        };
+        let available = outer_width.unwrap_or(width).saturating_sub(frame);
+        let lines = render_rows(&self.rows, data, settings, metric, available)?;
+        let inner_width = match outer_width {
+            Some(_) => available,
+            None => {
+                let content = lines.iter().map(|l| display_width(l)).max().unwrap_or(0);
+                let title = if panel.title.is_empty() {
+                    0
+                } else {
+                    (display_width(&panel.title) + 3).saturating_sub(2 * panel.padding)
+                };
+                content.max(title).min(available)
+            }
+        };
+        let color = panel
+            .color
+            .as_deref()
+            .and_then(|name| theme::with_current(|theme| theme.color(name)))
+            .unwrap_or_default();
+        let paint = |s: String| {
+            if color.is_empty() {
+                s
+            } else {
+                format!("{color}{s}{}", color::RESET)
+            }
+        };
+        let spacing = " ".repeat(panel.padding);
+        let border_width = inner_width + 2 * panel.padding;
+        let top = if panel.title.is_empty() || border_width < 3 {
+            horizontal.repeat(border_width)
+        } else {
+            let title = truncate(&panel.title, border_width - 3);
+            let rest = border_width - 3 - display_width(&title);
+            format!("{horizontal} {title} {}", horizontal.repeat(rest))
+        };
+        let mut result = vec![paint(format!("{top_left}{top}{top_right}"))];
+        for line in lines {
+            let text = truncate(&line, inner_width) + color::RESET;
+            result.push(format!(
+                "{}{spacing}{}{spacing}{}",
+                paint(vertical.to_string()),
+                pad(&text, inner_width, align, ' '),
+                paint(vertical.to_string())
+            ));
+        }
+        result.push(paint(format!(
+            "{bottom_left}{}{bottom_right}",
+            horizontal.repeat(border_width)
+        )));
+        Ok(result)
+    }
+}
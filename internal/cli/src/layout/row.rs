@@ -1,12 +1,24 @@
 use serde_json::Value;
 
+use crate::layout::columns::Columns;
+use crate::layout::expression;
 use crate::layout::item::Item;
-use crate::layout::layout_serde::ItemSerde;
+use crate::layout::layout_error;
+use crate::layout::layout_serde::{RepeatSerde, RowSerde};
+use crate::layout::panel::Panel;
 use crate::layout::LayoutSettings;
 use weather_error::{Error, LayoutErr};
 
-pub struct Row {
-    items: Vec<Item>,
+pub enum Row {
+    Items(Vec<Item>),
+    Repeat(Repeat),
+    Columns(Columns),
+    Box(Panel),
+}
+
+pub struct Repeat {
+    settings: RepeatSerde,
+    rows: Vec<Row>,
 }
 
 fn reemit_layout_error(e: Error, count: usize) -> Error {
@@ -15,32 +27,124 @@ fn reemit_layout_error(e: Error, count: usize) -> Error {
             message: e.message,
             row: None,
             item: Some(count as u64),
+            line: e.line,
+            column: e.column,
         }),
         _ => e,
     }
 }
 
 impl Row {
-    pub fn new(data: Vec<ItemSerde>) -> Self {
-        let mut items: Vec<Item> = Vec::new();
-        for item in data.into_iter() {
-            items.push(Item::new(item));
+    pub fn new(data: RowSerde) -> Self {
+        match data {
+            RowSerde::Items(items) => Self::Items(items.into_iter().map(Item::new).collect()),
+            RowSerde::Repeat(mut repeat) => {
+                let rows = std::mem::take(&mut repeat.rows)
+                    .into_iter()
+                    .map(Self::new)
+                    .collect();
+                Self::Repeat(Repeat {
+                    settings: repeat,
+                    rows,
+                })
+            }
+            RowSerde::Columns(columns) => Self::Columns(Columns::new(columns)),
+            RowSerde::Box(panel) => Self::Box(Panel::new(panel)),
+        }
+    }
+
+    /// Renders the row, which can have any number of lines, `width` is the number of terminal
+    /// columns available
+    pub fn to_lines(
+        &self,
+        data: &Value,
+        settings: LayoutSettings,
+        metric: bool,
+        width: usize,
+    ) -> crate::Result<Vec<String>> {
+        match self {
+            Self::Items(items) => {
+                let mut s = String::new();
+                for (count, i) in items.iter().enumerate() {
+                    s += &*i
+                        .to_string(data, settings.clone(), metric)
+                        .map_err(|e| reemit_layout_error(e, count))?;
+                }
+                Ok(vec![s])
+            }
+            Self::Repeat(repeat) => repeat.to_lines(data, settings, metric, width),
+            Self::Columns(columns) => columns.to_lines(data, settings, metric, width),
+            Self::Box(panel) => panel.to_lines(data, settings, metric, width),
         }
-        Self { items }
     }
+}
 
-    pub fn to_string(
+impl Repeat {
+    fn to_lines(
         &self,
         data: &Value,
         settings: LayoutSettings,
         metric: bool,
-    ) -> crate::Result<String> {
-        let mut s = String::new();
-        for (count, i) in self.items.iter().enumerate() {
-            s += &*i
-                .to_string(data, settings.clone(), metric)
-                .map_err(|e| reemit_layout_error(e, count))?;
+        width: usize,
+    ) -> crate::Result<Vec<String>> {
+        let repeat = &self.settings;
+        let list = match expression::eval_str(&repeat.over, data)? {
+            expression::Value::Json(Value::Array(list)) => list,
+            expression::Value::Null => Vec::new(),
+            value => {
+                return Err(layout_error(format!(
+                    "Can't repeat over {}, it is a {}",
+                    repeat.over,
+                    value.type_name()
+                )))
+            }
+        };
+        if repeat.step == 0 {
+            return Err(layout_error("The step of a repeat can't be 0"));
+        }
+        let filter = repeat
+            .filter
+            .as_deref()
+            .map(expression::parse)
+            .transpose()?;
+        let end = repeat.end.unwrap_or(list.len()).min(list.len());
+        // The element and its index are added to a copy of the data, so they can be used like
+        // any other variable
+        let mut scope = data.clone();
+        let mut lines = Vec::new();
+        for index in (repeat.start..end).step_by(repeat.step) {
+            if let Some(object) = scope.as_object_mut() {
+                object.insert(repeat.name.clone(), list[index].clone());
+                object.insert(repeat.index.clone(), index.into());
+            }
+            if let Some(filter) = &filter {
+                if !expression::evaluate(filter, &scope)?.is_truthy() {
+                    continue;
+                }
+            }
+            lines.append(&mut render_rows(
+                &self.rows,
+                &scope,
+                settings.clone(),
+                metric,
+                width,
+            )?);
         }
-        Ok(s)
+        Ok(lines)
+    }
+}
+
+/// Renders rows below each other
+pub fn render_rows(
+    rows: &[Row],
+    data: &Value,
+    settings: LayoutSettings,
+    metric: bool,
+    width: usize,
+) -> crate::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for row in rows {
+        lines.append(&mut row.to_lines(data, settings.clone(), metric, width)?);
     }
+    Ok(lines)
 }
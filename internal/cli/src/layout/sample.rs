@@ -0,0 +1,154 @@
+//! Made up forecasts, used to check and preview layouts without fetching one
+
+use backend::WeatherForecast;
+use serde_json::{json, Value};
+
+/// Names of the sample forecasts, `missing` has the lists and texts backends can leave empty
+pub const SAMPLES: [&str; 5] = ["sunny", "rain", "snow", "storm", "missing"];
+
+const HOUR: i64 = 3_600_000;
+
+/// How the weather looks in a sample
+struct Weather {
+    /// In degrees Celsius
+    temperature: f64,
+    condition_id: u16,
+    sentence: &'static str,
+    /// Name of the ASCII art
+    image_ascii: &'static str,
+    cloud_cover: i64,
+    /// In km/h
+    wind: f64,
+    forecast_sentence: &'static str,
+    alert: Option<&'static str>,
+}
+
+fn weather(name: &str) -> crate::Result<Weather> {
+    let (temperature, condition_id, sentence, image_ascii) = match name {
+        "sunny" | "missing" => (24.0, 800, "Clear sky", "Sunny"),
+        "rain" => (12.0, 501, "Moderate rain", "HeavyRain"),
+        "snow" => (-3.0, 601, "Snow", "LightSnow"),
+        "storm" => (19.0, 211, "Thunderstorm", "ThunderyHeavyRain"),
+        _ => {
+            return Err(format!(
+                "There is no sample called {name}, try {}",
+                SAMPLES.join(", ")
+            ))?
+        }
+    };
+    let (cloud_cover, wind, forecast_sentence, alert) = match name {
+        "sunny" | "missing" => (5, 8.0, "Clear skies all day.", None),
+        "rain" => (95, 18.0, "Rain for the next 6 hours.", None),
+        "snow" => (90, 14.0, "Snow until tomorrow.", Some("Winter Storm Watch")),
+        _ => (
+            100,
+            45.0,
+            "Thunderstorms this afternoon.",
+            Some("Severe Thunderstorm Warning"),
+        ),
+    };
+    Ok(Weather {
+        temperature,
+        condition_id,
+        sentence,
+        image_ascii,
+        cloud_cover,
+        wind,
+        forecast_sentence,
+        alert,
+    })
+}
+
+/// A sample forecast for San Francisco starting now, `metric` decides the units
+pub fn forecast(name: &str, metric: bool) -> crate::Result<WeatherForecast> {
+    Ok(serde_json::from_value(sample(name, metric)?)?)
+}
+
+/// A sample with every list and optional field filled, layouts are checked against it
+pub fn full(metric: bool) -> crate::Result<WeatherForecast> {
+    let mut sample = sample("storm", metric)?;
+    sample["raw_data"] = json!(["{}"]);
+    sample["utc_offset"] = json!(0);
+    Ok(serde_json::from_value(sample)?)
+}
+
+fn sample(name: &str, metric: bool) -> crate::Result<Value> {
+    let weather = weather(name)?;
+    let missing = name == "missing";
+    let temperature = |celsius: f64| {
+        if metric {
+            celsius.round()
+        } else {
+            (celsius * 9.0 / 5.0 + 32.0).round()
+        }
+    };
+    let speed = |kmh: f64| if metric { kmh } else { (kmh / 1.609).round() };
+    let conditions = if missing {
+        json!([])
+    } else {
+        json!([{
+            "condition_id": weather.condition_id,
+            "image_url": "https://openweathermap.org/img/wn/01d@4x.png",
+            "sentence": weather.sentence,
+            "image_ascii": weather.image_ascii,
+        }])
+    };
+    let now = local::now() as i64;
+    let weather_data = |hour: i64| {
+        let t = weather.temperature + 3.0 * (hour as f64 / 24.0 * std::f64::consts::TAU).sin();
+        json!({
+            "time": now + hour * HOUR,
+            "temperature": temperature(t),
+            "min_temp": temperature(t - 3.0),
+            "max_temp": temperature(t + 3.0),
+            "wind": {"speed": speed(weather.wind + (hour % 5) as f64), "heading": (hour * 15) % 360},
+            "raw_data": "",
+            "dewpoint": temperature(t - 5.0),
+            "feels_like": temperature(t - 1.0),
+            "aqi": if missing { 0 } else { 1 + hour % 5 },
+            "cloud_cover": (weather.cloud_cover + hour % 5 - 2).clamp(0, 100),
+            "precipitation": if weather.condition_id < 700 { (hour % 4) as f64 * 0.8 } else { 0.0 },
+            "conditions": conditions,
+            "condition_sentence": if missing { String::new() } else { format!("{}.", weather.sentence) },
+        })
+    };
+    let daily = (0..if missing { 0 } else { 7 })
+        .map(|day: i64| {
+            json!({
+                "time": now + day * 24 * HOUR,
+                "min_temp": temperature(weather.temperature - 5.0 + (day % 3) as f64),
+                "max_temp": temperature(weather.temperature + 3.0 + (day % 4) as f64),
+                "wind": {"speed": speed(weather.wind), "heading": 270},
+                "cloud_cover": weather.cloud_cover,
+                "precipitation_probability": if weather.condition_id < 800 { 80 } else { 0 },
+                "conditions": conditions,
+                "condition_sentence": format!("{}.", weather.sentence),
+            })
+        })
+        .collect::<Vec<Value>>();
+    let alerts = weather
+        .alert
+        .iter()
+        .map(|event| {
+            json!({
+                "sender": "NWS San Francisco CA",
+                "event": event,
+                "start": now,
+                "end": now + 6 * HOUR,
+                "description": format!("{event} in effect until this evening."),
+                "tags": [weather.sentence],
+            })
+        })
+        .collect::<Vec<Value>>();
+    Ok(json!({
+        "region": if missing { "" } else { "San Francisco" },
+        "country": if missing { "" } else { "United States" },
+        "forecast": (0..if missing { 1 } else { 24 }).map(weather_data).collect::<Vec<Value>>(),
+        "current_weather": weather_data(0),
+        "forecast_sentence": if missing { "" } else { weather.forecast_sentence },
+        "daily": daily,
+        "alerts": alerts,
+        "raw_data": null,
+        "utc_offset": null,
+    }))
+}
@@ -0,0 +1,517 @@
+//! Loads layouts from their JSON or TOML source
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{self, DeserializeOwned, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::layout::layout_serde::{
+    BoxSerde, ColumnSerde, ColumnsSerde, ItemSerde, LayoutDefaultsSerde, LayoutSerde, RepeatSerde,
+    RowSerde,
+};
+use local::weather_file::WeatherFile;
+use weather_error::{Error, LayoutErr};
+
+/// An item in a layout source, either a full item or the shorthand notation
+enum ItemSourceSerde {
+    Shorthand(String),
+    Item(Box<ItemSerde>),
+}
+
+/// Picks the kind of item by whether it is a string or an object, unlike an untagged enum this
+/// keeps the location of errors inside the item
+impl<'de> Deserialize<'de> for ItemSourceSerde {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ItemVisitor;
+
+        impl<'de> Visitor<'de> for ItemVisitor {
+            type Value = ItemSourceSerde;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an item, either a string or an object with a type and a value")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                Ok(ItemSourceSerde::Shorthand(s.to_string()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let item = ItemSerde::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(ItemSourceSerde::Item(Box::new(item)))
+            }
+        }
+
+        deserializer.deserialize_any(ItemVisitor)
+    }
+}
+
+/// `{"repeat": "forecast", "start": 1, "end": 13, "as": "hour", "rows": [...]}`
+struct RepeatSourceSerde {
+    repeat: String,
+    name: String,
+    index: String,
+    start: usize,
+    end: Option<usize>,
+    step: usize,
+    filter: Option<String>,
+    rows: Vec<RowSourceSerde>,
+}
+
+/// A column, `items` is a shorter way to write a column with a single row
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColumnSourceSerde {
+    width: Option<usize>,
+    flex: Option<usize>,
+    #[serde(default = "default_align")]
+    align: String,
+    when: Option<String>,
+    #[serde(default)]
+    items: Vec<ItemSourceSerde>,
+    #[serde(default)]
+    rows: Vec<RowSourceSerde>,
+}
+
+/// `{"columns": [{"width": 10, "items": [...]}, {"flex": 1, "rows": [...]}]}`
+struct ColumnsSourceSerde {
+    columns: Vec<ColumnSourceSerde>,
+    separator: String,
+}
+
+/// `{"box": "Title", "border": "rounded", "rows": [...]}`
+struct BoxSourceSerde {
+    title: String,
+    border: String,
+    color: Option<String>,
+    width: Option<usize>,
+    fill: bool,
+    align: String,
+    padding: usize,
+    rows: Vec<RowSourceSerde>,
+}
+
+fn default_align() -> String {
+    "left".to_string()
+}
+
+enum RowSourceSerde {
+    Items(Vec<ItemSourceSerde>),
+    Repeat(RepeatSourceSerde),
+    Columns(ColumnsSourceSerde),
+    Box(BoxSourceSerde),
+}
+
+/// Every key a row object can have, `repeat`, `columns` or `box` decides which kind of row it is
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RowObjectSerde {
+    repeat: Option<String>,
+    #[serde(rename = "as")]
+    name: Option<String>,
+    index: Option<String>,
+    start: Option<usize>,
+    end: Option<usize>,
+    step: Option<usize>,
+    filter: Option<String>,
+    columns: Option<Vec<ColumnSourceSerde>>,
+    separator: Option<String>,
+    #[serde(rename = "box")]
+    title: Option<String>,
+    border: Option<String>,
+    color: Option<String>,
+    width: Option<usize>,
+    fill: Option<bool>,
+    align: Option<String>,
+    padding: Option<usize>,
+    rows: Option<Vec<RowSourceSerde>>,
+}
+
+impl RowObjectSerde {
+    /// Keys that are set, by name
+    fn keys(&self) -> [(&'static str, bool); 17] {
+        [
+            ("repeat", self.repeat.is_some()),
+            ("as", self.name.is_some()),
+            ("index", self.index.is_some()),
+            ("start", self.start.is_some()),
+            ("end", self.end.is_some()),
+            ("step", self.step.is_some()),
+            ("filter", self.filter.is_some()),
+            ("columns", self.columns.is_some()),
+            ("separator", self.separator.is_some()),
+            ("box", self.title.is_some()),
+            ("border", self.border.is_some()),
+            ("color", self.color.is_some()),
+            ("width", self.width.is_some()),
+            ("fill", self.fill.is_some()),
+            ("align", self.align.is_some()),
+            ("padding", self.padding.is_some()),
+            ("rows", self.rows.is_some()),
+        ]
+    }
+
+    fn into_row(self) -> Result<RowSourceSerde, String> {
+        let (kind, allowed): (&str, &[&str]) = match (&self.repeat, &self.columns, &self.title) {
+            (Some(_), None, None) => (
+                "repeat",
+                &[
+                    "repeat", "as", "index", "start", "end", "step", "filter", "rows",
+                ],
+            ),
+            (None, Some(_), None) => ("columns", &["columns", "separator"]),
+            (None, None, Some(_)) => (
+                "box",
+                &[
+                    "box", "border", "color", "width", "fill", "align", "padding", "rows",
+                ],
+            ),
+            _ => return Err("A row needs exactly one of repeat, columns or box".to_string()),
+        };
+        if let Some((key, _)) = self
+            .keys()
+            .iter()
+            .find(|(key, set)| *set && !allowed.contains(key))
+        {
+            return Err(format!("{key} can't be used in a {kind} row"));
+        }
+        Ok(match kind {
+            "repeat" => RowSourceSerde::Repeat(RepeatSourceSerde {
+                repeat: self.repeat.unwrap_or_default(),
+                name: self.name.unwrap_or_else(|| "item".to_string()),
+                index: self.index.unwrap_or_else(|| "index".to_string()),
+                start: self.start.unwrap_or(0),
+                end: self.end,
+                step: self.step.unwrap_or(1),
+                filter: self.filter,
+                rows: self.rows.ok_or(format!("A {kind} row needs rows"))?,
+            }),
+            "columns" => RowSourceSerde::Columns(ColumnsSourceSerde {
+                columns: self.columns.unwrap_or_default(),
+                separator: self.separator.unwrap_or_else(|| " ".to_string()),
+            }),
+            _ => RowSourceSerde::Box(BoxSourceSerde {
+                title: self.title.unwrap_or_default(),
+                border: self.border.unwrap_or_else(|| "rounded".to_string()),
+                color: self.color,
+                width: self.width,
+                fill: self.fill.unwrap_or(false),
+                align: self.align.unwrap_or_else(default_align),
+                padding: self.padding.unwrap_or(1),
+                rows: self.rows.ok_or(format!("A {kind} row needs rows"))?,
+            }),
+        })
+    }
+}
+
+/// Picks the kind of row by whether it is a list of items or an object and by its keys, unlike an
+/// untagged enum this keeps the location of errors inside the row
+impl<'de> Deserialize<'de> for RowSourceSerde {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RowVisitor;
+
+        impl<'de> Visitor<'de> for RowVisitor {
+            type Value = RowSourceSerde;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a row, either a list of items or a repeat, columns or box object")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                let items = Vec::deserialize(SeqAccessDeserializer::new(seq))?;
+                Ok(RowSourceSerde::Items(items))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                RowObjectSerde::deserialize(MapAccessDeserializer::new(map))?
+                    .into_row()
+                    .map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(RowVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayoutSourceSerde {
+    version: u64,
+    #[serde(default)]
+    defaults: LayoutDefaultsSerde,
+    layout: Vec<RowSourceSerde>,
+}
+
+/// A compiled layout, cached until the source changes
+#[derive(Serialize, Deserialize)]
+struct LayoutCache {
+    hash: String,
+    layout: LayoutSerde,
+}
+
+fn new_item(item_type: &str, value: &str, color: Option<String>) -> ItemSerde {
+    ItemSerde {
+        item_type: item_type.to_string(),
+        color,
+        bg_color: None,
+        metric: None,
+        imperial: None,
+        unit_color: None,
+        value: value.to_string(),
+        args: None,
+        kwargs: None,
+        scale: None,
+    }
+}
+
+/// Parses the shorthand notation, `$color$` may prefix any item: `@variable|imperial unit|metric unit`,
+/// `#function|arg|key=arg`, `=expression` or text (`\` escapes `@`, `#` and `=`)
+pub fn parse_shorthand(s: &str) -> ItemSerde {
+    let mut rest = s;
+    let mut color = None;
+    if let Some((item_color, after)) = rest.strip_prefix('$').and_then(|r| r.split_once('$')) {
+        color = Some(item_color.to_string());
+        rest = after;
+    }
+    if let Some(expression) = rest.strip_prefix('=') {
+        return new_item("expression", expression, color);
+    }
+    if let Some(variable) = rest.strip_prefix('@') {
+        let split: Vec<&str> = variable.split('|').collect();
+        let (imperial, metric) = match split.len() {
+            2 => (Some(split[1]), Some(split[1])),
+            3 => (Some(split[1]), Some(split[2])),
+            _ => (None, None),
+        };
+        let mut item = new_item("variable", split[0], color);
+        item.imperial = imperial.map(str::to_string);
+        item.metric = metric.map(str::to_string);
+        return item;
+    }
+    if let Some(function) = rest.strip_prefix('#') {
+        let mut split = function.split('|');
+        let mut item = new_item("function", split.next().unwrap_or_default(), color);
+        let mut args = Vec::new();
+        let mut kwargs = HashMap::new();
+        for arg in split {
+            match arg.split_once('=') {
+                Some((key, value)) => {
+                    kwargs.insert(key.to_string(), parse_shorthand(value));
+                }
+                None => args.push(parse_shorthand(arg)),
+            }
+        }
+        item.args = Some(args);
+        item.kwargs = Some(kwargs);
+        return item;
+    }
+    new_item("text", rest.strip_prefix('\\').unwrap_or(rest), color)
+}
+
+/// Converts a byte offset into a 1 based line and column
+fn line_column(text: &str, offset: usize) -> (u64, u64) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line as u64, column as u64)
+}
+
+fn located_error(message: String, line: u64, column: u64) -> Error {
+    Error::LayoutError(LayoutErr {
+        message,
+        row: None,
+        item: None,
+        line: Some(line),
+        column: Some(column),
+    })
+}
+
+/// Parses JSON or TOML, errors have the line and column they were found at
+pub(crate) fn parse_source<T: DeserializeOwned>(text: &str, format: &str) -> crate::Result<T> {
+    match format {
+        "json" => serde_json::from_str(text).map_err(|e| {
+            // serde_json's message ends with the location, which is reported separately
+            let message = e.to_string();
+            let message = message
+                .rsplit_once(" at line ")
+                .map_or(&*message, |(message, _)| message);
+            located_error(message.to_string(), e.line() as u64, e.column() as u64)
+        }),
+        "toml" => toml::from_str(text).map_err(|e| {
+            let (line, column) = line_column(text, e.span().map_or(0, |span| span.start));
+            located_error(e.message().to_string(), line, column)
+        }),
+        _ => Err(format!("Files can't be written in {format}"))?,
+    }
+}
+
+fn compile_items(items: Vec<ItemSourceSerde>) -> Vec<ItemSerde> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            ItemSourceSerde::Shorthand(s) => parse_shorthand(&s),
+            ItemSourceSerde::Item(item) => *item,
+        })
+        .collect()
+}
+
+fn compile_column(column: ColumnSourceSerde) -> ColumnSerde {
+    let mut rows: Vec<RowSerde> = column.rows.into_iter().map(compile_row).collect();
+    if !column.items.is_empty() {
+        rows.insert(0, RowSerde::Items(compile_items(column.items)));
+    }
+    ColumnSerde {
+        width: column.width,
+        flex: column.flex,
+        align: column.align,
+        when: column.when,
+        rows,
+    }
+}
+
+fn compile_row(row: RowSourceSerde) -> RowSerde {
+    match row {
+        RowSourceSerde::Items(items) => RowSerde::Items(compile_items(items)),
+        RowSourceSerde::Repeat(repeat) => RowSerde::Repeat(RepeatSerde {
+            over: repeat.repeat,
+            name: repeat.name,
+            index: repeat.index,
+            start: repeat.start,
+            end: repeat.end,
+            step: repeat.step,
+            filter: repeat.filter,
+            rows: repeat.rows.into_iter().map(compile_row).collect(),
+        }),
+        RowSourceSerde::Columns(columns) => RowSerde::Columns(ColumnsSerde {
+            columns: columns.columns.into_iter().map(compile_column).collect(),
+            separator: columns.separator,
+        }),
+        RowSourceSerde::Box(panel) => RowSerde::Box(BoxSerde {
+            title: panel.title,
+            border: panel.border,
+            color: panel.color,
+            width: panel.width,
+            fill: panel.fill,
+            align: panel.align,
+            padding: panel.padding,
+            rows: panel.rows.into_iter().map(compile_row).collect(),
+        }),
+    }
+}
+
+/// Compiles the source of a layout, `format` is either "json" or "toml"
+pub fn compile(text: &str, format: &str) -> crate::Result<LayoutSerde> {
+    let source: LayoutSourceSerde = parse_source(text, format)?;
+    Ok(LayoutSerde {
+        version: source.version,
+        defaults: source.defaults,
+        layout: source.layout.into_iter().map(compile_row).collect(),
+    })
+}
+
+/// Compiles the source, reusing the compiled layout in `cache/layouts` if the source is unchanged
+pub fn compile_cached(name: &str, data: &[u8], format: &str) -> crate::Result<LayoutSerde> {
+    let text = std::str::from_utf8(data).map_err(|_| "Layout file is not valid UTF-8")?;
+    // The compiled format changes between versions
+    let hash = format!("{}-{}", crate::layout::VERSION, local::hash(data));
+    let mut cache_file = WeatherFile::new(format!("cache/layouts/{name}.res"))?;
+    if let Ok(cache) = bincode::deserialize::<LayoutCache>(&cache_file.data) {
+        if cache.hash == hash {
+            return Ok(cache.layout);
+        }
+    }
+    let layout = compile(text, format)?;
+    cache_file.data = bincode::serialize(&LayoutCache {
+        hash,
+        layout: layout.clone(),
+    })?;
+    cache_file.write()?;
+    Ok(layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line, column and message of the error compiling `text`
+    fn error(text: &str, format: &str) -> (u64, u64, String) {
+        match compile(text, format) {
+            Err(Error::LayoutError(e)) => (e.line.unwrap(), e.column.unwrap(), e.message),
+            Err(e) => panic!("unexpected error {e}"),
+            Ok(_) => panic!("{text} compiled"),
+        }
+    }
+
+    #[test]
+    fn rows() {
+        let layout = compile(
+            r#"{"version": 21, "layout": [
+                ["text", {"type": "variable", "value": "region"}],
+                {"rows": [["@item.temperature"]], "repeat": "forecast", "step": 2},
+                {"columns": [{"width": 5, "items": ["a"]}], "separator": "|"},
+                {"box": "Now", "rows": [["b"]]}
+            ]}"#,
+            "json",
+        )
+        .unwrap();
+        assert!(matches!(&layout.layout[0], RowSerde::Items(items) if items.len() == 2));
+        assert!(
+            matches!(&layout.layout[1], RowSerde::Repeat(r) if r.step == 2 && r.name == "item")
+        );
+        assert!(matches!(&layout.layout[2], RowSerde::Columns(c) if c.separator == "|"));
+        assert!(matches!(&layout.layout[3], RowSerde::Box(b) if b.border == "rounded"));
+    }
+
+    #[test]
+    fn errors_inside_rows() {
+        let (line, column, message) = error(
+            "{\"version\": 21, \"layout\": [\n  {\"repeat\": \"forecast\", \"stpe\": 2, \"rows\": []}\n]}",
+            "json",
+        );
+        assert_eq!((line, column), (2, 31));
+        assert!(message.starts_with("unknown field `stpe`"), "{message}");
+        let (line, column, message) = error(
+            "{\"version\": 21, \"layout\": [\n  {\"box\": \"Now\", \"rows\": [\n    [{\"type\": \"text\", \"value\": 1}]\n  ]}\n]}",
+            "json",
+        );
+        assert_eq!((line, column), (3, 32));
+        assert!(
+            message.starts_with("invalid type: integer `1`"),
+            "{message}"
+        );
+        let (line, column, _) = error(
+            "{\"version\": 21, \"layout\": [\n  {\"columns\": [{\"widht\": 3}]}\n]}",
+            "json",
+        );
+        assert_eq!((line, column), (2, 23));
+    }
+
+    #[test]
+    fn errors_between_kinds() {
+        let (line, _, message) = error(
+            "{\"version\": 21, \"layout\": [\n  {\"repeat\": \"forecast\", \"border\": \"single\", \"rows\": []}\n]}",
+            "json",
+        );
+        assert_eq!(line, 2);
+        assert_eq!(message, "border can't be used in a repeat row");
+        let (_, _, message) = error(r#"{"version": 21, "layout": [{"rows": []}]}"#, "json");
+        assert_eq!(message, "A row needs exactly one of repeat, columns or box");
+    }
+
+    #[test]
+    fn toml_errors() {
+        let (line, column, message) = error(
+            "version = 21\n\n[[layout]]\nrepeat = \"forecast\"\nrepaet = 1\nrows = []\n",
+            "toml",
+        );
+        assert_eq!((line, column), (5, 1));
+        assert!(message.contains("repaet"), "{message}");
+    }
+}
@@ -0,0 +1,160 @@
+//! Themes map roles like `variable` or `warning` to colors, so layouts don't have to name colors
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use terminal::color;
+use weather_dirs::themes_dir;
+
+use crate::layout::source::parse_source;
+
+/// Extensions of theme files
+pub const EXTENSIONS: [&str; 2] = ["json", "toml"];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Colors by role, like `variable` or `warning`
+    pub colors: HashMap<String, String>,
+    /// Colors of the air quality index, from good to bad
+    pub aqi: Vec<String>,
+    /// Gradient stops of `color_scale`, from cold to hot
+    pub temperature: Vec<String>,
+    /// Colors by condition group: thunderstorm, drizzle, rain, snow, atmosphere, clear or clouds
+    pub conditions: HashMap<String, String>,
+}
+
+fn to_map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            colors: to_map(&[
+                ("variable", "FORE_LIGHTGREEN"),
+                ("text", "FORE_LIGHTBLUE"),
+                ("unit", "FORE_MAGENTA"),
+                ("variable_bg", "BACK_RESET"),
+                ("text_bg", "BACK_RESET"),
+                ("unit_bg", "BACK_RESET"),
+                ("warning", "FORE_YELLOW"),
+                ("error", "FORE_RED"),
+            ]),
+            aqi: [
+                "FORE_GREEN",
+                "FORE_GREEN",
+                "FORE_LIGHTYELLOW",
+                "FORE_LIGHTYELLOW",
+                "FORE_RED",
+            ]
+            .map(String::from)
+            .to_vec(),
+            temperature: ["blue", "green", "yellow", "red"]
+                .map(String::from)
+                .to_vec(),
+            conditions: to_map(&[
+                ("thunderstorm", "FORE_MAGENTA"),
+                ("drizzle", "FORE_LIGHTCYAN"),
+                ("rain", "FORE_BLUE"),
+                ("snow", "FORE_WHITE"),
+                ("atmosphere", "FORE_LIGHTBLACK"),
+                ("clear", "FORE_YELLOW"),
+                ("clouds", "FORE_LIGHTBLACK"),
+            ]),
+        }
+    }
+}
+
+static CURRENT: RwLock<Option<Theme>> = RwLock::new(None);
+
+impl Theme {
+    /// Loads a theme from the themes directory, "default" is the built in theme
+    pub fn new(name: &str) -> crate::Result<Self> {
+        if name == "default" {
+            return Ok(Self::default());
+        }
+        Self::from_path(themes_dir()?.join(name))
+    }
+
+    /// Loads a JSON or TOML theme, roles it doesn't set keep their default colors
+    pub fn from_path<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(format!("Theme file {} not found", path.display()))?;
+        }
+        let ext = path.extension().unwrap_or_default().to_string_lossy();
+        if !EXTENSIONS.contains(&&*ext) {
+            return Err("Theme file does not have an extension of .json or .toml")?;
+        }
+        let mut theme: Self = parse_source(&fs::read_to_string(path)?, &ext)?;
+        let default = Self::default();
+        for (role, color) in default.colors {
+            theme.colors.entry(role).or_insert(color);
+        }
+        for (group, color) in default.conditions {
+            theme.conditions.entry(group).or_insert(color);
+        }
+        if theme.aqi.is_empty() {
+            theme.aqi = default.aqi;
+        }
+        if theme.temperature.is_empty() {
+            theme.temperature = default.temperature;
+        }
+        Ok(theme)
+    }
+
+    /// The escape sequence of a role or of a color like `FORE_RED` or `255,0,0`
+    pub fn color(&self, name: &str) -> Option<String> {
+        let name = self.colors.get(name).map_or(name, String::as_str);
+        color::from_string(name.to_string())
+    }
+
+    /// The escape sequence for an air quality index from 1 (good) to 5 (bad)
+    pub fn aqi_color(&self, aqi: u8) -> String {
+        let index = usize::from(aqi.max(1) - 1).min(self.aqi.len().saturating_sub(1));
+        self.aqi
+            .get(index)
+            .and_then(|name| self.color(name))
+            .unwrap_or_default()
+    }
+
+    /// The escape sequence for the group of an OpenWeatherMap condition id
+    pub fn condition_color(&self, condition_id: u16) -> String {
+        let group = match condition_id {
+            200..=299 => "thunderstorm",
+            300..=399 => "drizzle",
+            500..=599 => "rain",
+            600..=699 => "snow",
+            700..=799 => "atmosphere",
+            800 => "clear",
+            _ => "clouds",
+        };
+        self.conditions
+            .get(group)
+            .and_then(|name| self.color(name))
+            .unwrap_or_default()
+    }
+}
+
+/// Sets the theme layouts are rendered with
+pub fn set_current(theme: Theme) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = Some(theme);
+    }
+}
+
+/// Calls `f` with the theme layouts are rendered with, the default theme if none was set
+pub fn with_current<T>(f: impl FnOnce(&Theme) -> T) -> T {
+    let current = CURRENT.read();
+    match current.as_ref().ok().and_then(|current| current.as_ref()) {
+        Some(theme) => f(theme),
+        None => f(&Theme::default()),
+    }
+}
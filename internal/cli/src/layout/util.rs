@@ -1,34 +1,143 @@
+use local::weather_file::WeatherFile;
 use regex::Regex;
-use std::fs;
-use std::io::Write;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use terminal::color;
+use terminal::graphics::{self, Graphics};
+
+use crate::layout::expression::Value;
+use crate::layout::{layout_error, theme};
 
 pub fn color_aqi(aqi: u8) -> crate::Result<String> {
-    Ok(match aqi {
-        5 => color::FORE_RED.to_string() + &aqi.to_string(),
-        3 | 4 => color::FORE_LIGHTYELLOW.to_string() + &aqi.to_string(),
-        _ => color::FORE_GREEN.to_string() + &aqi.to_string(),
-    })
+    Ok(theme::with_current(|theme| theme.aqi_color(aqi)) + &aqi.to_string())
 }
 
+/// Copies of the resources compiled into weather, used until `weather setup` downloads them
+const BUILTIN_WEATHER_CODES: &[u8] = include_bytes!("../../../../docs_templates/weather_codes.res");
+const BUILTIN_ASCII_ART: &[u8] =
+    include_bytes!("../../../../docs_templates/weather_ascii_images.res");
+
 fn url_validator(u: &str) -> bool {
     let r = Regex::new(r"https?://(www\d?\.)?\w+\.\w+").expect("Regex failed (bug)");
     r.is_match(u)
 }
 
-pub fn image(source: String, scale: f64) -> crate::Result<String> {
+/// Downloads an image, or reads it from `resources/images` if it was downloaded before
+fn cached_image(url: &str) -> crate::Result<Vec<u8>> {
+    let mut file = WeatherFile::new(format!("resources/images/{}", local::hash(url.as_bytes())))?;
+    if file.data.is_empty() {
+        let resp = networking::get_url(url, None, None, None)?;
+        if resp.status != 200 {
+            return Err(layout_error(format!(
+                "Failed to download the image {url}, status {}",
+                resp.status
+            )));
+        }
+        file.data = resp.bytes;
+        file.write()?;
+    }
+    Ok(file.data)
+}
+
+/// Renders an image from a URL, `protocol` is `kitty`, `iterm2`, `sixel`, `blocks` or `auto` to
+/// use the best one the terminal supports
+pub fn image(source: String, scale: f64, protocol: &str) -> crate::Result<String> {
+    let graphics = match protocol {
+        "auto" => graphics::detect(),
+        _ => Graphics::from_string(protocol).ok_or_else(|| {
+            layout_error(format!(
+                "Unknown image protocol {protocol}, use auto, kitty, iterm2, sixel or blocks"
+            ))
+        })?,
+    };
     let is_url = url_validator(&source);
     if is_url {
-        let response = networking::get_url(&source, None, None, None)?;
-        let mut f = fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open("temp.img")?;
-        f.write_all(&response.bytes)?;
-        return crate::layout::image_to_text::ascii_image("temp.img", scale);
+        return crate::layout::image_to_text::to_text(&cached_image(&source)?, scale, graphics);
     }
     Err("source is not a url".to_string())? // TODO: Fix
 }
 
-// TODO: Implement to_ascii and rainbow
+/// Colors every visible character with the next hue of a rainbow
+pub fn rainbow(text: &str) -> String {
+    let mut s = String::new();
+    let mut hue: f64 = 0.0;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            s.push(c);
+            continue;
+        }
+        // HSV to RGB with full saturation and value
+        let x = 1.0 - ((hue / 60.0) % 2.0 - 1.0).abs();
+        let (r, g, b) = match (hue / 60.0) as u8 {
+            0 => (1.0, x, 0.0),
+            1 => (x, 1.0, 0.0),
+            2 => (0.0, 1.0, x),
+            3 => (0.0, x, 1.0),
+            4 => (x, 0.0, 1.0),
+            _ => (1.0, 0.0, x),
+        };
+        s += &color::rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8);
+        s.push(c);
+        hue = (hue + 30.0) % 360.0;
+    }
+    s + color::FORE_RESET
+}
+
+/// Reads a downloaded resource, or the copy compiled into weather if it isn't downloaded yet
+fn resource<T: DeserializeOwned>(file: WeatherFile, builtin: &[u8]) -> crate::Result<T> {
+    match bincode::deserialize(&file.data) {
+        Ok(data) => Ok(data),
+        Err(_) => Ok(bincode::deserialize(builtin)?),
+    }
+}
+
+/// Name of the ASCII art of a condition id in `weather_codes.res`
+fn ascii_art_name(condition_id: f64) -> crate::Result<String> {
+    let weather_codes: HashMap<String, Vec<String>> =
+        resource(WeatherFile::weather_codes()?, BUILTIN_WEATHER_CODES)?;
+    Ok(weather_codes
+        .get(&(condition_id as u16).to_string())
+        .and_then(|code| code.get(4))
+        .map_or_else(|| "Unknown".to_string(), String::clone))
+}
+
+/// ASCII art of a condition, either a condition object or a condition id, from the art in
+/// `weather_ascii_images.res`, `night` uses the night variant of the art if there is one
+pub fn to_ascii(condition: &Value, night: bool) -> crate::Result<String> {
+    let art: HashMap<String, Vec<String>> =
+        resource(WeatherFile::weather_ascii_art()?, BUILTIN_ASCII_ART)?;
+    let name = match condition {
+        Value::Json(json) => match json.get("image_ascii").and_then(|name| name.as_str()) {
+            Some(name) if art.contains_key(name) => name.to_string(),
+            _ => ascii_art_name(
+                json.get("condition_id")
+                    .and_then(|id| id.as_f64())
+                    .ok_or_else(|| layout_error("to_ascii needs a condition or a condition id"))?,
+            )?,
+        },
+        value => ascii_art_name(value.as_number()?)?,
+    };
+    let lines = night
+        .then(|| art.get(&format!("{name}Night")))
+        .flatten()
+        .or_else(|| art.get(&name))
+        .or_else(|| art.get("Unknown"))
+        .ok_or_else(|| layout_error(format!("No ASCII art for {name}")))?;
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_resources() {
+        let codes: HashMap<String, Vec<String>> =
+            bincode::deserialize(BUILTIN_WEATHER_CODES).unwrap();
+        let art: HashMap<String, Vec<String>> = bincode::deserialize(BUILTIN_ASCII_ART).unwrap();
+        assert!(art.contains_key("Unknown"));
+        for name in codes.values().filter_map(|code| code.get(4)) {
+            assert!(art.contains_key(name), "No ASCII art for {name}");
+        }
+    }
+}
@@ -1,17 +1,23 @@
+use crate::layout::theme::{self, Theme};
 use crate::layout::LayoutFile;
+use crate::output::Output;
 use backend::WeatherForecast;
+use terminal::color;
 use weather_error;
 use weather_error::LayoutErr;
 
 pub mod layout;
 pub mod arguments;
 pub mod commands;
+pub mod graph;
+pub mod output;
 
 pub type Result<T> = std::result::Result<T, weather_error::Error>;
 
 #[derive(Clone, Eq, PartialEq)]
 pub enum Datasource {
     Meteo,
+    MetNo,
     Openweathermap,
     OpenweathermapOneCall,
     NWS,
@@ -24,32 +30,58 @@ pub fn datasource_from_str(s: &str) -> Datasource {
         "openweathermap" => Datasource::Openweathermap,
         "openweathermap_onecall" => Datasource::OpenweathermapOneCall,
         "meteo" => Datasource::Meteo,
+        "metno" => Datasource::MetNo,
         _ => Datasource::Other(s.to_string()),
     }
 }
 
 fn print_out(
     layout_file: String,
+    theme_name: &str,
     data: WeatherForecast,
-    json: bool,
+    output: Output,
     metric: bool,
 ) -> crate::Result<()> {
-    if json {
-        println!("{:#?}", data.raw_data.expect("No raw data to print"));
+    if output == Output::Raw {
+        println!("{}", crate::output::raw(&data)?);
+    } else if let Output::Format(format) = output {
+        println!("{}", crate::output::format(&data, format, metric)?);
     } else {
+        match Theme::new(theme_name) {
+            Ok(theme) => theme::set_current(theme),
+            Err(e) => eprintln!(
+                "{}",
+                color::downsample(&format!(
+                    "{}{e}, using the default theme{}",
+                    color::FORE_YELLOW,
+                    color::FORE_RESET
+                ))
+            ),
+        }
         let mut out = LayoutFile::new(layout_file);
-        if out.is_err() {
+        if let Err(e) = &out {
+            eprintln!(
+                "{}",
+                color::downsample(&format!(
+                    "{}{e}, using the default layout{}",
+                    color::FORE_YELLOW,
+                    color::FORE_RESET
+                ))
+            );
             out = LayoutFile::new("default.res".to_string());
         }
-        println!(
-            "{}",
-            out.map_err(|e| weather_error::Error::LayoutError(LayoutErr {
-                message: e.to_string(),
-                row: None,
-                item: None
-            }))?
-            .to_string(data, metric)?
-        );
+        let out = out
+            .map_err(|e| {
+                weather_error::Error::LayoutError(LayoutErr {
+                    message: e.to_string(),
+                    row: None,
+                    item: None,
+                    line: None,
+                    column: None,
+                })
+            })?
+            .to_string(data, metric)?;
+        println!("{}", color::downsample(&out));
     }
     Ok(())
 }
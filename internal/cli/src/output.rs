@@ -0,0 +1,478 @@
+//! Machine readable forecasts, the fields and their names are the same for every backend
+
+use backend::{DailyForecast, WeatherAlert, WeatherCondition, WeatherData, WeatherForecast};
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde::Serialize;
+
+/// Formats forecasts can be printed in instead of a layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    /// A JSON object per line for the current weather, every hour, day and alert
+    Ndjson,
+    Yaml,
+    /// The current weather and every hour, days and alerts are left out
+    Csv,
+    Toml,
+}
+
+/// What is printed for a forecast
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    Layout,
+    Format(Format),
+    /// The responses of the weather provider
+    Raw,
+}
+
+#[derive(Serialize)]
+struct Condition {
+    id: u16,
+    description: String,
+    icon_url: String,
+}
+
+#[derive(Serialize)]
+struct Hour {
+    time: String,
+    temperature: f64,
+    feels_like: f64,
+    dewpoint: f64,
+    min_temperature: f64,
+    max_temperature: f64,
+    wind_speed: f64,
+    wind_direction: u16,
+    cloud_cover: u8,
+    /// In millimeters, whatever the units are
+    precipitation: f64,
+    aqi: u8,
+    conditions: Vec<Condition>,
+    summary: String,
+}
+
+#[derive(Serialize)]
+struct Day {
+    time: String,
+    min_temperature: f64,
+    max_temperature: f64,
+    wind_speed: f64,
+    wind_direction: u16,
+    cloud_cover: u8,
+    precipitation_probability: u8,
+    conditions: Vec<Condition>,
+    summary: String,
+}
+
+#[derive(Serialize)]
+struct Alert {
+    sender: String,
+    event: String,
+    start: String,
+    end: String,
+    description: String,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Forecast {
+    region: String,
+    country: String,
+    /// `metric` or `imperial`
+    units: &'static str,
+    summary: String,
+    current: Hour,
+    hourly: Vec<Hour>,
+    daily: Vec<Day>,
+    alerts: Vec<Alert>,
+}
+
+/// A line of ndjson, `type` is `current`, `hourly`, `daily` or `alert`
+#[derive(Serialize)]
+struct Record<'a, T> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    #[serde(flatten)]
+    data: &'a T,
+}
+
+/// ISO 8601 time in UTC of a time in milliseconds
+fn iso_time(time: i128) -> String {
+    i64::try_from(time)
+        .ok()
+        .and_then(|time| Utc.timestamp_millis_opt(time).single())
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// Widens an `f32` without the noise of its binary representation, 12.1 stays 12.1
+fn number(n: f32) -> f64 {
+    n.to_string().parse().unwrap_or_default()
+}
+
+fn conditions(conditions: &[WeatherCondition]) -> Vec<Condition> {
+    conditions
+        .iter()
+        .map(|condition| Condition {
+            id: condition.condition_id,
+            description: condition.sentence.clone(),
+            icon_url: condition.image_url.clone(),
+        })
+        .collect()
+}
+
+fn hour(data: &WeatherData) -> Hour {
+    Hour {
+        time: iso_time(data.time),
+        temperature: number(data.temperature),
+        feels_like: number(data.feels_like),
+        dewpoint: number(data.dewpoint),
+        min_temperature: number(data.min_temp),
+        max_temperature: number(data.max_temp),
+        wind_speed: data.wind.speed,
+        wind_direction: data.wind.heading,
+        cloud_cover: data.cloud_cover,
+        precipitation: number(data.precipitation),
+        aqi: data.aqi,
+        conditions: conditions(&data.conditions),
+        summary: data.condition_sentence.clone(),
+    }
+}
+
+fn day(data: &DailyForecast) -> Day {
+    Day {
+        time: iso_time(data.time),
+        min_temperature: number(data.min_temp),
+        max_temperature: number(data.max_temp),
+        wind_speed: data.wind.speed,
+        wind_direction: data.wind.heading,
+        cloud_cover: data.cloud_cover,
+        precipitation_probability: data.precipitation_probability,
+        conditions: conditions(&data.conditions),
+        summary: data.condition_sentence.clone(),
+    }
+}
+
+fn alert(data: &WeatherAlert) -> Alert {
+    Alert {
+        sender: data.sender.clone(),
+        event: data.event.clone(),
+        start: iso_time(data.start),
+        end: iso_time(data.end),
+        description: data.description.clone(),
+        tags: data.tags.clone(),
+    }
+}
+
+fn normalize(data: &WeatherForecast, metric: bool) -> Forecast {
+    Forecast {
+        region: data.region.clone(),
+        country: data.country.clone(),
+        units: if metric { "metric" } else { "imperial" },
+        summary: data.forecast_sentence.clone(),
+        current: hour(&data.current_weather),
+        hourly: data.forecast.iter().map(hour).collect(),
+        daily: data.daily.iter().map(day).collect(),
+        alerts: data.alerts.iter().map(alert).collect(),
+    }
+}
+
+/// A past hour or day of `weather history`
+#[derive(Serialize)]
+struct Past<T> {
+    units: &'static str,
+    #[serde(flatten)]
+    data: T,
+}
+
+fn past<T: Serialize>(data: T, metric: bool) -> crate::Result<String> {
+    let units = if metric { "metric" } else { "imperial" };
+    Ok(serde_json::to_string_pretty(&Past { units, data })?)
+}
+
+/// A past hour as JSON with the fields of the hours of a forecast
+pub fn past_hour(data: &WeatherData, metric: bool) -> crate::Result<String> {
+    past(hour(data), metric)
+}
+
+/// A past day as JSON with the fields of the days of a forecast
+pub fn past_day(data: &DailyForecast, metric: bool) -> crate::Result<String> {
+    past(day(data), metric)
+}
+
+fn record<T: Serialize>(kind: &str, data: &T) -> crate::Result<String> {
+    Ok(serde_json::to_string(&Record { kind, data })?)
+}
+
+fn ndjson(forecast: &Forecast) -> crate::Result<String> {
+    let mut lines = vec![record("current", &forecast.current)?];
+    for hour in &forecast.hourly {
+        lines.push(record("hourly", hour)?);
+    }
+    for day in &forecast.daily {
+        lines.push(record("daily", day)?);
+    }
+    for alert in &forecast.alerts {
+        lines.push(record("alert", alert)?);
+    }
+    Ok(lines.join("\n"))
+}
+
+const CSV_COLUMNS: [&str; 15] = [
+    "type",
+    "time",
+    "temperature",
+    "feels_like",
+    "dewpoint",
+    "min_temperature",
+    "max_temperature",
+    "wind_speed",
+    "wind_direction",
+    "cloud_cover",
+    "precipitation",
+    "aqi",
+    "condition_id",
+    "condition",
+    "summary",
+];
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_row(kind: &str, hour: &Hour) -> String {
+    let condition = hour.conditions.first();
+    [
+        kind.to_string(),
+        hour.time.clone(),
+        hour.temperature.to_string(),
+        hour.feels_like.to_string(),
+        hour.dewpoint.to_string(),
+        hour.min_temperature.to_string(),
+        hour.max_temperature.to_string(),
+        hour.wind_speed.to_string(),
+        hour.wind_direction.to_string(),
+        hour.cloud_cover.to_string(),
+        hour.precipitation.to_string(),
+        hour.aqi.to_string(),
+        condition.map_or_else(String::new, |condition| condition.id.to_string()),
+        condition.map_or_else(String::new, |condition| condition.description.clone()),
+        hour.summary.clone(),
+    ]
+    .iter()
+    .map(|field| csv_field(field))
+    .collect::<Vec<String>>()
+    .join(",")
+}
+
+fn csv(forecast: &Forecast) -> String {
+    let mut rows = vec![CSV_COLUMNS.join(","), csv_row("current", &forecast.current)];
+    for hour in &forecast.hourly {
+        rows.push(csv_row("hourly", hour));
+    }
+    rows.join("\n")
+}
+
+/// The forecast in a machine readable format, times are ISO 8601 in UTC
+pub fn format(data: &WeatherForecast, format: Format, metric: bool) -> crate::Result<String> {
+    let forecast = normalize(data, metric);
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(&forecast)?,
+        Format::Ndjson => ndjson(&forecast)?,
+        Format::Yaml => serde_yaml::to_string(&forecast)
+            .map_err(|e| format!("Failed to write YAML, {e}"))?
+            .trim_end()
+            .to_string(),
+        Format::Csv => csv(&forecast),
+        Format::Toml => toml::to_string(&forecast)
+            .map_err(|e| format!("Failed to write TOML, {e}"))?
+            .trim_end()
+            .to_string(),
+    })
+}
+
+/// The responses of the weather provider, or the data of every hour if the backend didn't keep them
+pub fn raw(data: &WeatherForecast) -> crate::Result<String> {
+    if let Some(responses) = &data.raw_data {
+        return Ok(responses.join("\n"));
+    }
+    let hours: Vec<&str> = data
+        .forecast
+        .iter()
+        .map(|hour| hour.raw_data.as_str())
+        .filter(|raw| !raw.is_empty())
+        .collect();
+    if hours.is_empty() {
+        return Err("The backend did not keep the responses of the weather provider".into());
+    }
+    Ok(hours.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::sample;
+    use serde_json::Value;
+
+    /// 2023-05-06 14:30 UTC
+    const TIME: i128 = 1_683_383_400_000;
+    const HOUR: i128 = 3_600_000;
+
+    /// The full sample with fixed times
+    fn forecast() -> WeatherForecast {
+        let mut data = sample::full(true).unwrap();
+        data.current_weather.time = TIME;
+        data.current_weather.condition_sentence = "Thunderstorm, \"heavy\"".to_string();
+        for (i, hour) in data.forecast.iter_mut().enumerate() {
+            hour.time = TIME + i as i128 * HOUR;
+        }
+        for (i, day) in data.daily.iter_mut().enumerate() {
+            day.time = TIME + i as i128 * 24 * HOUR;
+        }
+        for alert in &mut data.alerts {
+            alert.start = TIME;
+            alert.end = TIME + 6 * HOUR;
+        }
+        data
+    }
+
+    fn keys(value: &Value) -> Vec<&str> {
+        value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn json() -> Value {
+        serde_json::from_str(&format(&forecast(), Format::Json, true).unwrap()).unwrap()
+    }
+
+    const HOUR_KEYS: [&str; 13] = [
+        "aqi",
+        "cloud_cover",
+        "conditions",
+        "dewpoint",
+        "feels_like",
+        "max_temperature",
+        "min_temperature",
+        "precipitation",
+        "summary",
+        "temperature",
+        "time",
+        "wind_direction",
+        "wind_speed",
+    ];
+
+    #[test]
+    fn json_fields() {
+        let value = json();
+        let mut top = keys(&value);
+        top.sort_unstable();
+        assert_eq!(
+            top,
+            ["alerts", "country", "current", "daily", "hourly", "region", "summary", "units"]
+        );
+        assert_eq!(value["units"], "metric");
+        assert_eq!(value["region"], "San Francisco");
+        let mut current = keys(&value["current"]);
+        current.sort_unstable();
+        assert_eq!(current, HOUR_KEYS);
+        assert_eq!(value["current"]["time"], "2023-05-06T14:30:00Z");
+        assert_eq!(
+            keys(&value["current"]["conditions"][0]),
+            ["description", "icon_url", "id"]
+        );
+        assert_eq!(value["current"]["conditions"][0]["id"], 211);
+        let hourly = value["hourly"].as_array().unwrap();
+        assert_eq!(hourly.len(), 24);
+        assert_eq!(hourly[1]["time"], "2023-05-06T15:30:00Z");
+        let mut day = keys(&value["daily"][1]);
+        day.sort_unstable();
+        assert_eq!(
+            day,
+            [
+                "cloud_cover",
+                "conditions",
+                "max_temperature",
+                "min_temperature",
+                "precipitation_probability",
+                "summary",
+                "time",
+                "wind_direction",
+                "wind_speed"
+            ]
+        );
+        assert_eq!(value["daily"][1]["time"], "2023-05-07T14:30:00Z");
+        assert_eq!(value["alerts"][0]["event"], "Severe Thunderstorm Warning");
+        assert_eq!(value["alerts"][0]["end"], "2023-05-06T20:30:00Z");
+        let imperial: Value =
+            serde_json::from_str(&format(&forecast(), Format::Json, false).unwrap()).unwrap();
+        assert_eq!(imperial["units"], "imperial");
+    }
+
+    #[test]
+    fn ndjson_records() {
+        let out = format(&forecast(), Format::Ndjson, true).unwrap();
+        let records: Vec<Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 1 + 24 + 7 + 1);
+        let kinds: Vec<&str> = records
+            .iter()
+            .map(|record| record["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds[0], "current");
+        assert!(kinds[1..25].iter().all(|kind| *kind == "hourly"));
+        assert!(kinds[25..32].iter().all(|kind| *kind == "daily"));
+        assert_eq!(kinds[32], "alert");
+        // The fields are next to the type, not nested
+        let mut current = keys(&records[0]);
+        current.sort_unstable();
+        let mut expected = HOUR_KEYS.to_vec();
+        expected.push("type");
+        expected.sort_unstable();
+        assert_eq!(current, expected);
+        assert_eq!(records[0]["time"], "2023-05-06T14:30:00Z");
+        assert_eq!(records[32]["start"], "2023-05-06T14:30:00Z");
+    }
+
+    #[test]
+    fn csv_rows() {
+        let out = format(&forecast(), Format::Csv, true).unwrap();
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows[0], CSV_COLUMNS.join(","));
+        // The current weather and every hour, no days or alerts
+        assert_eq!(rows.len(), 1 + 1 + 24);
+        assert!(rows[1].starts_with("current,2023-05-06T14:30:00Z,"));
+        assert!(rows[1].ends_with(",211,Thunderstorm,\"Thunderstorm, \"\"heavy\"\"\""));
+        assert!(rows[2].starts_with("hourly,2023-05-06T14:30:00Z,"));
+        assert!(rows[3].starts_with("hourly,2023-05-06T15:30:00Z,"));
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn yaml_and_toml() {
+        let yaml = format(&forecast(), Format::Yaml, true).unwrap();
+        let yaml: Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(yaml, json());
+        let toml = format(&forecast(), Format::Toml, true).unwrap();
+        let toml: Value = toml::from_str(&toml).unwrap();
+        assert_eq!(toml, json());
+    }
+
+    #[test]
+    fn iso_times() {
+        assert_eq!(iso_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso_time(TIME + 999), "2023-05-06T14:30:00Z");
+        assert_eq!(iso_time(i128::MAX), "");
+    }
+}
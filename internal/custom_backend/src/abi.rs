@@ -0,0 +1,199 @@
+//! The C ABI shared by the CLI and native plugins.
+//!
+//! Only `#[repr(C)]` types and `extern "C"` functions cross the library boundary,
+//! requests and responses are passed as JSON so plugins built with a different compiler
+//! or different crate versions can't corrupt memory.
+
+use std::collections::HashMap;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{mem, slice};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{PluginRegistrar, WeatherForecastPlugin, CORE_VERSION};
+use backend::WeatherForecast;
+use local::settings::{PluginConfigKey, Settings};
+
+/// Name of the exported `PluginDeclaration` static
+pub const DECLARATION_SYMBOL: &[u8] = b"weather_plugin_declaration\0";
+
+/// A buffer allocated by the plugin, it has to be released with `PluginDeclaration::free`
+#[repr(C)]
+pub struct PluginBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl PluginBuffer {
+    fn new(data: Vec<u8>) -> Self {
+        let mut data = mem::ManuallyDrop::new(data);
+        Self {
+            ptr: data.as_mut_ptr(),
+            len: data.len(),
+            capacity: data.capacity(),
+        }
+    }
+
+    /// Copies the contents out of the buffer
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been returned by a plugin and not freed yet.
+    pub unsafe fn to_vec(&self) -> Vec<u8> {
+        if self.ptr.is_null() {
+            return Vec::new();
+        }
+        slice::from_raw_parts(self.ptr, self.len).to_vec()
+    }
+}
+
+/// The only symbol a native plugin exports, created by `export_plugin!`
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// NUL terminated version of the interface the plugin was built against
+    pub core_version: *const c_char,
+    /// Returns a JSON list of `PluginInfo`
+    pub list: extern "C" fn() -> PluginBuffer,
+    /// Takes a JSON `PluginRequest` and returns a JSON `Result<WeatherForecast, String>`
+    pub call: unsafe extern "C" fn(*const u8, usize) -> PluginBuffer,
+    /// Releases a buffer returned by `list` or `call`
+    pub free: unsafe extern "C" fn(PluginBuffer),
+}
+
+// The declaration only points at static data and functions
+unsafe impl Sync for PluginDeclaration {}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub help: Option<String>,
+    /// Added in 1.1
+    #[serde(default)]
+    pub config: Vec<PluginConfigKey>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PluginRequest {
+    /// Interface version of the caller
+    pub version: String,
+    pub name: String,
+    pub coordinates: [String; 2],
+    pub settings: Settings,
+}
+
+/// Parses `major.minor[.patch]`
+fn parse_version(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+    Some((major, minor))
+}
+
+/// Whether a plugin built against `plugin` can be used by a core at `core`.
+/// Major versions must match and the plugin can't use a newer minor version than the core,
+/// before 1.0 every minor version is breaking.
+pub fn is_compatible(plugin: &str, core: &str) -> bool {
+    match (parse_version(plugin), parse_version(core)) {
+        (Some((plugin_major, plugin_minor)), Some((core_major, core_minor))) => {
+            if plugin_major != core_major {
+                false
+            } else if core_major == 0 {
+                plugin_minor == core_minor
+            } else {
+                plugin_minor <= core_minor
+            }
+        }
+        _ => false,
+    }
+}
+
+struct Registry {
+    functions: HashMap<String, Box<dyn WeatherForecastPlugin>>,
+}
+
+impl PluginRegistrar for Registry {
+    fn register_function(&mut self, name: &str, function: Box<dyn WeatherForecastPlugin>) {
+        self.functions.insert(name.to_string(), function);
+    }
+}
+
+fn registry<F: Fn(&mut dyn PluginRegistrar)>(register: F) -> Registry {
+    let mut registry = Registry {
+        functions: HashMap::new(),
+    };
+    register(&mut registry);
+    registry
+}
+
+fn respond<T: Serialize>(value: &T) -> PluginBuffer {
+    PluginBuffer::new(simd_json::to_vec(value).unwrap_or_default())
+}
+
+/// Plugin side implementation of `PluginDeclaration::list`, used by `export_plugin!`
+pub fn list<F: Fn(&mut dyn PluginRegistrar)>(register: F) -> PluginBuffer {
+    let infos: Vec<PluginInfo> = catch_unwind(AssertUnwindSafe(|| {
+        registry(register)
+            .functions
+            .iter()
+            .map(|(name, function)| PluginInfo {
+                name: name.clone(),
+                help: function.help().map(str::to_string),
+                config: function.config().to_vec(),
+            })
+            .collect()
+    }))
+    .unwrap_or_default();
+    respond(&infos)
+}
+
+fn call_registered<F: Fn(&mut dyn PluginRegistrar)>(
+    register: F,
+    request: &mut [u8],
+) -> std::result::Result<WeatherForecast, String> {
+    let request: PluginRequest =
+        simd_json::from_slice(request).map_err(|e| format!("Invalid request: {e}"))?;
+    if !is_compatible(CORE_VERSION, &request.version) {
+        return Err(format!(
+            "Plugin built for version {CORE_VERSION}, but called by version {}",
+            request.version
+        ));
+    }
+    let registry = registry(register);
+    let function = registry
+        .functions
+        .get(&request.name)
+        .ok_or_else(|| format!("No backend named {}", request.name))?;
+    let coordinates = [&*request.coordinates[0], &*request.coordinates[1]];
+    function
+        .call(coordinates, request.settings)
+        .map_err(|e| e.to_string())
+}
+
+/// Plugin side implementation of `PluginDeclaration::call`, used by `export_plugin!`
+///
+/// # Safety
+///
+/// `ptr` must point to `len` readable bytes.
+pub unsafe fn call<F: Fn(&mut dyn PluginRegistrar)>(
+    register: F,
+    ptr: *const u8,
+    len: usize,
+) -> PluginBuffer {
+    let mut request = slice::from_raw_parts(ptr, len).to_vec();
+    let result = catch_unwind(AssertUnwindSafe(|| call_registered(register, &mut request)))
+        .unwrap_or_else(|_| Err("The plugin panicked".to_string()));
+    respond(&result)
+}
+
+/// Plugin side implementation of `PluginDeclaration::free`, used by `export_plugin!`
+///
+/// # Safety
+///
+/// The buffer must have been created by this plugin and not freed yet.
+pub unsafe extern "C" fn free_buffer(buffer: PluginBuffer) {
+    if !buffer.ptr.is_null() {
+        drop(Vec::from_raw_parts(buffer.ptr, buffer.len, buffer.capacity));
+    }
+}
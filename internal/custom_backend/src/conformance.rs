@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use backend::{WeatherData, WeatherForecast};
+use local::now;
+use local::weather_file::WeatherFile;
+
+/// Locations backends are tested against, in different hemispheres and time zones
+pub const FIXTURE_COORDINATES: [(&str, [&str; 2]); 3] = [
+    ("San Francisco", ["37.7749", "-122.4194"]),
+    ("London", ["51.5074", "-0.1278"]),
+    ("Sydney", ["-33.8688", "151.2093"]),
+];
+
+const DAY: i128 = 86_400_000;
+
+/// Ranges are wide enough for both metric and imperial units
+fn check_data(name: &str, data: &WeatherData, codes: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut check = |ok: bool, msg: String| {
+        if !ok {
+            problems.push(format!("{name}: {msg}"));
+        }
+    };
+    for (field, value) in [
+        ("temperature", data.temperature),
+        ("min_temp", data.min_temp),
+        ("max_temp", data.max_temp),
+        ("dewpoint", data.dewpoint),
+        ("feels_like", data.feels_like),
+    ] {
+        check(
+            value.is_finite() && (-130.0..=180.0).contains(&value),
+            format!("{field} is {value}"),
+        );
+    }
+    check(
+        data.min_temp <= data.max_temp,
+        format!(
+            "min_temp {} is above max_temp {}",
+            data.min_temp, data.max_temp
+        ),
+    );
+    check(
+        data.dewpoint <= data.temperature + 1.0,
+        format!(
+            "dewpoint {} is above the temperature {}",
+            data.dewpoint, data.temperature
+        ),
+    );
+    check(
+        data.wind.speed.is_finite() && (0.0..500.0).contains(&data.wind.speed),
+        format!("wind speed is {}", data.wind.speed),
+    );
+    check(
+        data.wind.heading < 360,
+        format!("wind heading is {}", data.wind.heading),
+    );
+    check(
+        data.cloud_cover <= 100,
+        format!("cloud cover is {}", data.cloud_cover),
+    );
+    check(
+        data.precipitation.is_finite() && data.precipitation >= 0.0,
+        format!("precipitation is {}", data.precipitation),
+    );
+    check(!data.conditions.is_empty(), "no conditions".to_string());
+    for condition in &data.conditions {
+        check(
+            codes.contains_key(&condition.condition_id.to_string()),
+            format!(
+                "condition {} is not in weather_codes.res",
+                condition.condition_id
+            ),
+        );
+    }
+    problems
+}
+
+/// Checks that a forecast returned by a backend is usable, returns a list of problems
+pub fn validate_forecast(forecast: &WeatherForecast) -> crate::Result<Vec<String>> {
+    let weather_file = WeatherFile::weather_codes()?;
+    let codes: HashMap<String, Vec<String>> = bincode::deserialize(&weather_file.data)?;
+    let mut problems = Vec::new();
+    if forecast.forecast.is_empty() {
+        problems.push("forecast is empty".to_string());
+    }
+    let current = &forecast.current_weather;
+    let time = now() as i128;
+    if (current.time - time).abs() > DAY {
+        problems.push(format!(
+            "current_weather: time {} is more than a day from now ({time}), times are in milliseconds",
+            current.time
+        ));
+    }
+    problems.extend(check_data("current_weather", current, &codes));
+    for (i, data) in forecast.forecast.iter().enumerate() {
+        problems.extend(check_data(&format!("forecast[{i}]"), data, &codes));
+    }
+    for (i, pair) in forecast.forecast.windows(2).enumerate() {
+        if pair[1].time <= pair[0].time {
+            problems.push(format!(
+                "forecast[{}]: time {} is not after the previous time {}",
+                i + 1,
+                pair[1].time,
+                pair[0].time
+            ));
+        }
+    }
+    Ok(problems)
+}
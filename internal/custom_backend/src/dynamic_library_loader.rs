@@ -1,30 +1,23 @@
-use std::{collections::HashMap, ffi::OsStr, io, rc::Rc};
+use std::env::consts::DLL_EXTENSION;
+use std::path::Path;
+use std::{collections::HashMap, ffi::CStr, ffi::OsStr, io, rc::Rc};
 
 use libloading::Library;
 use log::{debug, error, trace};
 
+use crate::abi::{is_compatible, PluginBuffer, PluginInfo, PluginRequest, DECLARATION_SYMBOL};
+use crate::manifest::BackendInfo;
 use crate::{PluginDeclaration, WeatherForecastPlugin};
 use backend::WeatherForecast;
-use local::settings::Settings;
+use local::settings::{PluginConfigKey, Settings};
 use weather_error::Error;
 use weather_error::InvocationError;
 
-#[cfg(target_os = "windows")]
+/// Whether the file has the extension of a native library on this platform
 pub fn is_valid_ext(f: &str) -> bool {
-    let len = f.len();
-    &f[len - 4..] == ".dll"
-}
-
-#[cfg(target_os = "linux")]
-pub fn is_valid_ext(f: &str) -> bool {
-    let len = f.len();
-    &f[len - 3..] == ".so"
-}
-
-#[cfg(target_os = "macos")]
-pub fn is_valid_ext(f: &str) -> bool {
-    let len = f.len();
-    &f[len - 6..] == ".dylib"
+    Path::new(f)
+        .extension()
+        .map_or(false, |ext| ext == DLL_EXTENSION)
 }
 
 pub fn load(paths: Vec<String>) -> ExternalBackends {
@@ -58,7 +51,7 @@ pub fn run(
 /// A map of all externally provided functions.
 #[derive(Default)]
 pub struct ExternalBackends {
-    functions: HashMap<String, BackendWrapper>,
+    functions: HashMap<String, Box<dyn WeatherForecastPlugin>>,
     libraries: Vec<Rc<Library>>,
 }
 
@@ -67,6 +60,26 @@ impl ExternalBackends {
         Self::default()
     }
 
+    /// Adds a backend that was loaded by other means, replacing any backend with the same name
+    pub fn register(&mut self, name: &str, backend: Box<dyn WeatherForecastPlugin>) {
+        self.functions.insert(name.to_string(), backend);
+    }
+
+    /// Names and help texts of all backends, sorted by name
+    pub fn list(&self) -> Vec<BackendInfo> {
+        let mut backends: Vec<BackendInfo> = self
+            .functions
+            .iter()
+            .map(|(name, backend)| BackendInfo {
+                name: name.clone(),
+                help: backend.help().map(str::to_string),
+                config: backend.config().to_vec(),
+            })
+            .collect();
+        backends.sort_by(|a, b| a.name.cmp(&b.name));
+        backends
+    }
+
     pub fn call(
         &self,
         name: &str,
@@ -74,10 +87,12 @@ impl ExternalBackends {
         settings: Settings,
     ) -> crate::Result<WeatherForecast> {
         debug!("Calling function {name}");
-        self.functions
+        let backend = self
+            .functions
             .get(name)
-            .ok_or(Error::InvocationError(InvocationError::NotFound))?
-            .call(coordinates, settings)
+            .ok_or(Error::InvocationError(InvocationError::NotFound))?;
+        settings.check_plugin_config(name, backend.config())?;
+        backend.call(coordinates, settings)
     }
 
     /// Load a plugin library and add all contained functions to the internal
@@ -86,7 +101,7 @@ impl ExternalBackends {
     /// # Safety
     ///
     /// A plugin library **must** be implemented using the
-    /// [`weather_core::backend::custom_backends::plugin_declaration!()`] macro. Trying manually implement
+    /// [`weather_plugin::export_plugin!()`] macro. Trying manually implement
     /// a plugin without going through that macro will result in undefined
     /// behaviour.
     pub unsafe fn load<P: AsRef<OsStr>>(&mut self, library_path: P) -> crate::Result<()> {
@@ -100,29 +115,45 @@ impl ExternalBackends {
                 .map_err(|e| format!("Could not load library at {path}, details: {e}"))?,
         );
 
-        // get a pointer to the plugin_declaration symbol.
-        let decl = library
-            .get::<*mut PluginDeclaration>(b"plugin_declaration\0")
-            .expect("plugin decl failed")
-            .read();
+        // get a pointer to the plugin declaration, plugins built for the old
+        // Rust ABI interface don't export it
+        let decl: *const PluginDeclaration = *library
+            .get::<*const PluginDeclaration>(DECLARATION_SYMBOL)
+            .map_err(|_| {
+                format!("{path} is not a plugin or was built for an older version of weathercli")
+            })?;
 
         // version checks to prevent accidental ABI incompatibilities
-        if decl.core_version != crate::CORE_VERSION {
+        let plugin_version = CStr::from_ptr((*decl).core_version).to_string_lossy();
+        if !is_compatible(&plugin_version, crate::CORE_VERSION) {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
                 format!(
-                    "Plugin version mismatch, found {}, but expected {}",
-                    decl.core_version,
+                    "Plugin version mismatch, found {plugin_version}, but expected {}",
                     crate::CORE_VERSION
                 ),
             ))?;
         }
-        let mut registrar = PluginRegistrar::new(Rc::clone(&library));
-
-        (decl.register)(&mut registrar);
+        let backend = NativeBackend {
+            decl,
+            name: None,
+            help: None,
+            config: Vec::new(),
+            _lib: Rc::clone(&library),
+        };
+        let mut infos = backend.response(((*decl).list)())?;
+        let infos: Vec<PluginInfo> = simd_json::from_slice(&mut infos)?;
 
         // add all loaded plugins to the functions map
-        self.functions.extend(registrar.functions);
+        for info in infos {
+            let proxy = NativeBackend {
+                name: Some(info.name.clone()),
+                help: info.help,
+                config: info.config,
+                ..backend.clone()
+            };
+            self.functions.insert(info.name, Box::new(proxy));
+        }
         // and make sure ExternalFunctions keeps a reference to the library
         self.libraries.push(library);
 
@@ -130,47 +161,65 @@ impl ExternalBackends {
     }
 }
 
-struct PluginRegistrar {
-    functions: HashMap<String, BackendWrapper>,
-    lib: Rc<Library>,
+/// A backend in a native plugin, it keeps the library it came from loaded.
+#[derive(Clone)]
+pub struct NativeBackend {
+    decl: *const PluginDeclaration,
+    name: Option<String>,
+    help: Option<String>,
+    config: Vec<PluginConfigKey>,
+    _lib: Rc<Library>,
 }
 
-impl PluginRegistrar {
-    fn new(lib: Rc<Library>) -> Self {
-        Self {
-            lib,
-            functions: HashMap::default(),
+impl NativeBackend {
+    /// Copies a buffer returned by the plugin and releases it
+    unsafe fn response(&self, buffer: PluginBuffer) -> crate::Result<Vec<u8>> {
+        let data = buffer.to_vec();
+        ((*self.decl).free)(buffer);
+        if data.is_empty() {
+            return Err("The plugin returned an empty response")?;
         }
+        Ok(data)
     }
 }
 
-impl crate::PluginRegistrar for PluginRegistrar {
-    fn register_function(&mut self, name: &str, backend: Box<dyn WeatherForecastPlugin>) {
-        let proxy = BackendWrapper {
-            backend,
-            _lib: Rc::clone(&self.lib),
-        };
-        self.functions.insert(name.to_string(), proxy);
-    }
-}
-
-/// A proxy object which wraps a [`WeatherForecastPlugin`] and makes sure it can't outlive
-/// the library it came from.
-pub struct BackendWrapper {
-    backend: Box<dyn WeatherForecastPlugin>,
-    _lib: Rc<Library>,
-}
-
-impl WeatherForecastPlugin for BackendWrapper {
+impl WeatherForecastPlugin for NativeBackend {
     fn call(&self, coordinates: [&str; 2], settings: Settings) -> crate::Result<WeatherForecast> {
-        self.backend.call(coordinates, settings)
+        let request = simd_json::to_vec(&PluginRequest {
+            version: crate::CORE_VERSION.to_string(),
+            name: self.name.clone().unwrap_or_default(),
+            coordinates: [coordinates[0].to_string(), coordinates[1].to_string()],
+            settings,
+        })?;
+        let mut response =
+            unsafe { self.response(((*self.decl).call)(request.as_ptr(), request.len()))? };
+        let result: Result<WeatherForecast, String> = simd_json::from_slice(&mut response)?;
+        result.map_err(|msg| Error::InvocationError(InvocationError::Other { msg }))
     }
 
     fn name(&self) -> Option<&str> {
-        self.backend.name()
+        self.name.as_deref()
     }
 
     fn help(&self) -> Option<&str> {
-        self.backend.help()
+        self.help.as_deref()
+    }
+
+    fn config(&self) -> &[PluginConfigKey] {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extensions() {
+        assert!(is_valid_ext(&format!("plugin.{DLL_EXTENSION}")));
+        assert!(is_valid_ext(&format!("/plugins/a.b.{DLL_EXTENSION}")));
+        for path in ["", "so", ".so", "plugin.wasm", "plugin.é", "é"] {
+            assert!(!is_valid_ext(path), "{path}");
+        }
     }
 }
@@ -1,19 +1,38 @@
+pub mod abi;
+pub mod conformance;
 pub mod dynamic_library_loader;
 pub mod loader;
+pub mod manifest;
+pub mod script_loader;
+pub mod template;
 pub mod wasm_loader;
 
+use std::path::Path;
 use std::{fs, io};
 
 use backend::WeatherForecast;
-use local::settings::Settings;
+use local::settings::{PluginConfigKey, Settings};
 
 use weather_dirs::custom_backends_dir;
 
-use log::debug;
+use log::{debug, error};
+use manifest::PluginManifest;
+use terminal::color;
+use wasm_loader::WasmBackend;
 
 pub type Result<T> = std::result::Result<T, weather_error::Error>;
 
-pub static CORE_VERSION: &str = "0.0";
+macro_rules! core_version {
+    () => {
+        "1.1"
+    };
+}
+
+/// Version of the plugin interface, see `abi::is_compatible` for the compatibility rules
+pub static CORE_VERSION: &str = core_version!();
+
+/// `CORE_VERSION` as a NUL terminated string for `PluginDeclaration::core_version`
+pub const CORE_VERSION_C: &str = concat!(core_version!(), "\0");
 
 pub trait WeatherForecastPlugin {
     fn call(&self, coordinates: [&str; 2], settings: Settings) -> crate::Result<WeatherForecast>;
@@ -26,37 +45,60 @@ pub trait WeatherForecastPlugin {
     fn help(&self) -> Option<&str> {
         None
     }
-}
 
-#[derive(Clone)]
-pub struct PluginDeclaration {
-    pub core_version: &'static str,
-    pub register: unsafe extern "C" fn(&mut dyn PluginRegistrar),
+    /// Config keys the backend reads from `Settings::plugin_config`, checked before every call
+    fn config(&self) -> &[PluginConfigKey] {
+        &[]
+    }
 }
 
+pub use abi::PluginDeclaration;
+
 pub trait PluginRegistrar {
     fn register_function(&mut self, name: &str, function: Box<dyn WeatherForecastPlugin>);
 }
 
+/// Exports the `PluginDeclaration` of a native plugin, `$register` is a
+/// `fn(&mut dyn PluginRegistrar)` that registers the backends of the plugin.
 #[macro_export]
 macro_rules! export_plugin {
     ($register:expr) => {
+        extern "C" fn __weather_plugin_list() -> weather_plugin::custom_backend::abi::PluginBuffer {
+            weather_plugin::custom_backend::abi::list($register)
+        }
+
+        unsafe extern "C" fn __weather_plugin_call(
+            ptr: *const u8,
+            len: usize,
+        ) -> weather_plugin::custom_backend::abi::PluginBuffer {
+            weather_plugin::custom_backend::abi::call($register, ptr, len)
+        }
+
         #[no_mangle]
-        pub static plugin_declaration: weather_plugin::custom_backend::PluginDeclaration =
+        #[allow(non_upper_case_globals)]
+        pub static weather_plugin_declaration: weather_plugin::custom_backend::PluginDeclaration =
             weather_plugin::custom_backend::PluginDeclaration {
-                core_version: weather_plugin::custom_backend::CORE_VERSION,
-                register: $register,
+                core_version: weather_plugin::custom_backend::CORE_VERSION_C.as_ptr()
+                    as *const ::std::os::raw::c_char,
+                list: __weather_plugin_list,
+                call: __weather_plugin_call,
+                free: weather_plugin::custom_backend::abi::free_buffer,
             };
     };
 }
 
+/// Whether the file is a native library or a WASM module
+pub fn is_valid_ext(f: &str) -> bool {
+    dynamic_library_loader::is_valid_ext(f) || wasm_loader::is_valid_ext(f)
+}
+
 fn is_ext(f: &io::Result<fs::DirEntry>) -> bool {
     match f {
         Err(_e) => false,
         Ok(dir) => {
             if dir.metadata().is_ok()
                 && dir.metadata().unwrap().is_file()
-                && dynamic_library_loader::is_valid_ext(dir.file_name().to_str().unwrap())
+                && is_valid_ext(dir.file_name().to_str().unwrap())
             {
                 return true;
             }
@@ -65,16 +107,72 @@ fn is_ext(f: &io::Result<fs::DirEntry>) -> bool {
     }
 }
 
-pub fn load_custom_backends() -> crate::Result<dynamic_library_loader::ExternalBackends> {
-    debug!("Detecting external dlls");
+/// Only plugins that didn't change since they were installed are loaded,
+/// plugins installed before installs were verified are loaded with a warning
+fn is_verified(plugin: &str) -> bool {
+    match manifest::check_installed(plugin) {
+        Ok(None) => true,
+        Ok(Some(warning)) => {
+            eprintln!(
+                "{}",
+                color::downsample(&format!(
+                    "{}Warning: {warning}{}",
+                    color::FORE_YELLOW,
+                    color::FORE_RESET
+                ))
+            );
+            true
+        }
+        Err(e) => {
+            error!("Skipping {plugin}: {e}");
+            eprintln!(
+                "{}",
+                color::downsample(&format!(
+                    "{}Not loading a plugin: {e}{}",
+                    color::FORE_RED,
+                    color::FORE_RESET
+                ))
+            );
+            false
+        }
+    }
+}
+
+/// Loads the backends of a single plugin file, without checking its manifest
+pub fn load_plugin<P: AsRef<Path>>(
+    path: P,
+) -> crate::Result<dynamic_library_loader::ExternalBackends> {
+    let path = path.as_ref();
+    let file_name = path.to_str().ok_or("Failed to get plugin path")?;
+    let mut backends = dynamic_library_loader::ExternalBackends::new();
+    if wasm_loader::is_valid_ext(file_name) {
+        let backend = WasmBackend::new(path, PluginManifest::network_hosts(path)?)?;
+        backends.register(&wasm_loader::get_name(path), Box::new(backend));
+    } else if dynamic_library_loader::is_valid_ext(file_name) {
+        unsafe { backends.load(path)? };
+    } else {
+        return Err("Not a plugin")?;
+    }
+    Ok(backends)
+}
+
+pub fn load_custom_backends(
+    settings: &Settings,
+) -> crate::Result<dynamic_library_loader::ExternalBackends> {
+    debug!("Detecting external backends");
     let path = custom_backends_dir()?;
-    let plugins: Vec<String> = path
-        .read_dir()
-        .expect("Reading the custom_backends dir failed")
-        .filter(is_ext) // We only care about files
-        .map(|f| f.unwrap().path().display().to_string())
-        .collect();
+    let plugins: Vec<String> = if path.exists() {
+        path.read_dir()?
+            .filter(is_ext) // We only care about files
+            .map(|f| f.unwrap().path().display().to_string())
+            .filter(|plugin| is_verified(plugin))
+            .collect()
+    } else {
+        Vec::new()
+    };
     debug!("Loading: {plugins:?}");
-    let custom_backends = dynamic_library_loader::load(plugins);
+    let mut custom_backends = dynamic_library_loader::load(plugins.clone());
+    wasm_loader::load(plugins, &mut custom_backends);
+    script_loader::load(&settings.script_backends, &mut custom_backends);
     Ok(custom_backends)
 }
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::abi::is_compatible;
+use crate::{load_plugin, CORE_VERSION};
+use local::hash_file;
+use local::settings::PluginConfigKey;
+use local::weather_file::WeatherFile;
+
+/// Host owned record of the plugins `weather backend install` verified, by file name
+const VERIFIED_PLUGINS: &str = "verified_plugins.json";
+
+/// Describes a plugin, stored next to it as `<plugin name>.manifest.json`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    /// Version of the plugin interface the plugin was built against
+    pub core_version: String,
+    #[serde(default)]
+    pub author: String,
+    /// SHA-256 of the plugin file
+    pub sha256: String,
+    /// Hosts the plugin connects to, WASM plugins can't reach any other host
+    #[serde(default)]
+    pub network_hosts: Vec<String>,
+}
+
+impl PluginManifest {
+    /// Where the manifest of the plugin at `plugin` is stored
+    pub fn path_for<P: AsRef<Path>>(plugin: P) -> PathBuf {
+        plugin.as_ref().with_extension("manifest.json")
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut text = fs::read_to_string(path)
+            .map_err(|_| format!("No plugin manifest found at {}", path.display()))?;
+        unsafe { simd_json::from_str(&mut text) }
+            .map_err(|e| format!("Invalid plugin manifest {}: {e}", path.display()).into())
+    }
+
+    /// Reads the manifest of the plugin at `plugin`, `None` if there is none
+    pub fn for_plugin<P: AsRef<Path>>(plugin: P) -> crate::Result<Option<Self>> {
+        let path = Self::path_for(plugin);
+        if path.exists() {
+            Self::read(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Hosts the WASM plugin at `plugin` may connect to, none if it has no manifest
+    pub fn network_hosts<P: AsRef<Path>>(plugin: P) -> crate::Result<Vec<String>> {
+        Ok(Self::for_plugin(plugin)?
+            .map(|manifest| manifest.network_hosts)
+            .unwrap_or_default())
+    }
+
+    /// Checks that `plugin` is the file described by the manifest and that it is compatible
+    pub fn verify<P: AsRef<Path>>(&self, plugin: P) -> crate::Result<()> {
+        let path = plugin.as_ref();
+        let hash = hash_file(path.to_str().ok_or("Failed to get plugin path")?)?;
+        if !hash.eq_ignore_ascii_case(self.sha256.trim()) {
+            return Err(format!(
+                "SHA-256 of {} is {hash}, but the manifest expects {}",
+                path.display(),
+                self.sha256
+            ))?;
+        }
+        if !is_compatible(&self.core_version, CORE_VERSION) {
+            return Err(format!(
+                "{} is built for version {} of the plugin interface, but this is version {CORE_VERSION}",
+                self.name, self.core_version
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+fn file_name(plugin: &Path) -> crate::Result<String> {
+    Ok(plugin
+        .file_name()
+        .ok_or("Failed to get plugin file name")?
+        .to_string_lossy()
+        .to_string())
+}
+
+fn verified_plugins() -> crate::Result<HashMap<String, String>> {
+    let mut file = WeatherFile::new(VERIFIED_PLUGINS)?;
+    Ok(simd_json::from_slice(&mut file.data).unwrap_or_default())
+}
+
+fn write_verified_plugins(plugins: &HashMap<String, String>) -> crate::Result<()> {
+    let mut file = WeatherFile::new(VERIFIED_PLUGINS)?;
+    file.data = simd_json::to_vec(plugins)?;
+    file.write()
+}
+
+/// Records the SHA-256 of the installed plugin at `plugin`, after its manifest was verified
+pub fn record_verified<P: AsRef<Path>>(plugin: P) -> crate::Result<()> {
+    let path = plugin.as_ref();
+    let hash = hash_file(path.to_str().ok_or("Failed to get plugin path")?)?;
+    let mut plugins = verified_plugins()?;
+    plugins.insert(file_name(path)?, hash);
+    write_verified_plugins(&plugins)
+}
+
+/// Removes the record of the plugin at `plugin`
+pub fn forget_verified<P: AsRef<Path>>(plugin: P) -> crate::Result<()> {
+    let mut plugins = verified_plugins()?;
+    if plugins.remove(&file_name(plugin.as_ref())?).is_some() {
+        write_verified_plugins(&plugins)?;
+    }
+    Ok(())
+}
+
+/// Checks an installed plugin against the hash recorded when it was installed.
+/// Plugins installed before installs were verified have no record, they are still loaded
+/// and the returned warning asks to reinstall them.
+pub fn check_installed<P: AsRef<Path>>(plugin: P) -> crate::Result<Option<String>> {
+    let path = plugin.as_ref();
+    let name = file_name(path)?;
+    let recorded = match verified_plugins()?.remove(&name) {
+        Some(hash) => hash,
+        None => {
+            return Ok(Some(format!(
+                "{name} was installed without being verified, reinstall it with `weather backend install` and its manifest"
+            )))
+        }
+    };
+    let hash = hash_file(path.to_str().ok_or("Failed to get plugin path")?)?;
+    if hash != recorded {
+        return Err(format!(
+            "{name} changed since it was installed, reinstall it with `weather backend install`"
+        ))?;
+    }
+    if let Some(manifest) = PluginManifest::for_plugin(path)? {
+        if !is_compatible(&manifest.core_version, CORE_VERSION) {
+            return Err(format!(
+                "{name} is built for version {} of the plugin interface, but this is version {CORE_VERSION}",
+                manifest.core_version
+            ))?;
+        }
+    }
+    Ok(None)
+}
+
+/// Whether the host of `url` is one of `hosts`
+pub(crate) fn is_allowed_url(url: &str, hosts: &[String]) -> bool {
+    get_host(url).map_or(false, |host| {
+        hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    })
+}
+
+fn get_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://")?.1;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    host.split(':').next().filter(|host| !host.is_empty())
+}
+
+/// A backend provided by a plugin
+pub struct BackendInfo {
+    pub name: String,
+    pub help: Option<String>,
+    pub config: Vec<PluginConfigKey>,
+}
+
+/// The result of loading a single plugin file
+pub struct PluginReport {
+    pub file: String,
+    pub manifest: Option<PluginManifest>,
+    pub backends: Vec<BackendInfo>,
+    /// Set for plugins that were installed without being verified
+    pub warning: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Loads the plugin at `path` on its own to report what it provides and whether it works
+pub fn inspect_plugin<P: AsRef<Path>>(path: P) -> PluginReport {
+    let path = path.as_ref();
+    let manifest = PluginManifest::for_plugin(path);
+    let mut report = PluginReport {
+        file: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        manifest: manifest.as_ref().ok().cloned().flatten(),
+        backends: Vec::new(),
+        warning: None,
+        error: None,
+    };
+    let result = check_installed(path).and_then(|warning| {
+        report.warning = warning;
+        load_plugin(path)
+    });
+    match result {
+        Ok(backends) => report.backends = backends.list(),
+        Err(e) => report.error = Some(e.to_string()),
+    }
+    report
+}
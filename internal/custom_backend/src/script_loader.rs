@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, trace};
+use serde::Serialize;
+
+use crate::dynamic_library_loader::ExternalBackends;
+use crate::WeatherForecastPlugin;
+use backend::WeatherForecast;
+use local::settings::{PluginConfigKey, ScriptBackendSettings, Settings};
+use weather_error::{Error, InvocationError};
+
+/// Written to the script's stdin
+#[derive(Serialize)]
+struct ScriptRequest<'a> {
+    latitude: &'a str,
+    longitude: &'a str,
+    settings: &'a Settings,
+}
+
+/// A backend that runs an executable, it receives a `ScriptRequest` as JSON on stdin
+/// and has to print a `WeatherForecast` as JSON to stdout before the timeout.
+pub struct ScriptBackend {
+    name: String,
+    config: ScriptBackendSettings,
+}
+
+impl ScriptBackend {
+    pub fn new(name: &str, config: ScriptBackendSettings) -> Self {
+        Self {
+            name: name.to_string(),
+            config,
+        }
+    }
+
+    fn fail(&self, msg: String) -> Error {
+        Error::InvocationError(InvocationError::Other {
+            msg: format!("{}: {msg}", self.name),
+        })
+    }
+
+    /// Waits for the child to exit, killing it once the timeout is exceeded
+    fn wait(&self, child: &mut Child) -> crate::Result<bool> {
+        let deadline = Instant::now() + Duration::from_secs(self.config.timeout);
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status.success());
+            }
+            if Instant::now() >= deadline {
+                child.kill().unwrap_or(());
+                child.wait()?;
+                return Err(self.fail(format!("timed out after {} seconds", self.config.timeout)));
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+fn read_all<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap_or(0);
+        data
+    })
+}
+
+impl WeatherForecastPlugin for ScriptBackend {
+    fn call(&self, coordinates: [&str; 2], settings: Settings) -> crate::Result<WeatherForecast> {
+        trace!("Running {} {:?}", self.config.command, self.config.args);
+        let request = simd_json::to_vec(&ScriptRequest {
+            latitude: coordinates[0],
+            longitude: coordinates[1],
+            settings: &settings,
+        })?;
+        let mut child = Command::new(&self.config.command)
+            .args(&self.config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.fail(format!("failed to run {}: {e}", self.config.command)))?;
+        // Pipes are handled on other threads so a full pipe can't block past the timeout
+        let stdout = read_all(child.stdout.take().ok_or("Failed to open stdout")?);
+        let stderr = read_all(child.stderr.take().ok_or("Failed to open stderr")?);
+        if let Some(mut stdin) = child.stdin.take() {
+            // The script may exit (or hang) without reading its input
+            thread::spawn(move || stdin.write_all(&request).unwrap_or(()));
+        }
+        let success = self.wait(&mut child)?;
+        let mut stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if !stderr.is_empty() {
+            debug!("{} stderr: {}", self.name, String::from_utf8_lossy(&stderr));
+        }
+        if !success {
+            return Err(self.fail(format!(
+                "exited with an error: {}",
+                String::from_utf8_lossy(&stderr).trim()
+            )));
+        }
+        simd_json::from_slice(&mut stdout)
+            .map_err(|e| self.fail(format!("printed an invalid forecast: {e}")))
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn help(&self) -> Option<&str> {
+        self.config.help.as_deref()
+    }
+
+    fn config(&self) -> &[PluginConfigKey] {
+        &self.config.config
+    }
+}
+
+/// Registers every script backend configured in `SCRIPT_BACKENDS`
+pub fn load(scripts: &HashMap<String, ScriptBackendSettings>, backends: &mut ExternalBackends) {
+    for (name, config) in scripts {
+        trace!("Registering script backend {name}");
+        backends.register(name, Box::new(ScriptBackend::new(name, config.clone())));
+    }
+}
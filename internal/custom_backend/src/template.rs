@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+
+use crate::CORE_VERSION;
+
+const CARGO_TOML: &str = r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+weather_plugin = { git = "https://github.com/arihant2math/weathercli" }
+bincode = "1.3"
+"#;
+
+const LIB_RS: &str = r#"use std::collections::HashMap;
+
+use weather_plugin::custom_backend::{PluginRegistrar, WeatherForecastPlugin};
+use weather_plugin::settings::Settings;
+use weather_plugin::weather_file::WeatherFile;
+use weather_plugin::{export_plugin, get_conditions_sentence, get_forecast_sentence, now};
+use weather_plugin::{WeatherCondition, WeatherData, WeatherForecast, WindData};
+
+fn get_forecast(coordinates: [&str; 2], settings: Settings) -> weather_plugin::Result<WeatherForecast> {
+    let weather_file = WeatherFile::weather_codes()?;
+    let weather_codes: HashMap<String, Vec<String>> = bincode::deserialize(&weather_file.data)?;
+    // TODO: fetch the weather at the coordinates with weather_plugin::networking,
+    // in metric units if settings.metric_default is set
+    let _ = (coordinates, settings.metric_default);
+    let conditions = vec![WeatherCondition::new(800, &weather_codes)?];
+    let current = WeatherData {
+        time: now() as i128, // milliseconds
+        temperature: 20.0,
+        min_temp: 15.0,
+        max_temp: 25.0,
+        wind: WindData { speed: 5.0, heading: 180 },
+        raw_data: String::new(),
+        dewpoint: 10.0,
+        feels_like: 20.0,
+        aqi: 0,
+        cloud_cover: 0,
+        precipitation: 0.0, // millimeters
+        condition_sentence: get_conditions_sentence(conditions.clone()),
+        conditions,
+    };
+    let forecast = vec![current.clone()];
+    Ok(WeatherForecast {
+        region: String::new(),
+        country: String::new(),
+        forecast_sentence: get_forecast_sentence(&forecast),
+        forecast,
+        current_weather: current,
+        daily: Vec::new(),
+        alerts: Vec::new(),
+        raw_data: None,
+        utc_offset: None,
+    })
+}
+
+export_plugin!(register);
+
+fn register(registrar: &mut dyn PluginRegistrar) {
+    registrar.register_function("{name}", Box::new(Backend));
+}
+
+struct Backend;
+
+impl WeatherForecastPlugin for Backend {
+    fn call(&self, coordinates: [&str; 2], settings: Settings) -> weather_plugin::Result<WeatherForecast> {
+        get_forecast(coordinates, settings)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("{name}")
+    }
+
+    fn help(&self) -> Option<&str> {
+        Some("TODO: describe {name}")
+    }
+}
+"#;
+
+const MANIFEST: &str = r#"{
+    "name": "{name}",
+    "version": "0.1.0",
+    "core_version": "{core_version}",
+    "author": "",
+    "sha256": "",
+    "network_hosts": []
+}
+"#;
+
+const README: &str = r#"# {name}
+
+A weathercli backend.
+
+1. Implement `get_forecast` in `src/lib.rs`
+2. Build it with `cargo build --release`
+3. Copy `{name}.manifest.json` next to the library in `target/release` as
+   `lib{name}.manifest.json` (`{name}.manifest.json` on Windows), set `sha256`
+   to the SHA-256 of the library and list the hosts the backend connects to
+4. Check it with `weather backend test target/release/<library>`
+5. Install it with `weather backend install target/release/<library>`
+"#;
+
+/// Whether `name` can be used as a crate and backend name
+pub fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Creates a native plugin crate for a backend called `name` in `dir`
+pub fn create_plugin(dir: &Path, name: &str) -> crate::Result<()> {
+    if !is_valid_name(name) {
+        return Err(format!(
+            "{name} is not a valid name, use lowercase letters, digits and underscores"
+        ))?;
+    }
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()))?;
+    }
+    let fill = |template: &str| {
+        template
+            .replace("{name}", name)
+            .replace("{core_version}", CORE_VERSION)
+    };
+    fs::create_dir_all(dir.join("src"))?;
+    fs::write(dir.join("Cargo.toml"), fill(CARGO_TOML))?;
+    fs::write(dir.join("src").join("lib.rs"), fill(LIB_RS))?;
+    fs::write(dir.join(format!("{name}.manifest.json")), fill(MANIFEST))?;
+    fs::write(dir.join("README.md"), fill(README))?;
+    Ok(())
+}
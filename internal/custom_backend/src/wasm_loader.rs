@@ -1,4 +1,382 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use log::{debug, error, trace};
+use serde::{Deserialize, Serialize};
+use wasmer::wasmparser::Operator;
+use wasmer::{
+    imports, CompilerConfig, Cranelift, EngineBuilder, Function, FunctionEnv, FunctionEnvMut,
+    Instance, Memory, Module, Store, TypedFunction,
+};
+use wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints};
+use wasmer_middlewares::Metering;
+
+use crate::dynamic_library_loader::ExternalBackends;
+use crate::manifest::{is_allowed_url, PluginManifest};
+use crate::WeatherForecastPlugin;
+use backend::WeatherForecast;
+use local::settings::{PluginConfigKey, Settings};
+use weather_error::{Error, InvocationError};
+
+/// Instructions a module may run per call, so a module that never returns can't hang `weather`
+const FUEL: u64 = 10_000_000_000;
+
 pub fn is_valid_ext(f: &str) -> bool {
-    let len = f.len();
-    &f[len - 4..] == ".lib"
+    Path::new(f).extension().map_or(false, |ext| ext == "wasm")
 }
+
+/// Request passed to the `weather_fetch` host function
+#[derive(Deserialize)]
+struct FetchRequest {
+    url: String,
+    user_agent: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+}
+
+/// Response of the `weather_fetch` host function, a status of 0 means the request failed
+#[derive(Serialize)]
+struct FetchResponse {
+    status: u16,
+    text: String,
+}
+
+/// Request passed to the `weather_forecast` export
+#[derive(Serialize)]
+struct ForecastRequest<'a> {
+    latitude: &'a str,
+    longitude: &'a str,
+}
+
+/// Settings passed to the module, only its own config and never the API keys of other backends
+#[derive(Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct GuestSettings<'a> {
+    metric_default: bool,
+    /// `Settings::plugin_config` of this backend
+    plugin_config: Option<&'a HashMap<String, String>>,
+}
+
+#[derive(Default)]
+struct HostEnv {
+    memory: Option<Memory>,
+    alloc: Option<TypedFunction<u32, u32>>,
+    settings: String,
+    network_hosts: Vec<String>,
+}
+
+/// Buffers are passed between the host and the module as a pointer in the high 32 bits and a
+/// length in the low 32 bits
+fn pack(ptr: u32, len: u32) -> u64 {
+    (u64::from(ptr) << 32) | u64::from(len)
+}
+
+fn unpack(value: u64) -> (u32, u32) {
+    ((value >> 32) as u32, value as u32)
+}
+
+fn read_guest(env: &FunctionEnvMut<HostEnv>, ptr: u32, len: u32) -> Option<Vec<u8>> {
+    let memory = env.data().memory.as_ref()?;
+    let mut buf = vec![0; len as usize];
+    memory.view(env).read(u64::from(ptr), &mut buf).ok()?;
+    Some(buf)
+}
+
+/// Copies `data` into a buffer allocated by the module, returns 0 on failure
+fn write_guest(env: &mut FunctionEnvMut<HostEnv>, data: &[u8]) -> u64 {
+    let (memory, alloc) = match (env.data().memory.clone(), env.data().alloc.clone()) {
+        (Some(memory), Some(alloc)) => (memory, alloc),
+        _ => return 0,
+    };
+    let len = match u32::try_from(data.len()) {
+        Ok(len) => len,
+        Err(_) => return 0,
+    };
+    match alloc.call(env, len) {
+        Ok(ptr) if memory.view(&*env).write(u64::from(ptr), data).is_ok() => pack(ptr, len),
+        _ => 0,
+    }
+}
+
+fn fetch(request: FetchRequest, network_hosts: &[String]) -> FetchResponse {
+    trace!("WASM backend fetching {}", request.url);
+    if !is_allowed_url(&request.url, network_hosts) {
+        return FetchResponse {
+            status: 0,
+            text: format!("{} is not declared in the plugin manifest", request.url),
+        };
+    }
+    match networking::get_url(request.url, request.user_agent, Some(request.headers), None) {
+        Ok(resp) => FetchResponse {
+            status: resp.status,
+            text: resp.text,
+        },
+        Err(e) => FetchResponse {
+            status: 0,
+            text: e.to_string(),
+        },
+    }
+}
+
+/// `weather_fetch(ptr, len) -> packed`, takes a JSON `FetchRequest` and returns a JSON `FetchResponse`
+fn host_fetch(mut env: FunctionEnvMut<HostEnv>, ptr: u32, len: u32) -> u64 {
+    let response = match read_guest(&env, ptr, len)
+        .and_then(|mut request| simd_json::from_slice::<FetchRequest>(&mut request).ok())
+    {
+        Some(request) => fetch(request, &env.data().network_hosts),
+        None => FetchResponse {
+            status: 0,
+            text: "Invalid fetch request".to_string(),
+        },
+    };
+    match simd_json::to_vec(&response) {
+        Ok(data) => write_guest(&mut env, &data),
+        Err(_) => 0,
+    }
+}
+
+/// `weather_settings() -> packed`, returns the `GuestSettings` as JSON
+fn host_settings(mut env: FunctionEnvMut<HostEnv>) -> u64 {
+    let settings = env.data().settings.clone();
+    write_guest(&mut env, settings.as_bytes())
+}
+
+/// `weather_log(ptr, len)`, logs a UTF-8 message at debug level
+fn host_log(env: FunctionEnvMut<HostEnv>, ptr: u32, len: u32) {
+    if let Some(message) = read_guest(&env, ptr, len) {
+        debug!("WASM backend: {}", String::from_utf8_lossy(&message));
+    }
+}
+
+/// A backend compiled to WebAssembly.
+///
+/// The module only has access to the host functions in the `env` namespace
+/// (`weather_fetch`, `weather_settings` and `weather_log`), and must export `memory`,
+/// `weather_alloc(len) -> ptr` and `weather_forecast(ptr, len) -> packed`. The forecast export
+/// receives the coordinates as JSON and returns a JSON `{"Ok": WeatherForecast}` or `{"Err": message}`.
+/// `weather_name() -> packed`, `weather_help() -> packed` and `weather_config() -> packed`
+/// (a JSON list of `PluginConfigKey`) may be exported as well.
+/// Requests to hosts that aren't in `network_hosts` fail.
+/// Every call may run `FUEL` instructions, it traps once they are used up.
+pub struct WasmBackend {
+    /// Name the backend is registered as, its config is looked up by it
+    plugin: String,
+    store: RefCell<Store>,
+    env: FunctionEnv<HostEnv>,
+    instance: Instance,
+    memory: Memory,
+    alloc: TypedFunction<u32, u32>,
+    forecast: TypedFunction<(u32, u32), u64>,
+    name: Option<String>,
+    help: Option<String>,
+    config: Vec<PluginConfigKey>,
+}
+
+impl WasmBackend {
+    pub fn new<P: AsRef<Path>>(path: P, network_hosts: Vec<String>) -> crate::Result<Self> {
+        let plugin = get_name(&path);
+        let bytes = fs::read(path)?;
+        let mut compiler = Cranelift::default();
+        compiler.push_middleware(Arc::new(Metering::new(FUEL, |_: &Operator| 1)));
+        let mut store = Store::new(EngineBuilder::new(compiler));
+        let module = Module::new(&store, bytes)?;
+        let env = FunctionEnv::new(
+            &mut store,
+            HostEnv {
+                network_hosts,
+                ..HostEnv::default()
+            },
+        );
+        let import_object = imports! {
+            "env" => {
+                "weather_fetch" => Function::new_typed_with_env(&mut store, &env, host_fetch),
+                "weather_settings" => Function::new_typed_with_env(&mut store, &env, host_settings),
+                "weather_log" => Function::new_typed_with_env(&mut store, &env, host_log),
+            }
+        };
+        let instance = Instance::new(&mut store, &module, &import_object)
+            .map_err(|e| format!("Failed to instantiate WASM backend: {e}"))?;
+        let exports = &instance.exports;
+        let missing = |e: wasmer::ExportError| format!("Invalid WASM backend: {e}");
+        let memory = exports.get_memory("memory").map_err(missing)?.clone();
+        let alloc = exports
+            .get_typed_function(&store, "weather_alloc")
+            .map_err(missing)?;
+        let forecast = exports
+            .get_typed_function(&store, "weather_forecast")
+            .map_err(missing)?;
+        let name_fn = exports.get_typed_function(&store, "weather_name").ok();
+        let help_fn = exports.get_typed_function(&store, "weather_help").ok();
+        let config_fn = exports.get_typed_function(&store, "weather_config").ok();
+        let data = env.as_mut(&mut store);
+        data.memory = Some(memory.clone());
+        data.alloc = Some(alloc.clone());
+        let mut backend = Self {
+            plugin,
+            store: RefCell::new(store),
+            env,
+            instance,
+            memory,
+            alloc,
+            forecast,
+            name: None,
+            help: None,
+            config: Vec::new(),
+        };
+        backend.name = backend.call_string(name_fn)?;
+        backend.help = backend.call_string(help_fn)?;
+        if let Some(config) = backend.call_string(config_fn)? {
+            backend.config = simd_json::from_slice(&mut config.into_bytes())?;
+        }
+        Ok(backend)
+    }
+
+    fn read(&self, store: &Store, packed: u64) -> crate::Result<Vec<u8>> {
+        let (ptr, len) = unpack(packed);
+        let mut buf = vec![0; len as usize];
+        self.memory
+            .view(store)
+            .read(u64::from(ptr), &mut buf)
+            .map_err(|e| format!("WASM backend returned an invalid buffer: {e}"))?;
+        Ok(buf)
+    }
+
+    fn write(&self, store: &mut Store, data: &[u8]) -> crate::Result<(u32, u32)> {
+        let len = u32::try_from(data.len()).map_err(|_| "WASM request too large")?;
+        let ptr = self
+            .alloc
+            .call(store, len)
+            .map_err(|e| format!("WASM backend failed to allocate: {e}"))?;
+        self.memory
+            .view(&*store)
+            .write(u64::from(ptr), data)
+            .map_err(|e| format!("WASM backend returned an invalid buffer: {e}"))?;
+        Ok((ptr, len))
+    }
+
+    /// Calls an export with a fresh budget of `FUEL` instructions
+    fn run<T>(
+        &self,
+        store: &mut Store,
+        call: impl FnOnce(&mut Store) -> Result<T, wasmer::RuntimeError>,
+    ) -> crate::Result<T> {
+        set_remaining_points(store, &self.instance, FUEL);
+        call(store).map_err(|e| {
+            let msg = match get_remaining_points(store, &self.instance) {
+                MeteringPoints::Exhausted => {
+                    format!("WASM backend was stopped after running {FUEL} instructions")
+                }
+                MeteringPoints::Remaining(_) => format!("WASM backend trapped: {e}"),
+            };
+            msg.into()
+        })
+    }
+
+    fn call_string(
+        &self,
+        function: Option<TypedFunction<(), u64>>,
+    ) -> crate::Result<Option<String>> {
+        let function = match function {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+        let mut store = self.store.borrow_mut();
+        let packed = self.run(&mut store, |store| function.call(store))?;
+        let bytes = self.read(&store, packed)?;
+        Ok(Some(String::from_utf8_lossy(&bytes).to_string()))
+    }
+}
+
+impl WeatherForecastPlugin for WasmBackend {
+    fn call(&self, coordinates: [&str; 2], settings: Settings) -> crate::Result<WeatherForecast> {
+        let mut store = self.store.borrow_mut();
+        self.env.as_mut(&mut store).settings = simd_json::to_string(&GuestSettings {
+            metric_default: settings.metric_default,
+            plugin_config: settings.plugin_config.get(&self.plugin),
+        })?;
+        let request = simd_json::to_vec(&ForecastRequest {
+            latitude: coordinates[0],
+            longitude: coordinates[1],
+        })?;
+        let (ptr, len) = self.write(&mut store, &request)?;
+        let packed = self.run(&mut store, |store| self.forecast.call(store, ptr, len))?;
+        let mut response = self.read(&store, packed)?;
+        let result: Result<WeatherForecast, String> = simd_json::from_slice(&mut response)?;
+        result.map_err(|msg| Error::InvocationError(InvocationError::Other { msg }))
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+
+    fn config(&self) -> &[PluginConfigKey] {
+        &self.config
+    }
+}
+
+/// WASM backends are named after the file stem
+pub fn get_name<P: AsRef<Path>>(path: P) -> String {
+    path.as_ref()
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Loads every `.wasm` backend in `paths` into `backends`, restricted to the hosts in their manifest
+pub fn load(paths: Vec<String>, backends: &mut ExternalBackends) {
+    for path in paths {
+        if is_valid_ext(&path) {
+            trace!("Loading {path}");
+            let backend = PluginManifest::network_hosts(&path)
+                .and_then(|network_hosts| WasmBackend::new(&path, network_hosts));
+            match backend {
+                Ok(backend) => {
+                    backends.register(&get_name(&path), Box::new(backend));
+                    trace!("Loaded {path} successfully");
+                }
+                Err(e) => error!("Failed to load WASM backend at {path}: {e}"),
+            }
+        }
+    }
+}
+
+/// Defines `__rust_probestack`, the stack probe wasmer 3 links compiled modules against.
+/// Newer Rust toolchains inline their stack probes and no longer export it, so the symbol is
+/// defined weakly here, a toolchain that still exports it takes precedence.
+macro_rules! probestack {
+    ($name:literal, $linkage:literal) => {
+        std::arch::global_asm!(
+            concat!(".text\n", $linkage, " ", $name, "\n"),
+            concat!(".p2align 4\n", $name, ":"),
+            "push rbp",
+            "mov rbp, rsp",
+            "mov r11, rax",
+            "cmp r11, 0x1000",
+            "jna 3f",
+            "2:",
+            "sub rsp, 0x1000",
+            "test qword ptr [rsp + 8], rsp",
+            "sub r11, 0x1000",
+            "cmp r11, 0x1000",
+            "ja 2b",
+            "3:",
+            "sub rsp, r11",
+            "test qword ptr [rsp + 8], rsp",
+            "add rsp, rax",
+            "leave",
+            "ret",
+        );
+    };
+}
+
+#[cfg(all(target_arch = "x86_64", not(windows), not(target_vendor = "apple")))]
+probestack!("__rust_probestack", ".weak");
+#[cfg(all(target_arch = "x86_64", target_vendor = "apple"))]
+probestack!("___rust_probestack", ".globl ___rust_probestack\n.weak_definition");
@@ -16,7 +16,12 @@ pub type Result<T> = std::result::Result<T, weather_error::Error>;
 pub fn hash_file(filename: &str) -> crate::Result<String> {
     let input = Path::new(filename);
     let bytes = fs::read(input)?;
-    Ok(hex::encode(sha2::Sha256::digest(bytes)))
+    Ok(hash(&bytes))
+}
+
+/// returns the sha-256 of the data
+pub fn hash(data: &[u8]) -> String {
+    hex::encode(sha2::Sha256::digest(data))
 }
 
 pub fn now() -> u128 {
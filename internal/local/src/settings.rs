@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 #[cfg(windows)]
 use windows::Win32::System::Power::SYSTEM_POWER_STATUS;
@@ -12,6 +14,10 @@ fn _default_layout() -> String {
     String::from("default.res")
 }
 
+fn _default_theme() -> String {
+    String::from("default")
+}
+
 const fn _default_daemon_update_interval() -> i64 {
     600
 }
@@ -20,6 +26,14 @@ fn _meteo() -> String {
     String::from("meteo")
 }
 
+fn _one_call_version() -> String {
+    String::from("3.0")
+}
+
+const fn _script_timeout() -> u64 {
+    30
+}
+
 fn _update_server() -> String {
     String::from("https://arihant2math.github.io/weathercli/")
 }
@@ -54,12 +68,63 @@ fn _constant_location() -> bool {
     unsafe { _constant_location_base().unwrap_or(false) }
 }
 
+/// Type of a plugin config value, values are always stored as strings
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginConfigType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+}
+
+impl PluginConfigType {
+    pub fn is_valid(self, value: &str) -> bool {
+        match self {
+            Self::String => true,
+            Self::Integer => value.trim().parse::<i64>().is_ok(),
+            Self::Float => value.trim().parse::<f64>().is_ok(),
+            Self::Boolean => value.trim().parse::<bool>().is_ok(),
+        }
+    }
+}
+
+/// A config key declared by a plugin
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PluginConfigKey {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub value_type: PluginConfigType,
+    #[serde(default)]
+    pub help: String,
+    /// Optional keys aren't reported when missing
+    #[serde(default = "_true")]
+    pub required: bool,
+}
+
+/// An executable used as a backend, see `SCRIPT_BACKENDS`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScriptBackendSettings {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds before the script is killed
+    #[serde(default = "_script_timeout")]
+    pub timeout: u64,
+    #[serde(default)]
+    pub help: Option<String>,
+    #[serde(default)]
+    pub config: Vec<PluginConfigKey>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Settings {
     #[serde(default)]
     pub open_weather_map_api_key: String,
+    #[serde(default = "_one_call_version")]
+    pub open_weather_map_one_call_version: String,
     #[serde(default)]
     pub bing_maps_api_key: String,
     #[serde(default)]
@@ -78,6 +143,9 @@ pub struct Settings {
     pub show_alerts: bool,
     #[serde(default = "_default_layout")]
     pub layout_file: String,
+    /// Theme file in the themes directory, or "default" for the built in theme
+    #[serde(default = "_default_theme")]
+    pub theme: String,
     #[serde(default)]
     pub enable_daemon: bool,
     #[serde(default = "_default_daemon_update_interval")]
@@ -89,6 +157,11 @@ pub struct Settings {
     pub update_server: String,
     #[serde(default)]
     pub enable_custom_backends: bool,
+    #[serde(default)]
+    pub script_backends: HashMap<String, ScriptBackendSettings>,
+    /// Config of custom backends, by backend name and then key
+    #[serde(default)]
+    pub plugin_config: HashMap<String, HashMap<String, String>>,
     #[serde(skip_serializing, skip_deserializing)]
     #[serde(default = "_file")]
     file: WeatherFile,
@@ -99,6 +172,39 @@ impl Settings {
         unsafe { Ok(simd_json::from_str(&mut _file().get_text()?)?) }
     }
 
+    /// Gets a config value of a custom backend
+    pub fn get_plugin_config(&self, plugin: &str, key: &str) -> Option<&str> {
+        self.plugin_config
+            .get(plugin)
+            .and_then(|config| config.get(key))
+            .map(String::as_str)
+    }
+
+    /// Reports keys that are missing or have a value of the wrong type
+    pub fn check_plugin_config(&self, plugin: &str, keys: &[PluginConfigKey]) -> crate::Result<()> {
+        let mut problems = Vec::new();
+        for key in keys {
+            match self.get_plugin_config(plugin, &key.name) {
+                None if key.required => {
+                    problems.push(format!("{} is missing ({})", key.name, key.help))
+                }
+                Some(value) if !key.value_type.is_valid(value) => problems.push(format!(
+                    "{} should be of type {}, but is {value}",
+                    key.name,
+                    format!("{:?}", key.value_type).to_lowercase()
+                )),
+                _ => {}
+            }
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "Invalid config for {plugin}: {}. Set keys with `weather config {plugin}.<key> <value>`",
+            problems.join(", ")
+        ))?
+    }
+
     pub fn write(&mut self) -> crate::Result<()> {
         self.file.data = Vec::from(simd_json::to_string(&self)?);
         self.file.write()?;
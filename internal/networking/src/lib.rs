@@ -13,6 +13,19 @@ pub struct Resp {
     pub status: u16,
     pub bytes: Vec<u8>,
     pub text: String,
+    /// Response headers, names are lowercase
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+fn get_headers(resp: &ureq::Response) -> HashMap<String, String> {
+    resp.headers_names()
+        .into_iter()
+        .filter_map(|name| {
+            let value = resp.header(&name)?.to_string();
+            Some((name.to_lowercase(), value))
+        })
+        .collect()
 }
 
 fn get_user_agent<S: AsRef<str>>(custom: Option<S>) -> String {
@@ -67,6 +80,7 @@ pub fn get_url<S: AsRef<str>>(
     }
     let data = real_resp.unwrap();
     let status = data.status();
+    let headers = get_headers(&data);
     let mut bytes: Vec<u8> = Vec::with_capacity(100);
     data.into_reader()
         .take(10_000_000)
@@ -79,6 +93,7 @@ pub fn get_url<S: AsRef<str>>(
         status,
         bytes,
         text,
+        headers,
     })
 }
 
@@ -120,6 +135,7 @@ pub fn get_urls(
             let req = client.get(url);
             let data = req.call().expect("Request failed");
             let status = data.status();
+            let headers = get_headers(&data);
             let mut bytes: Vec<u8> = Vec::with_capacity(128);
             data.into_reader()
                 .take(10_000_000)
@@ -134,6 +150,7 @@ pub fn get_urls(
                 status,
                 bytes,
                 text,
+                headers,
             }
         })
         .collect();
@@ -6,7 +6,7 @@ use iced::theme::Theme;
 use iced::widget::{button, column, container, radio, row, text, text_input, toggler};
 use iced::{Alignment, Element, Length, Sandbox};
 
-use local::settings;
+use local::settings::{self, PluginConfigKey};
 
 pub fn run_settings_app() -> iced::Result {
     App::run(iced::Settings {
@@ -37,6 +37,29 @@ pub fn run_settings_app() -> iced::Result {
 struct App {
     theme: Theme,
     data: settings::Settings,
+    /// Config keys declared by the loaded custom backends, by backend name
+    config_keys: Vec<(String, PluginConfigKey)>,
+}
+
+/// Config keys of the custom backends, sorted by backend and key name
+fn declared_config(settings: &settings::Settings) -> Vec<(String, PluginConfigKey)> {
+    if !settings.enable_custom_backends {
+        return Vec::new();
+    }
+    let mut keys: Vec<(String, PluginConfigKey)> = custom_backend::load_custom_backends(settings)
+        .map(|backends| backends.list())
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|backend| {
+            let name = backend.name;
+            backend
+                .config
+                .into_iter()
+                .map(move |key| (name.clone(), key))
+        })
+        .collect();
+    keys.sort_by(|(a, a_key), (b, b_key)| (a, &a_key.name).cmp(&(b, &b_key.name)));
+    keys
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +69,7 @@ enum Message {
     AutoUpdateInternetResources(bool),
     EnableDaemon(bool),
     OpenWeatherMapAPIKey(String),
+    PluginConfig(String, String, String),
     DataSource(DataSource),
     Cancel,
     Save,
@@ -57,6 +81,7 @@ enum DataSource {
     OpenWeatherMap,
     OpenWeatherMapOneCall,
     Nws,
+    MetNo,
 }
 
 impl fmt::Display for DataSource {
@@ -66,6 +91,7 @@ impl fmt::Display for DataSource {
             DataSource::OpenWeatherMap => "openweathermap".to_string(),
             DataSource::OpenWeatherMapOneCall => "openweathermap_onecall".to_string(),
             DataSource::Nws => "nws".to_string(),
+            DataSource::MetNo => "metno".to_string(),
         };
         write!(f, "{s}")
     }
@@ -84,6 +110,7 @@ impl Sandbox for App {
         let data = settings::Settings::new().expect("Loading settings failed");
         App {
             theme,
+            config_keys: declared_config(&data),
             data,
         }
     }
@@ -105,6 +132,13 @@ impl Sandbox for App {
             }
             Message::EnableDaemon(value) => self.data.enable_daemon = value,
             Message::OpenWeatherMapAPIKey(value) => self.data.open_weather_map_api_key = value,
+            Message::PluginConfig(plugin, key, value) => {
+                self.data
+                    .plugin_config
+                    .entry(plugin)
+                    .or_default()
+                    .insert(key, value);
+            }
             Message::DataSource(value) => {
                 self.data.default_backend = value.to_string().to_uppercase()
             }
@@ -116,6 +150,7 @@ impl Sandbox for App {
             DataSource::Meteo,
             DataSource::OpenWeatherMap,
             DataSource::Nws,
+            DataSource::MetNo,
         ]
         .iter()
         .fold(
@@ -128,6 +163,7 @@ impl Sandbox for App {
                         "openweathermap" => DataSource::OpenWeatherMap,
                         "openweathermap_onecall" => DataSource::OpenWeatherMapOneCall,
                         "nws" => DataSource::Nws,
+                        "metno" => DataSource::MetNo,
                         _ => DataSource::Meteo,
                     }),
                     Message::DataSource,
@@ -170,6 +206,64 @@ impl Sandbox for App {
         )
         .width(Length::Shrink)
         .spacing(10);
+        // The keys the backends declare, then keys that are set but no backend declares
+        let mut plugin_entries: Vec<(String, String, String)> = self
+            .config_keys
+            .iter()
+            .map(|(plugin, key)| {
+                let kind = format!("{:?}", key.value_type).to_lowercase();
+                let help = if key.help.is_empty() {
+                    kind
+                } else {
+                    format!("{kind}, {}", key.help)
+                };
+                (plugin.clone(), key.name.clone(), help)
+            })
+            .collect();
+        let mut undeclared: Vec<(String, String, String)> = self
+            .data
+            .plugin_config
+            .iter()
+            .flat_map(|(plugin, config)| {
+                config
+                    .keys()
+                    .map(move |key| (plugin.clone(), key.clone(), String::new()))
+            })
+            .filter(|(plugin, key, _)| {
+                !self
+                    .config_keys
+                    .iter()
+                    .any(|(p, k)| p == plugin && k.name == *key)
+            })
+            .collect();
+        undeclared.sort();
+        plugin_entries.append(&mut undeclared);
+        let plugin_config = plugin_entries.into_iter().fold(
+            column![text("Custom Backend Config:")].spacing(10),
+            |column, (plugin, key, help)| {
+                let value = self
+                    .data
+                    .get_plugin_config(&plugin, &key)
+                    .unwrap_or_default();
+                let label = text(format!("{plugin}.{key}: "));
+                let mut entry = column![row![
+                    label,
+                    text_input("value", value)
+                        .on_input(move |value| {
+                            Message::PluginConfig(plugin.clone(), key.clone(), value)
+                        })
+                        .padding(10)
+                        .size(20)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)]
+                .spacing(5);
+                if !help.is_empty() {
+                    entry = entry.push(text(help).size(14));
+                }
+                column.push(entry)
+            },
+        );
 
         let save = button("Save").padding(10).on_press(Message::Save);
         let cancel = button("Cancel").padding(10).on_press(Message::Cancel);
@@ -198,6 +292,7 @@ impl Sandbox for App {
                 .spacing(10)
                 .height(50)
                 .align_items(Alignment::Center),
+            plugin_config,
             row![cancel, save]
                 .spacing(10)
                 .align_items(Alignment::Center),
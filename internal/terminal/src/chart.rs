@@ -0,0 +1,375 @@
+//! Line and bar charts drawn with braille and block characters
+
+use crate::color::{FORE_BLUE, FORE_CYAN, FORE_LIGHTBLACK, FORE_RED, FORE_RESET, FORE_YELLOW};
+use crate::text::truncate;
+
+/// Eighths of a character, from empty to full
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Bits of the braille dots by column and row, a character has two columns of four dots
+const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// How a chart draws its values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// A line of braille dots, two by four dots per character
+    Line,
+    /// Bars of block characters, eight steps per character
+    Bars,
+}
+
+impl Style {
+    /// Parses `line` or `bars`
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "line" => Some(Self::Line),
+            "bars" => Some(Self::Bars),
+            _ => None,
+        }
+    }
+}
+
+/// A series of values, each with a label for the x axis like its time
+pub struct Chart<'a> {
+    pub values: &'a [f64],
+    pub labels: &'a [String],
+    /// Unit of the values, shown above the y axis and with the min and max
+    pub unit: &'a str,
+    /// Index of the value for now, marked on the x axis
+    pub now: Option<usize>,
+    pub style: Style,
+}
+
+/// Smallest and largest finite value, 0 and 1 if there are none
+fn range(values: &[f64]) -> (f64, f64) {
+    let finite = values.iter().copied().filter(|value| value.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+    if min.is_finite() {
+        (min, max)
+    } else {
+        (0.0, 1.0)
+    }
+}
+
+/// Range of the y axis, flat lines are drawn in the middle and bars start at 0 unless there are
+/// negative values
+fn axis_range(values: &[f64], style: Style) -> (f64, f64) {
+    let (min, max) = range(values);
+    match style {
+        Style::Line if max > min => (min, max),
+        Style::Line => (min - 1.0, max + 1.0),
+        Style::Bars => {
+            let min = min.min(0.0);
+            (min, if max > min { max } else { min + 1.0 })
+        }
+    }
+}
+
+/// A number with one decimal when the range of the chart is small
+fn number(value: f64, span: f64) -> String {
+    if span < 10.0 {
+        format!("{value:.1}")
+    } else {
+        format!("{value:.0}")
+    }
+}
+
+fn with_unit(value: String, unit: &str) -> String {
+    if unit.is_empty() || unit.starts_with('°') || unit == "%" {
+        value + unit
+    } else {
+        format!("{value} {unit}")
+    }
+}
+
+/// Index of the first largest or smallest value
+fn extreme(values: &[f64], largest: bool) -> Option<usize> {
+    let mut found: Option<usize> = None;
+    for (i, value) in values.iter().enumerate() {
+        if !value.is_finite() {
+            continue;
+        }
+        let better = found.map_or(true, |j| {
+            if largest {
+                *value > values[j]
+            } else {
+                *value < values[j]
+            }
+        });
+        if better {
+            found = Some(i);
+        }
+    }
+    found
+}
+
+/// A single line of block characters as high as the values, to show a trend inline
+pub fn sparkline(values: &[f64]) -> String {
+    let (min, max) = axis_range(values, Style::Line);
+    values
+        .iter()
+        .map(|value| {
+            let step = ((value - min) / (max - min) * 7.0).round().clamp(0.0, 7.0);
+            // Missing values are left out
+            if value.is_finite() {
+                BLOCKS[1 + step as usize]
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+/// Braille cells of a line through the values, and the column of every value
+fn line(
+    values: &[f64],
+    min: f64,
+    span: f64,
+    columns: usize,
+    height: usize,
+) -> (Vec<Vec<char>>, Vec<usize>) {
+    let (dots_x, dots_y) = (columns * 2, height * 4);
+    let x = |i: usize| {
+        if values.len() > 1 {
+            i * (dots_x - 1) / (values.len() - 1)
+        } else {
+            0
+        }
+    };
+    // Dots are counted from the bottom
+    let y = |value: f64| {
+        let y = ((value - min) / span * (dots_y - 1) as f64).round();
+        y.clamp(0.0, (dots_y - 1) as f64) as usize
+    };
+    let mut bits = vec![vec![0_u32; columns]; height];
+    let mut set = |x: usize, y: usize| {
+        let from_top = dots_y - 1 - y;
+        bits[from_top / 4][x / 2] |= DOTS[x % 2][from_top % 4];
+    };
+    let points: Vec<(usize, usize)> = values
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| value.is_finite())
+        .map(|(i, value)| (x(i), y(*value)))
+        .collect();
+    if let Some(&(x0, y0)) = points.first() {
+        set(x0, y0);
+    }
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        let mut last = y0;
+        for x in x0..=x1 {
+            let t = if x1 > x0 {
+                (x - x0) as f64 / (x1 - x0) as f64
+            } else {
+                1.0
+            };
+            let y = (y0 as f64 + (y1 as f64 - y0 as f64) * t).round() as usize;
+            // Steep parts are filled so the line stays connected
+            for between in last.min(y)..=last.max(y) {
+                set(x, between);
+            }
+            last = y;
+        }
+    }
+    let cells = bits
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|bits| char::from_u32(0x2800 + bits).unwrap_or(' '))
+                .map(|c| if c == '\u{2800}' { ' ' } else { c })
+                .collect()
+        })
+        .collect();
+    (cells, (0..values.len()).map(|i| x(i) / 2).collect())
+}
+
+/// Columns each bar takes up including the gap after it
+fn bar_columns(columns: usize, count: usize) -> usize {
+    (columns / count.max(1)).max(1)
+}
+
+/// Block cells of a bar for every value, and the first column of every bar
+fn bars(
+    values: &[f64],
+    min: f64,
+    span: f64,
+    columns: usize,
+    height: usize,
+) -> (Vec<Vec<char>>, Vec<usize>) {
+    let mut cells = vec![vec![' '; columns]; height];
+    let mut positions = Vec::new();
+    let per_value = bar_columns(columns, values.len());
+    for (i, value) in values.iter().enumerate() {
+        let start = i * per_value;
+        positions.push(start);
+        // Bars are a column apart when there is room
+        let end = start + per_value - usize::from(per_value > 1);
+        let eighths = if value.is_finite() {
+            ((value - min) / span * (height * 8) as f64)
+                .round()
+                .max(0.0) as usize
+        } else {
+            0
+        };
+        for (row, line) in cells.iter_mut().enumerate() {
+            let level = (height - 1 - row) * 8;
+            let fill = eighths.saturating_sub(level).min(8);
+            for cell in &mut line[start..end] {
+                *cell = BLOCKS[fill];
+            }
+        }
+    }
+    (cells, positions)
+}
+
+impl Chart<'_> {
+    /// Time labels under the x axis, spaced so they don't touch, and the columns they start at
+    fn labels(&self, count: usize, positions: &[usize], columns: usize) -> (Vec<char>, Vec<usize>) {
+        let mut line = vec![' '; columns];
+        let mut ticks = Vec::new();
+        let mut place = |line: &mut Vec<char>, at: usize, text: &str| {
+            let end = at + text.chars().count();
+            let free = end <= columns
+                && line[at.saturating_sub(1)..(end + 1).min(columns)]
+                    .iter()
+                    .all(|c| *c == ' ');
+            if free {
+                line.splice(at..end, text.chars());
+                ticks.push(at);
+            }
+        };
+        if let Some(now) = self.now.filter(|now| *now < count) {
+            place(&mut line, positions[now], "now");
+        }
+        let widest = self
+            .labels
+            .iter()
+            .map(|label| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        // Enough values between labels to leave at least two columns free
+        let step = (widest + 2) * count / columns + 1;
+        for i in (0..count).step_by(step) {
+            if Some(i) != self.now {
+                if let Some(label) = self.labels.get(i) {
+                    place(&mut line, positions[i], label);
+                }
+            }
+        }
+        (line, ticks)
+    }
+
+    /// The chart in `width` columns with `height` rows for the values, the unit, the axes, the
+    /// labels and the min and max take 4 more lines
+    pub fn render(&self, width: usize, height: usize) -> Vec<String> {
+        let height = height.max(2);
+        let (min, max) = axis_range(self.values, self.style);
+        let span = max - min;
+        let axis = |row: usize| {
+            if row == 0 {
+                number(max, span)
+            } else if row == height - 1 {
+                number(min, span)
+            } else if height >= 5 && row == height / 2 {
+                number(min + span / 2.0, span)
+            } else {
+                String::new()
+            }
+        };
+        let axis_width = (0..height)
+            .map(|row| axis(row).len())
+            .chain([self.unit.chars().count()])
+            .max()
+            .unwrap_or(0);
+        let columns = width.saturating_sub(axis_width + 1).max(1);
+        let count = self.values.len().min(match self.style {
+            Style::Line => columns * 2,
+            Style::Bars => columns,
+        });
+        let values = &self.values[..count];
+        let (cells, positions) = match self.style {
+            Style::Line => line(values, min, span, columns, height),
+            Style::Bars => bars(values, min, span, columns, height),
+        };
+        // The max and min are colored, the column after the start of a bar is still the bar
+        let mut colors = vec![FORE_CYAN; columns];
+        let largest = extreme(values, true);
+        // A flat series only gets a max
+        let smallest = extreme(values, false).filter(|i| Some(*i) != largest);
+        for (index, color) in [(largest, FORE_RED), (smallest, FORE_BLUE)] {
+            if let Some(i) = index {
+                let end = match self.style {
+                    Style::Line => positions[i] + 1,
+                    Style::Bars => positions[i] + bar_columns(columns, count),
+                };
+                for column in &mut colors[positions[i]..end] {
+                    *column = color;
+                }
+            }
+        }
+        let mut lines = Vec::new();
+        if !self.unit.is_empty() {
+            lines.push(format!(
+                "{FORE_LIGHTBLACK}{:>axis_width$}{FORE_RESET}",
+                self.unit
+            ));
+        }
+        for (row, cells) in cells.iter().enumerate() {
+            let label = axis(row);
+            let tick = if label.is_empty() { '│' } else { '┤' };
+            let mut line = format!("{FORE_LIGHTBLACK}{label:>axis_width$}{tick}{FORE_RESET}");
+            let mut current = FORE_RESET;
+            for (cell, color) in cells.iter().zip(&colors) {
+                if *cell != ' ' && *color != current {
+                    line += color;
+                    current = color;
+                }
+                line.push(*cell);
+            }
+            lines.push(line + FORE_RESET);
+        }
+        let (labels, ticks) = self.labels(count, &positions, columns);
+        let now = self
+            .now
+            .filter(|now| *now < count)
+            .map(|now| positions[now]);
+        let mut axis_line = format!("{FORE_LIGHTBLACK}{}└", " ".repeat(axis_width));
+        for column in 0..columns {
+            axis_line += &match (ticks.contains(&column), Some(column) == now) {
+                (true, true) => format!("{FORE_YELLOW}┬{FORE_LIGHTBLACK}"),
+                (true, false) => "┬".to_string(),
+                _ => "─".to_string(),
+            };
+        }
+        lines.push(axis_line + FORE_RESET);
+        let mut label_line = " ".repeat(axis_width + 1);
+        match now.filter(|now| ticks.contains(now)) {
+            Some(now) => {
+                label_line += &labels[..now].iter().collect::<String>();
+                label_line += &format!("{FORE_YELLOW}now{FORE_RESET}");
+                label_line += &labels[now + 3..].iter().collect::<String>();
+            }
+            None => label_line += &labels.iter().collect::<String>(),
+        }
+        lines.push(label_line.trim_end().to_string());
+        let legend = |index: Option<usize>, marker: &str, name: &str| {
+            index.map(|i| {
+                let label = self.labels.get(i).map_or("", String::as_str);
+                format!(
+                    "{marker}{FORE_RESET} {name} {} {label}",
+                    with_unit(number(values[i], span), self.unit)
+                )
+            })
+        };
+        let extremes: Vec<String> = [
+            legend(largest, &format!("{FORE_RED}▲"), "max"),
+            legend(smallest, &format!("{FORE_BLUE}▼"), "min"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        lines.push(truncate(&extremes.join("   "), width));
+        lines
+    }
+}
@@ -1,3 +1,11 @@
+use std::env;
+use std::io;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crossterm::tty::IsTty;
+
+use crate::text::split_ansi;
+
 pub const RESET: &str = "\x1b[0m";
 pub const FORE_BLACK: &str = "\x1b[30m";
 pub const FORE_RED: &str = "\x1b[31m";
@@ -39,6 +47,10 @@ pub fn rgb(red: u8, green: u8, blue: u8) -> String {
     format!("\x1b[38;2;{red};{green};{blue}m")
 }
 
+pub fn rgb_back(red: u8, green: u8, blue: u8) -> String {
+    format!("\x1b[48;2;{red};{green};{blue}m")
+}
+
 pub fn string_to_rgb(s: String) -> Option<String> {
     // TODO: This is Foreground only
     let split: Vec<&str> = s.split(',').collect();
@@ -92,3 +104,200 @@ pub fn from_string(s: String) -> Option<String> {
         s => string_to_rgb(s.to_string()),
     }
 }
+
+/// Colors a terminal can show, from least to most
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    None,
+    /// The 16 standard colors
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors are used when the output is a terminal that supports them
+    Auto,
+    Always,
+    Never,
+}
+
+static COLOR_MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_color_mode(mode: ColorMode) {
+    COLOR_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Colors the terminal supports according to `COLORTERM` and `TERM`
+fn detect_from_env() -> ColorSupport {
+    let colorterm = env::var("COLORTERM").unwrap_or_default().to_lowercase();
+    let term = env::var("TERM").unwrap_or_default().to_lowercase();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        ColorSupport::TrueColor
+    } else if term.contains("256color") {
+        ColorSupport::Ansi256
+    } else if term == "dumb" {
+        ColorSupport::None
+    } else if term.is_empty() && cfg!(windows) {
+        // Windows terminals don't set TERM but support all colors
+        ColorSupport::TrueColor
+    } else {
+        ColorSupport::Basic
+    }
+}
+
+/// Colors that can be used for standard output, see https://no-color.org for `NO_COLOR`
+pub fn color_support() -> ColorSupport {
+    match COLOR_MODE.load(Ordering::Relaxed) {
+        2 => ColorSupport::None,
+        1 => detect_from_env().max(ColorSupport::Basic),
+        _ => {
+            if env::var("NO_COLOR").map_or(false, |v| !v.is_empty()) || !io::stdout().is_tty() {
+                ColorSupport::None
+            } else {
+                detect_from_env()
+            }
+        }
+    }
+}
+
+/// The standard colors as they are usually shown, in the order of their codes
+const BASIC_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn ansi256_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => BASIC_COLORS[usize::from(n)],
+        16..=231 => {
+            let n = usize::from(n - 16);
+            (
+                CUBE_LEVELS[n / 36],
+                CUBE_LEVELS[n / 6 % 6],
+                CUBE_LEVELS[n % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (n - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn rgb_to_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let nearest_level = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (i32::from(CUBE_LEVELS[i]) - i32::from(c)).abs())
+            .unwrap_or(0)
+    };
+    let cube = 16 + 36 * nearest_level(rgb.0) + 6 * nearest_level(rgb.1) + nearest_level(rgb.2);
+    let average = (u32::from(rgb.0) + u32::from(rgb.1) + u32::from(rgb.2)) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as usize;
+    // Grays are often closer to the gray ramp than to the cube
+    [cube, gray]
+        .into_iter()
+        .min_by_key(|&n| distance(rgb, ansi256_to_rgb(n as u8)))
+        .unwrap_or(cube) as u8
+}
+
+fn rgb_to_basic(rgb: (u8, u8, u8)) -> u8 {
+    (0..16)
+        .min_by_key(|&i| distance(rgb, BASIC_COLORS[usize::from(i)]))
+        .unwrap_or(0)
+}
+
+/// Rewrites the parameters of a color escape sequence (`ESC[...m`) for `support`
+fn downsample_sgr(params: &str, support: ColorSupport) -> String {
+    let params: Vec<&str> = params.split(';').collect();
+    let mut result: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < params.len() {
+        let param = params[i];
+        let number = |j: usize| params.get(j).and_then(|p| p.parse::<u8>().ok());
+        let color = match (param, params.get(i + 1)) {
+            ("38" | "48", Some(&"2")) => {
+                let rgb = (number(i + 2), number(i + 3), number(i + 4));
+                i += 5;
+                match rgb {
+                    (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+                    _ => continue,
+                }
+            }
+            ("38" | "48", Some(&"5")) => {
+                let n = number(i + 2);
+                i += 3;
+                match n {
+                    Some(n) if support == ColorSupport::Ansi256 => {
+                        result.push(format!("{param};5;{n}"));
+                        None
+                    }
+                    Some(n) => Some(ansi256_to_rgb(n)),
+                    None => continue,
+                }
+            }
+            _ => {
+                result.push(param.to_string());
+                i += 1;
+                None
+            }
+        };
+        if let Some(rgb) = color {
+            let background = param == "48";
+            result.push(match support {
+                ColorSupport::TrueColor => format!("{param};2;{};{};{}", rgb.0, rgb.1, rgb.2),
+                ColorSupport::Ansi256 => format!("{param};5;{}", rgb_to_ansi256(rgb)),
+                _ => {
+                    let n = rgb_to_basic(rgb);
+                    let base = if n < 8 { 30 + n } else { 90 + n - 8 };
+                    (base + if background { 10 } else { 0 }).to_string()
+                }
+            });
+        }
+    }
+    format!("\x1b[{}m", result.join(";"))
+}
+
+/// Rewrites the colors in text for the colors the terminal supports, without color support
+/// they are removed
+pub fn downsample(s: &str) -> String {
+    let support = color_support();
+    if support == ColorSupport::TrueColor {
+        return s.to_string();
+    }
+    let mut result = String::new();
+    for (escape, part) in split_ansi(s) {
+        let sgr = part
+            .strip_prefix("\x1b[")
+            .and_then(|p| p.strip_suffix('m'))
+            .filter(|_| escape);
+        match sgr {
+            Some(_) if support == ColorSupport::None => {}
+            Some(params) => result += &downsample_sgr(params, support),
+            None => result += part,
+        }
+    }
+    result
+}
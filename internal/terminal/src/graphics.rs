@@ -0,0 +1,58 @@
+//! Finds out how images can be shown in the terminal
+
+use std::env;
+
+use crate::color::{color_support, ColorSupport};
+
+/// Ways a terminal can show images
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Graphics {
+    /// https://sw.kovidgoyal.net/kitty/graphics-protocol
+    Kitty,
+    /// https://iterm2.com/documentation-images.html, also supported by WezTerm
+    Iterm2,
+    Sixel,
+    /// Colored `▀` characters, two pixels per character
+    HalfBlocks,
+}
+
+impl Graphics {
+    /// Parses `kitty`, `iterm2`, `sixel` or `blocks`
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "kitty" => Some(Self::Kitty),
+            "iterm2" => Some(Self::Iterm2),
+            "sixel" => Some(Self::Sixel),
+            "blocks" => Some(Self::HalfBlocks),
+            _ => None,
+        }
+    }
+}
+
+/// The best way to show images according to the environment, half blocks if the terminal
+/// isn't known to support a graphics protocol or colors are off
+pub fn detect() -> Graphics {
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    let program = var("TERM_PROGRAM");
+    // tmux and screen don't pass graphics through
+    if color_support() == ColorSupport::None
+        || !var("TMUX").is_empty()
+        || term.starts_with("screen")
+    {
+        Graphics::HalfBlocks
+    } else if term == "xterm-kitty" || !var("KITTY_WINDOW_ID").is_empty() || program == "ghostty" {
+        Graphics::Kitty
+    } else if program == "iTerm.app" || program == "WezTerm" || var("LC_TERMINAL") == "iTerm2" {
+        Graphics::Iterm2
+    } else if term.contains("sixel")
+        || ["foot", "mlterm", "contour"]
+            .iter()
+            .any(|sixel_term| term.starts_with(sixel_term))
+        || !var("WT_SESSION").is_empty()
+    {
+        Graphics::Sixel
+    } else {
+        Graphics::HalfBlocks
+    }
+}
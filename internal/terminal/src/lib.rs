@@ -1,2 +1,6 @@
+pub mod chart;
 pub mod color;
+pub mod graphics;
 pub mod prompt;
+pub mod screen;
+pub mod text;
@@ -1,4 +1,4 @@
-use crate::color::{FORE_BLUE, FORE_GREEN, FORE_LIGHTMAGENTA, FORE_RESET};
+use crate::color::{downsample, FORE_BLUE, FORE_GREEN, FORE_LIGHTMAGENTA, FORE_RESET};
 use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use std::io::Write;
@@ -24,7 +24,12 @@ pub fn input(prompt: Option<String>, default: Option<String>) -> io::Result<Stri
     loop {
         print!("\x1b[2K");
         print!("\x1b[1000D");
-        print!("{FORE_LIGHTMAGENTA}{real_prompt}{FORE_BLUE}{choice}");
+        print!(
+            "{}",
+            downsample(&format!(
+                "{FORE_LIGHTMAGENTA}{real_prompt}{FORE_BLUE}{choice}"
+            ))
+        );
         print!("\x1b[1000D");
         print!("\x1b[{}C", cursor_position + real_prompt.len());
         io::stdout().flush()?;
@@ -61,7 +66,7 @@ pub fn input(prompt: Option<String>, default: Option<String>) -> io::Result<Stri
                 ..
             }) => {
                 disable_raw_mode()?;
-                println!("{FORE_RESET}");
+                println!("{}", downsample(FORE_RESET));
                 panic!("Control-C pressed");
             }
             Event::Key(KeyEvent {
@@ -98,7 +103,12 @@ pub fn input(prompt: Option<String>, default: Option<String>) -> io::Result<Stri
     }
     print!("\x1b[2K");
     print!("\x1b[1000D");
-    println!("{FORE_LIGHTMAGENTA}{real_prompt}{FORE_GREEN}{choice}{FORE_RESET}");
+    println!(
+        "{}",
+        downsample(&format!(
+            "{FORE_LIGHTMAGENTA}{real_prompt}{FORE_GREEN}{choice}{FORE_RESET}"
+        ))
+    );
     // disabling raw mode
     disable_raw_mode()?;
     Ok(choice)
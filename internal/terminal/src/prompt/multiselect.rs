@@ -1,4 +1,6 @@
-use crate::color::{FORE_BLUE, FORE_GREEN, FORE_LIGHTMAGENTA};
+use crate::color::{
+    color_support, downsample, ColorSupport, FORE_BLUE, FORE_GREEN, FORE_LIGHTMAGENTA,
+};
 use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use std::io::Write;
@@ -13,6 +15,15 @@ pub fn draw<S: AsRef<str>>(
     multiline: bool,
 ) -> String {
     let mut result = String::new();
+    // Without colors selected options are shown in brackets
+    let plain = color_support() == ColorSupport::None;
+    let option_text = |count: usize, option: &str| {
+        if plain && selected[count] {
+            format!("[{option}]")
+        } else {
+            option.to_string()
+        }
+    };
     if multiline {
         for (count, option) in options.iter().enumerate() {
             if count == highlighted {
@@ -26,7 +37,7 @@ pub fn draw<S: AsRef<str>>(
             } else {
                 result += FORE_BLUE;
             }
-            result += option.as_ref();
+            result += &option_text(count, option.as_ref());
             result += "\n";
         }
     } else {
@@ -36,11 +47,11 @@ pub fn draw<S: AsRef<str>>(
             } else {
                 result += FORE_BLUE;
             }
-            result += option.as_ref();
+            result += &option_text(count, option.as_ref());
             result += " ";
         }
     }
-    result
+    downsample(&result)
 }
 
 pub fn multiselect<S: AsRef<str>>(
@@ -48,7 +59,11 @@ pub fn multiselect<S: AsRef<str>>(
     defaults: &[bool],
     multiline: Option<bool>,
 ) -> io::Result<Vec<bool>> {
-    println!("Press ctrl-q when finished, red is not selected and green is selected");
+    if color_support() == ColorSupport::None {
+        println!("Press ctrl-q when finished, selected options are in brackets");
+    } else {
+        println!("Press ctrl-q when finished, blue is not selected and green is selected");
+    }
     let multiline_standard = multiline.unwrap_or(true);
     thread::sleep(Duration::from_millis(100));
     read()?;
@@ -0,0 +1,67 @@
+//! Full screen applications, drawn on the alternate screen in raw mode
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{poll, read, Event, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, size, EnterAlternateScreen, LeaveAlternateScreen,
+};
+
+pub use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::color::RESET;
+
+/// The alternate screen, the terminal is restored when it is dropped
+pub struct Screen;
+
+impl Screen {
+    pub fn new() -> io::Result<Self> {
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Self)
+    }
+
+    /// Columns and rows of the terminal
+    pub fn size(&self) -> (usize, usize) {
+        size().map_or((80, 24), |(columns, rows)| {
+            (usize::from(columns), usize::from(rows))
+        })
+    }
+
+    /// Replaces what is shown with `lines`, which should fit the screen
+    pub fn draw(&self, lines: &[String]) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        write!(stdout, "\x1b[H")?;
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                write!(stdout, "\r\n")?;
+            }
+            // Clears the rest of the line, which can still have the last frame
+            write!(stdout, "{line}{RESET}\x1b[K")?;
+        }
+        write!(stdout, "\x1b[J")?;
+        stdout.flush()
+    }
+
+    /// Waits up to `timeout` for a key, `None` if there was none or the terminal was resized
+    pub fn key(&self, timeout: Duration) -> io::Result<Option<KeyEvent>> {
+        if !poll(timeout)? {
+            return Ok(None);
+        }
+        Ok(match read()? {
+            // Windows also reports releasing keys
+            Event::Key(key) if key.kind != KeyEventKind::Release => Some(key),
+            _ => None,
+        })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        execute!(io::stdout(), Show, LeaveAlternateScreen).unwrap_or(());
+        disable_raw_mode().unwrap_or(());
+    }
+}
@@ -0,0 +1,141 @@
+//! Measures text the way a terminal shows it
+
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+impl Align {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "center" => Some(Self::Center),
+            _ => None,
+        }
+    }
+}
+
+/// Length of the escape sequence at the start of `s`, `s` has to start with ESC
+fn escape_length(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        // CSI sequences like colors end with a byte in @..~
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        // OSC, DCS and APC sequences (links and images) end with BEL or ESC \
+        Some(b']' | b'P' | b'_') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                if bytes[i] == 0x07 {
+                    return i + 1;
+                } else if bytes[i] == 0x1b && bytes.get(i + 1) == Some(&b'\\') {
+                    return i + 2;
+                }
+                i += 1;
+            }
+            bytes.len()
+        }
+        Some(_) => 1 + s[1..].chars().next().map_or(0, char::len_utf8),
+        None => 1,
+    }
+}
+
+/// Splits text into escape sequences (`true`) and the text between them (`false`)
+pub(crate) fn split_ansi(s: &str) -> Vec<(bool, &str)> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        match rest.find('\x1b') {
+            Some(0) => {
+                let (escape, after) = rest.split_at(escape_length(rest));
+                parts.push((true, escape));
+                rest = after;
+            }
+            Some(i) => {
+                parts.push((false, &rest[..i]));
+                rest = &rest[i..];
+            }
+            None => {
+                parts.push((false, rest));
+                rest = "";
+            }
+        }
+    }
+    parts
+}
+
+/// Removes escape sequences like colors
+pub fn strip_ansi(s: &str) -> String {
+    split_ansi(s)
+        .into_iter()
+        .filter(|(escape, _)| !escape)
+        .map(|(_, text)| text)
+        .collect()
+}
+
+/// Number of terminal columns text takes up, wide characters like emoji take up two
+pub fn display_width(s: &str) -> usize {
+    split_ansi(s)
+        .into_iter()
+        .filter(|(escape, _)| !escape)
+        .flat_map(|(_, text)| text.chars())
+        .map(|c| c.width().unwrap_or(0))
+        .sum()
+}
+
+/// Cuts text to at most `width` columns, keeping its escape sequences
+pub fn truncate(s: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut used = 0;
+    for (escape, part) in split_ansi(s) {
+        if escape {
+            result += part;
+            continue;
+        }
+        for c in part.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if used + char_width > width {
+                return result;
+            }
+            used += char_width;
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Pads text with `fill` to `width` columns, text that is too wide is cut
+pub fn pad(s: &str, width: usize, align: Align, fill: char) -> String {
+    let s = if display_width(s) > width {
+        truncate(s, width)
+    } else {
+        s.to_string()
+    };
+    let fill_width = fill.width().unwrap_or(1).max(1);
+    let padding = (width - display_width(&s)) / fill_width;
+    let (left, right) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    let fill = fill.to_string();
+    format!("{}{s}{}", fill.repeat(left), fill.repeat(right))
+}
+
+/// Width of the terminal in columns, `COLUMNS` or 80 when it isn't known, like when the output is
+/// piped
+pub fn terminal_width() -> usize {
+    crossterm::terminal::size()
+        .ok()
+        .map(|(columns, _)| usize::from(columns))
+        .filter(|columns| *columns > 0)
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(80)
+}
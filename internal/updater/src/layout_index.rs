@@ -0,0 +1,55 @@
+//! The layout gallery, `layouts/index.json` on the update server lists the layouts that can be
+//! installed by name
+
+use serde::{Deserialize, Serialize};
+
+/// A layout in the gallery
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LayoutEntry {
+    pub name: String,
+    /// File name in `layouts/` on the update server, it is installed with the same name
+    pub file: String,
+    pub description: String,
+    /// The layout shown with a sample forecast, without colors
+    pub preview: String,
+    /// sha-256 of the layout file
+    pub hash: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct LayoutIndex {
+    layouts: Vec<LayoutEntry>,
+}
+
+/// Downloads the list of layouts in the gallery
+pub fn get_layout_index(server: &str) -> crate::Result<Vec<LayoutEntry>> {
+    let resp = networking::get_url(format!("{server}layouts/index.json"), None, None, None)?;
+    if resp.status != 200 {
+        return Err(weather_error::Error::NetworkError(format!(
+            "Failed to get the layout index, status {}",
+            resp.status
+        )));
+    }
+    let index: LayoutIndex = serde_json::from_str(&resp.text)?;
+    Ok(index.layouts)
+}
+
+/// Downloads a layout of the gallery, failing if it does not match the hash in the index
+pub fn download_layout(server: &str, entry: &LayoutEntry) -> crate::Result<Vec<u8>> {
+    let url = format!("{server}layouts/{}", entry.file);
+    let resp = networking::get_url(url, None, None, None)?;
+    if resp.status != 200 {
+        return Err(weather_error::Error::NetworkError(format!(
+            "Failed to download {}, status {}",
+            entry.name, resp.status
+        )));
+    }
+    let data = resp.bytes;
+    if local::hash(&data) != entry.hash {
+        return Err(format!(
+            "The download of {} does not match the hash in the layout index",
+            entry.name
+        ))?;
+    }
+    Ok(data)
+}
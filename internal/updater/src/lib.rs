@@ -1,4 +1,5 @@
 pub mod component;
+pub mod layout_index;
 pub mod resource;
 mod update_server_json;
 
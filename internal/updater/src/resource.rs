@@ -57,15 +57,16 @@ fn update_web_resource(
             resource.hash_name
         );
         if !quiet {
-            if f.exists {
-                println!(
+            let message = if f.exists {
+                format!(
                     "{}Downloading update for {}",
                     color::FORE_YELLOW,
                     resource.pretty_name
-                );
+                )
             } else {
-                println!("{}Downloading {}", color::FORE_YELLOW, resource.pretty_name);
-            }
+                format!("{}Downloading {}", color::FORE_YELLOW, resource.pretty_name)
+            };
+            println!("{}", color::downsample(&message));
         }
         let data = networking::get_url(web_path, None, None, None)?.text;
         f.data = Vec::from(data);
@@ -11,6 +11,10 @@ pub fn layouts_dir() -> crate::Result<PathBuf> {
     Ok(weathercli_dir()?.join("layouts"))
 }
 
+pub fn themes_dir() -> crate::Result<PathBuf> {
+    Ok(weathercli_dir()?.join("themes"))
+}
+
 pub fn custom_backends_dir() -> crate::Result<PathBuf> {
     Ok(weathercli_dir()?.join("custom_backends"))
 }
@@ -26,6 +26,9 @@ pub struct LayoutErr {
     pub message: String,
     pub row: Option<u64>,
     pub item: Option<u64>,
+    /// Location in the layout source, for errors found while parsing it
+    pub line: Option<u64>,
+    pub column: Option<u64>,
 }
 
 // Generation of an error is completely separate from how it is displayed.
@@ -35,6 +38,9 @@ pub struct LayoutErr {
 // which string failed to parse without modifying our types to carry that information.
 impl fmt::Display for LayoutErr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            return write!(f, "Error at line {line}, column {column}: {}", self.message);
+        }
         match self.row {
             Some(row) => match self.item {
                 Some(item) => write!(f, "Error at row {}, item {}: {}", row, item, self.message),
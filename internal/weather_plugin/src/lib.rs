@@ -6,5 +6,6 @@ pub use local::now;
 pub use local::settings;
 pub use local::weather_file;
 pub use backend::{WeatherData, WeatherForecast, WeatherCondition, WindData};
+pub use backend::{get_conditions_sentence, get_forecast_sentence};
 
 pub type Result<T> = std::result::Result<T, weather_error::Error>;
@@ -12,7 +12,8 @@ use log::LevelFilter;
 use cli::{Datasource, datasource_from_str};
 use cli::arguments::{App, Command};
 use cli::commands::{
-    backend_commands, cache, credits, layout_commands, open_settings_app, settings, weather,
+    backend_commands, cache, credits, graph, history, layout_commands, open_settings_app,
+    settings, theme_commands, tui, weather,
 };
 use cli::commands::util::{setup, update};
 use custom_backend::dynamic_library_loader::ExternalBackends;
@@ -72,13 +73,14 @@ fn main() {
     match r {
         Ok(()) => {}
         Err(e) => {
-            println!("{}{e}", color::FORE_RED);
+            println!("{}", color::downsample(&format!("{}{e}", color::FORE_RED)));
         }
     };
 }
 
 fn run() -> crate::Result<()> {
     let args = App::parse();
+    color::set_color_mode(args.global_opts.color.into());
     let settings_s = Settings::new()?;
     if settings_s.debug || args.global_opts.debug {
         let _handle = init_logging();
@@ -90,17 +92,17 @@ fn run() -> crate::Result<()> {
     } else {
         settings_s.metric_default
     };
-    let datasource = datasource_from_str(
-        &args
-            .global_opts
-            .datasource
-            .unwrap_or_else(|| settings_s.default_backend.clone()),
-    );
+    let backend_name = args
+        .global_opts
+        .datasource
+        .clone()
+        .unwrap_or_else(|| settings_s.default_backend.clone());
+    let datasource = datasource_from_str(&backend_name);
     let custom_backends = if settings_s.enable_custom_backends
         && discriminant(&datasource) == discriminant(&Datasource::Other(String::new()))
-        && custom_backends_dir()?.exists()
+        && (custom_backends_dir()?.exists() || !settings_s.script_backends.is_empty())
     {
-        load_custom_backends()?
+        load_custom_backends(&settings_s)?
     } else {
         ExternalBackends::default()
     };
@@ -112,7 +114,7 @@ fn run() -> crate::Result<()> {
                     local::location::geocode(opts.query, settings_s.bing_maps_api_key.clone())?,
                     settings_s,
                     true_metric,
-                    args.global_opts.json,
+                    args.global_opts.output(),
                     custom_backends,
                 )?,
                 Command::Backend(arg) => backend_commands::subcommand(arg, settings_s)?,
@@ -121,8 +123,39 @@ fn run() -> crate::Result<()> {
                 Command::Credits => credits(),
                 Command::Settings => settings()?,
                 Command::GuiSettings => open_settings_app(),
-                Command::Layout(arg) => layout_commands::subcommand(arg, settings_s)?,
+                Command::Graph(opts) => graph(
+                    datasource,
+                    local::location::get(
+                        args.global_opts.no_sys_loc,
+                        settings_s.constant_location,
+                    )?,
+                    settings_s,
+                    true_metric,
+                    opts,
+                    &custom_backends,
+                )?,
+                Command::Layout(arg) => {
+                    layout_commands::subcommand(arg, settings_s, true_metric)?
+                }
                 Command::Setup => setup(settings_s)?,
+                Command::Theme(arg) => theme_commands::subcommand(arg, settings_s)?,
+                Command::Tui(opts) => tui::tui(
+                    opts,
+                    settings_s,
+                    backend_name,
+                    true_metric,
+                    args.global_opts.no_sys_loc,
+                    &custom_backends,
+                )?,
+                Command::History(opts) => history(
+                    local::location::get(
+                        args.global_opts.no_sys_loc,
+                        settings_s.constant_location,
+                    )?,
+                    settings_s,
+                    true_metric,
+                    opts,
+                )?,
                 Command::Update(opts) => update(opts.force, version())?,
             };
         }
@@ -131,7 +164,7 @@ fn run() -> crate::Result<()> {
             local::location::get(args.global_opts.no_sys_loc, settings_s.constant_location)?,
             settings_s,
             true_metric,
-            args.global_opts.json,
+            args.global_opts.output(),
             custom_backends,
         )?,
     };
@@ -76,7 +76,7 @@ fn main() -> CResult<()> {
                     datasource_from_str(default_datasource),
                     local::location::get(false, settings.constant_location)?,
                     settings.clone(),
-                    ExternalBackends::default(),
+                    &ExternalBackends::default(),
                 )?;
                 let bytes = bincode::serialize(&data).expect("Serialization Failed");
                 let out = WeatherFile::new("d.cache")?;